@@ -1,11 +1,29 @@
 pub mod create;
+pub mod delete_by_info;
 pub mod get_by_id;
 pub mod get_by_info;
 pub mod get_by_info_unviewed_by_user;
 pub mod get_by_url;
+pub mod get_canonical;
+pub mod get_page;
+pub mod get_to_check;
+pub mod get_to_hash;
+pub mod get_to_validate;
+pub mod mark_checked;
+pub mod set_file_info;
+pub mod set_hashes;
 
 pub use create::CreateMedia;
+pub use delete_by_info::DeleteMediaByInfo;
 pub use get_by_id::GetMediaById;
 pub use get_by_info::GetMediaByInfo;
 pub use get_by_info_unviewed_by_user::GetMediaByInfoUnviewedByUser;
 pub use get_by_url::GetMediaByUrl;
+pub use get_canonical::GetCanonicalMedia;
+pub use get_page::GetMediaPage;
+pub use get_to_check::GetMediaToCheck;
+pub use get_to_hash::GetMediaToHash;
+pub use get_to_validate::GetMediaToValidate;
+pub use mark_checked::MarkMediaChecked;
+pub use set_file_info::SetMediaFileInfo;
+pub use set_hashes::SetMediaHashes;
@@ -7,7 +7,7 @@ pub struct CreateMedia<'a> {
     url: &'a str,
     genre: Option<&'a str>,
     media_type: &'a str,
-    is_sfw: Option<bool>,
+    age_restriction: &'a str,
     source_id: &'a Uuid,
 }
 
@@ -17,7 +17,7 @@ impl<'a> CreateMedia<'a> {
         url: &'a str,
         genre: Option<&'a str>,
         media_type: &'a str,
-        is_sfw: Option<bool>,
+        age_restriction: &'a str,
         source_id: &'a Uuid,
     ) -> Self {
         Self {
@@ -25,7 +25,7 @@ impl<'a> CreateMedia<'a> {
             url,
             genre,
             media_type,
-            is_sfw,
+            age_restriction,
             source_id,
         }
     }
@@ -46,8 +46,8 @@ impl<'a> CreateMedia<'a> {
         self.media_type
     }
 
-    pub const fn is_sfw(&self) -> Option<bool> {
-        self.is_sfw
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
     }
 
     pub const fn source_id(&self) -> &Uuid {
@@ -0,0 +1,32 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteMediaByInfo<'a> {
+    genre: Option<&'a str>,
+    media_type: &'a str,
+    age_restriction: &'a str,
+}
+
+impl<'a> DeleteMediaByInfo<'a> {
+    pub const fn new(
+        genre: Option<&'a str>,
+        media_type: &'a str,
+        age_restriction: &'a str,
+    ) -> Self {
+        Self {
+            genre,
+            media_type,
+            age_restriction,
+        }
+    }
+
+    pub const fn genre(&self) -> Option<&str> {
+        self.genre
+    }
+
+    pub const fn media_type(&self) -> &str {
+        self.media_type
+    }
+
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
+    }
+}
@@ -2,7 +2,7 @@
 pub struct GetMediaByInfo<'a> {
     genre: Option<&'a str>,
     media_type: &'a str,
-    is_sfw: Option<bool>,
+    age_restriction: &'a str,
     offset: Option<u64>,
     limit: Option<u64>,
 }
@@ -11,14 +11,14 @@ impl<'a> GetMediaByInfo<'a> {
     pub const fn new(
         genre: Option<&'a str>,
         media_type: &'a str,
-        is_sfw: Option<bool>,
+        age_restriction: &'a str,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Self {
         Self {
             genre,
             media_type,
-            is_sfw,
+            age_restriction,
             offset,
             limit,
         }
@@ -32,8 +32,8 @@ impl<'a> GetMediaByInfo<'a> {
         self.media_type
     }
 
-    pub const fn is_sfw(&self) -> Option<bool> {
-        self.is_sfw
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
     }
 
     pub const fn offset(&self) -> Option<u64> {
@@ -5,7 +5,7 @@ pub struct GetMediaByInfoUnviewedByUser<'a> {
     user_id: &'a Uuid,
     genre: Option<&'a str>,
     media_type: &'a str,
-    is_sfw: Option<bool>,
+    age_restriction: &'a str,
     offset: Option<u64>,
     limit: Option<u64>,
 }
@@ -15,7 +15,7 @@ impl<'a> GetMediaByInfoUnviewedByUser<'a> {
         user_id: &'a Uuid,
         genre: Option<&'a str>,
         media_type: &'a str,
-        is_sfw: Option<bool>,
+        age_restriction: &'a str,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Self {
@@ -23,7 +23,7 @@ impl<'a> GetMediaByInfoUnviewedByUser<'a> {
             user_id,
             genre,
             media_type,
-            is_sfw,
+            age_restriction,
             offset,
             limit,
         }
@@ -41,8 +41,8 @@ impl<'a> GetMediaByInfoUnviewedByUser<'a> {
         self.media_type
     }
 
-    pub const fn is_sfw(&self) -> Option<bool> {
-        self.is_sfw
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
     }
 
     pub const fn offset(&self) -> Option<u64> {
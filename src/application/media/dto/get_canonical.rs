@@ -0,0 +1,59 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetCanonicalMedia<'a> {
+    id: &'a Uuid,
+    genre: Option<&'a str>,
+    media_type: &'a str,
+    content_hash: &'a str,
+    perceptual_hash: Option<i64>,
+    max_distance: u32,
+}
+
+impl<'a> GetCanonicalMedia<'a> {
+    /// # Arguments
+    /// * `id` - Id of the media, which duplicates are searched for. It isn't returned itself.
+    /// * `perceptual_hash` - If `None`, only media with the same content hash are duplicates
+    /// * `max_distance` - Max count of different bits of perceptual hashes of duplicates
+    pub const fn new(
+        id: &'a Uuid,
+        genre: Option<&'a str>,
+        media_type: &'a str,
+        content_hash: &'a str,
+        perceptual_hash: Option<i64>,
+        max_distance: u32,
+    ) -> Self {
+        Self {
+            id,
+            genre,
+            media_type,
+            content_hash,
+            perceptual_hash,
+            max_distance,
+        }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn genre(&self) -> Option<&str> {
+        self.genre
+    }
+
+    pub const fn media_type(&self) -> &str {
+        self.media_type
+    }
+
+    pub const fn content_hash(&self) -> &str {
+        self.content_hash
+    }
+
+    pub const fn perceptual_hash(&self) -> Option<i64> {
+        self.perceptual_hash
+    }
+
+    pub const fn max_distance(&self) -> u32 {
+        self.max_distance
+    }
+}
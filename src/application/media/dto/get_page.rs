@@ -0,0 +1,21 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetMediaPage<'a> {
+    after_id: Option<&'a Uuid>,
+    limit: u64,
+}
+
+impl<'a> GetMediaPage<'a> {
+    pub const fn new(after_id: Option<&'a Uuid>, limit: u64) -> Self {
+        Self { after_id, limit }
+    }
+
+    pub const fn after_id(&self) -> Option<&Uuid> {
+        self.after_id
+    }
+
+    pub const fn limit(&self) -> u64 {
+        self.limit
+    }
+}
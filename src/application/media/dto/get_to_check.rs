@@ -0,0 +1,24 @@
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetMediaToCheck {
+    checked_before: OffsetDateTime,
+    limit: u64,
+}
+
+impl GetMediaToCheck {
+    pub const fn new(checked_before: OffsetDateTime, limit: u64) -> Self {
+        Self {
+            checked_before,
+            limit,
+        }
+    }
+
+    pub const fn checked_before(&self) -> OffsetDateTime {
+        self.checked_before
+    }
+
+    pub const fn limit(&self) -> u64 {
+        self.limit
+    }
+}
@@ -0,0 +1,14 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetMediaToHash {
+    limit: u64,
+}
+
+impl GetMediaToHash {
+    pub const fn new(limit: u64) -> Self {
+        Self { limit }
+    }
+
+    pub const fn limit(&self) -> u64 {
+        self.limit
+    }
+}
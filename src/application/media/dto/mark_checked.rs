@@ -0,0 +1,23 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkMediaChecked<'a> {
+    id: &'a Uuid,
+    status: Option<&'a str>,
+}
+
+impl<'a> MarkMediaChecked<'a> {
+    /// # Arguments
+    /// * `status` - New status of the media. If `None`, the status isn't changed, for example, if the check is inconclusive.
+    pub const fn new(id: &'a Uuid, status: Option<&'a str>) -> Self {
+        Self { id, status }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn status(&self) -> Option<&str> {
+        self.status
+    }
+}
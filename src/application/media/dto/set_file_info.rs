@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetMediaFileInfo<'a> {
+    id: &'a Uuid,
+    media_type: Option<&'a str>,
+    status: Option<&'a str>,
+    mime: Option<&'a str>,
+    size: Option<i64>,
+    width: Option<i32>,
+    height: Option<i32>,
+}
+
+impl<'a> SetMediaFileInfo<'a> {
+    /// # Arguments
+    /// * `media_type` - New type of the media, if the content doesn't match the current one. If `None`, the type isn't changed.
+    /// * `status` - New status of the media. If `None`, the status isn't changed.
+    pub const fn new(
+        id: &'a Uuid,
+        media_type: Option<&'a str>,
+        status: Option<&'a str>,
+        mime: Option<&'a str>,
+        size: Option<i64>,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Self {
+        Self {
+            id,
+            media_type,
+            status,
+            mime,
+            size,
+            width,
+            height,
+        }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn media_type(&self) -> Option<&str> {
+        self.media_type
+    }
+
+    pub const fn status(&self) -> Option<&str> {
+        self.status
+    }
+
+    pub const fn mime(&self) -> Option<&str> {
+        self.mime
+    }
+
+    pub const fn size(&self) -> Option<i64> {
+        self.size
+    }
+
+    pub const fn width(&self) -> Option<i32> {
+        self.width
+    }
+
+    pub const fn height(&self) -> Option<i32> {
+        self.height
+    }
+}
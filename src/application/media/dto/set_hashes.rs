@@ -0,0 +1,45 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetMediaHashes<'a> {
+    id: &'a Uuid,
+    content_hash: Option<&'a str>,
+    perceptual_hash: Option<i64>,
+    canonical_id: Option<&'a Uuid>,
+}
+
+impl<'a> SetMediaHashes<'a> {
+    /// # Arguments
+    /// * `content_hash` - If `None`, the media can't be downloaded and isn't deduplicated
+    /// * `perceptual_hash` - If `None`, the media can't be decoded as image
+    /// * `canonical_id` - Id of the media, which the media duplicates
+    pub const fn new(
+        id: &'a Uuid,
+        content_hash: Option<&'a str>,
+        perceptual_hash: Option<i64>,
+        canonical_id: Option<&'a Uuid>,
+    ) -> Self {
+        Self {
+            id,
+            content_hash,
+            perceptual_hash,
+            canonical_id,
+        }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn content_hash(&self) -> Option<&str> {
+        self.content_hash
+    }
+
+    pub const fn perceptual_hash(&self) -> Option<i64> {
+        self.perceptual_hash
+    }
+
+    pub const fn canonical_id(&self) -> Option<&Uuid> {
+        self.canonical_id
+    }
+}
@@ -2,7 +2,10 @@ use crate::{
     application::{
         common::exceptions::{RepoError, RepoKind},
         media::{
-            dto::{GetMediaById, GetMediaByInfo, GetMediaByInfoUnviewedByUser, GetMediaByUrl},
+            dto::{
+                GetCanonicalMedia, GetMediaById, GetMediaByInfo, GetMediaByInfoUnviewedByUser,
+                GetMediaByUrl, GetMediaPage, GetMediaToCheck, GetMediaToHash, GetMediaToValidate,
+            },
             exceptions::MediaIdNotExist,
         },
     },
@@ -34,6 +37,32 @@ pub trait MediaReader {
         media: GetMediaByInfoUnviewedByUser<'s>,
     ) -> Result<Vec<MediaEntity>, RepoError>;
 
+    /// Returns media ordered by id, which go after the media with `after_id`
+    async fn get_page<'s>(
+        &mut self,
+        media: GetMediaPage<'s>,
+    ) -> Result<Vec<MediaEntity>, RepoError>;
+
+    /// Returns active and broken media, which weren't checked since `checked_before`, starting from never checked ones
+    async fn get_to_check(&mut self, media: GetMediaToCheck)
+        -> Result<Vec<MediaEntity>, RepoError>;
+
+    /// Returns active media, which weren't hashed yet, starting from the oldest ones
+    async fn get_to_hash(&mut self, media: GetMediaToHash) -> Result<Vec<MediaEntity>, RepoError>;
+
+    /// Returns active media, which weren't validated yet, starting from the oldest ones
+    async fn get_to_validate(
+        &mut self,
+        media: GetMediaToValidate,
+    ) -> Result<Vec<MediaEntity>, RepoError>;
+
+    /// Returns the oldest canonical media of the same genre and type with the same content hash or a close perceptual hash.
+    /// Media with the same content hash are preferred.
+    async fn get_canonical<'s>(
+        &mut self,
+        media: GetCanonicalMedia<'s>,
+    ) -> Result<Option<MediaEntity>, RepoError>;
+
     async fn get_media_stats(&mut self) -> Result<MediaStats, RepoError>;
 
     async fn get_genre_stats(&mut self) -> Result<GenresStats, RepoError>;
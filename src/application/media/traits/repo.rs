@@ -1,9 +1,13 @@
 use crate::application::{
-    common::exceptions::RepoKind,
-    media::{dto::CreateMedia, exceptions::MediaUrlAndGenreAlreadyExists},
+    common::exceptions::{RepoError, RepoKind},
+    media::{
+        dto::{CreateMedia, DeleteMediaByInfo, MarkMediaChecked, SetMediaFileInfo, SetMediaHashes},
+        exceptions::MediaUrlAndGenreAlreadyExists,
+    },
 };
 
 use async_trait::async_trait;
+use uuid::Uuid;
 
 #[allow(clippy::module_name_repetitions)]
 #[async_trait]
@@ -12,4 +16,29 @@ pub trait MediaRepo {
         &mut self,
         media: CreateMedia<'s>,
     ) -> Result<(), RepoKind<MediaUrlAndGenreAlreadyExists>>;
+
+    /// Creates media, which don't exist yet. Media with the same url and genre are skipped.
+    /// # Returns
+    /// Ids of created media
+    async fn create_many<'s>(&mut self, media: &[CreateMedia<'s>]) -> Result<Vec<Uuid>, RepoError>;
+
+    /// Creates media or updates type, age restriction and source of media with the same url and genre.
+    /// Media without genre are the same if their urls are the same
+    /// # Returns
+    /// Count of created media. The rest of media are updated.
+    async fn upsert_many<'s>(&mut self, media: &[CreateMedia<'s>]) -> Result<u64, RepoError>;
+
+    /// Sets check time of media to now and updates its status. Status of invalid media isn't changed
+    async fn mark_checked<'s>(&mut self, media: MarkMediaChecked<'s>) -> Result<(), RepoError>;
+
+    /// Sets hashes and canonical media of media and its hash time to now
+    async fn set_hashes<'s>(&mut self, media: SetMediaHashes<'s>) -> Result<(), RepoError>;
+
+    /// Sets content info, type and status of media and its validation time to now
+    async fn set_file_info<'s>(&mut self, media: SetMediaFileInfo<'s>) -> Result<(), RepoError>;
+
+    /// Deletes media and their views
+    /// # Returns
+    /// Count of deleted media
+    async fn delete_by_info<'s>(&mut self, media: DeleteMediaByInfo<'s>) -> Result<u64, RepoError>;
 }
@@ -1,5 +1,5 @@
 use super::source::Source;
-use crate::domain::media_parser::entities::Media;
+use crate::domain::media_parser::entities::FetchResult;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc::Receiver;
@@ -9,5 +9,5 @@ pub trait Worker<S>
 where
     S: Source,
 {
-    async fn parse(self, source: S) -> Receiver<Media>;
+    async fn parse(self, source: S) -> Receiver<FetchResult>;
 }
@@ -2,8 +2,16 @@ pub mod create;
 pub mod get_by_id;
 pub mod get_by_name;
 pub mod get_by_name_and_url;
+pub mod record_fetch_run;
+pub mod update_enabled;
+pub mod update_genre_paused;
+pub mod update_polling_interval;
 
 pub use create::CreateSource;
 pub use get_by_id::GetSourceById;
 pub use get_by_name::GetSourceByName;
 pub use get_by_name_and_url::GetSourceByNameAndUrl;
+pub use record_fetch_run::RecordSourceFetchRun;
+pub use update_enabled::UpdateSourceEnabled;
+pub use update_genre_paused::UpdateSourceGenrePaused;
+pub use update_polling_interval::UpdateSourcePollingInterval;
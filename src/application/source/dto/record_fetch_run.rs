@@ -0,0 +1,70 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordSourceFetchRun<'a> {
+    source_id: &'a Uuid,
+    genre: &'a str,
+    media_type: &'a str,
+    age_restriction: &'a str,
+    error: Option<&'a str>,
+    new_media_count: i64,
+    duplicate_media_count: i64,
+    latency_ms: i64,
+}
+
+impl<'a> RecordSourceFetchRun<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        source_id: &'a Uuid,
+        genre: &'a str,
+        media_type: &'a str,
+        age_restriction: &'a str,
+        error: Option<&'a str>,
+        new_media_count: i64,
+        duplicate_media_count: i64,
+        latency_ms: i64,
+    ) -> Self {
+        Self {
+            source_id,
+            genre,
+            media_type,
+            age_restriction,
+            error,
+            new_media_count,
+            duplicate_media_count,
+            latency_ms,
+        }
+    }
+
+    pub const fn source_id(&self) -> &Uuid {
+        self.source_id
+    }
+
+    pub const fn genre(&self) -> &str {
+        self.genre
+    }
+
+    pub const fn media_type(&self) -> &str {
+        self.media_type
+    }
+
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
+    }
+
+    pub const fn error(&self) -> Option<&str> {
+        self.error
+    }
+
+    pub const fn new_media_count(&self) -> i64 {
+        self.new_media_count
+    }
+
+    pub const fn duplicate_media_count(&self) -> i64 {
+        self.duplicate_media_count
+    }
+
+    pub const fn latency_ms(&self) -> i64 {
+        self.latency_ms
+    }
+}
@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSourceEnabled<'a> {
+    name: &'a str,
+    enabled: bool,
+}
+
+impl<'a> UpdateSourceEnabled<'a> {
+    pub const fn new(name: &'a str, enabled: bool) -> Self {
+        Self { name, enabled }
+    }
+
+    pub const fn name(&self) -> &str {
+        self.name
+    }
+
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
@@ -0,0 +1,46 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSourceGenrePaused<'a> {
+    name: &'a str,
+    genre: &'a str,
+    media_type: &'a str,
+    age_restriction: &'a str,
+    paused: bool,
+}
+
+impl<'a> UpdateSourceGenrePaused<'a> {
+    pub const fn new(
+        name: &'a str,
+        genre: &'a str,
+        media_type: &'a str,
+        age_restriction: &'a str,
+        paused: bool,
+    ) -> Self {
+        Self {
+            name,
+            genre,
+            media_type,
+            age_restriction,
+            paused,
+        }
+    }
+
+    pub const fn name(&self) -> &str {
+        self.name
+    }
+
+    pub const fn genre(&self) -> &str {
+        self.genre
+    }
+
+    pub const fn media_type(&self) -> &str {
+        self.media_type
+    }
+
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
+    }
+
+    pub const fn paused(&self) -> bool {
+        self.paused
+    }
+}
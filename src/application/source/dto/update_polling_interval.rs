@@ -0,0 +1,22 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSourcePollingInterval<'a> {
+    name: &'a str,
+    polling_interval_ms: i64,
+}
+
+impl<'a> UpdateSourcePollingInterval<'a> {
+    pub const fn new(name: &'a str, polling_interval_ms: i64) -> Self {
+        Self {
+            name,
+            polling_interval_ms,
+        }
+    }
+
+    pub const fn name(&self) -> &str {
+        self.name
+    }
+
+    pub const fn polling_interval_ms(&self) -> i64 {
+        self.polling_interval_ms
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
             exceptions::{SourceIdNotExist, SourceNameAndUrlNotExist},
         },
     },
-    domain::source::entities::Source as SourceEntity,
+    domain::source::entities::{
+        Source as SourceEntity, SourceFetchStats as SourceFetchStatsEntity,
+        SourcePausedGenre as SourcePausedGenreEntity,
+    },
 };
 
 use async_trait::async_trait;
@@ -19,6 +22,8 @@ pub trait SourceReader {
         source: GetSourceById<'s>,
     ) -> Result<SourceEntity, RepoKind<SourceIdNotExist>>;
 
+    async fn get_all(&mut self) -> Result<Vec<SourceEntity>, RepoError>;
+
     async fn get_by_name<'s>(
         &mut self,
         source: GetSourceByName<'s>,
@@ -28,4 +33,8 @@ pub trait SourceReader {
         &mut self,
         source: GetSourceByNameAndUrl<'s>,
     ) -> Result<SourceEntity, RepoKind<SourceNameAndUrlNotExist>>;
+
+    async fn get_fetch_stats(&mut self) -> Result<Vec<SourceFetchStatsEntity>, RepoError>;
+
+    async fn get_paused_genres(&mut self) -> Result<Vec<SourcePausedGenreEntity>, RepoError>;
 }
@@ -1,6 +1,12 @@
 use crate::application::{
-    common::exceptions::RepoKind,
-    source::{dto::CreateSource, exceptions::SourceNameAndUrlAlreadyExists},
+    common::exceptions::{RepoError, RepoKind},
+    source::{
+        dto::{
+            CreateSource, RecordSourceFetchRun, UpdateSourceEnabled, UpdateSourceGenrePaused,
+            UpdateSourcePollingInterval,
+        },
+        exceptions::SourceNameAndUrlAlreadyExists,
+    },
 };
 
 use async_trait::async_trait;
@@ -12,4 +18,30 @@ pub trait SourceRepo {
         &mut self,
         source: CreateSource<'s>,
     ) -> Result<(), RepoKind<SourceNameAndUrlAlreadyExists>>;
+
+    async fn record_fetch_run<'s>(
+        &mut self,
+        fetch_run: RecordSourceFetchRun<'s>,
+    ) -> Result<(), RepoError>;
+
+    /// Updates the flag of all sources with the name.
+    /// Returns count of updated sources.
+    async fn update_enabled<'s>(
+        &mut self,
+        source: UpdateSourceEnabled<'s>,
+    ) -> Result<u64, RepoError>;
+
+    /// Updates the polling interval of all sources with the name.
+    /// Returns count of updated sources.
+    async fn update_polling_interval<'s>(
+        &mut self,
+        source: UpdateSourcePollingInterval<'s>,
+    ) -> Result<u64, RepoError>;
+
+    /// Pauses or resumes the genre of all sources with the name.
+    /// Returns count of updated sources.
+    async fn update_genre_paused<'s>(
+        &mut self,
+        source: UpdateSourceGenrePaused<'s>,
+    ) -> Result<u64, RepoError>;
 }
@@ -1,3 +1,4 @@
 pub mod dto;
 pub mod exceptions;
+pub mod services;
 pub mod traits;
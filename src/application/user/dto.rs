@@ -1,11 +1,13 @@
 pub mod create;
+pub mod delete;
 pub mod get_by_id;
 pub mod get_by_tg_id;
 pub mod update_language_code;
-pub mod update_show_nsfw;
+pub mod update_max_age_restriction;
 
 pub use create::CreateUser;
+pub use delete::DeleteUser;
 pub use get_by_id::GetUserById;
 pub use get_by_tg_id::GetUserByTgId;
 pub use update_language_code::UpdateUserLanguageCode;
-pub use update_show_nsfw::UpdateUserShowNsfw;
+pub use update_max_age_restriction::UpdateUserMaxAgeRestriction;
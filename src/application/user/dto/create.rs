@@ -6,7 +6,7 @@ pub struct CreateUser<'a> {
     id: &'a Uuid,
     tg_id: i64,
     language_code: Option<&'a str>,
-    show_nsfw: Option<bool>,
+    max_age_restriction: &'a str,
 }
 
 impl<'a> CreateUser<'a> {
@@ -14,13 +14,13 @@ impl<'a> CreateUser<'a> {
         id: &'a Uuid,
         tg_id: i64,
         language_code: Option<&'a str>,
-        show_nsfw: Option<bool>,
+        max_age_restriction: &'a str,
     ) -> Self {
         Self {
             id,
             tg_id,
             language_code,
-            show_nsfw,
+            max_age_restriction,
         }
     }
 
@@ -36,7 +36,7 @@ impl<'a> CreateUser<'a> {
         self.language_code
     }
 
-    pub const fn show_nsfw(&self) -> Option<bool> {
-        self.show_nsfw
+    pub const fn max_age_restriction(&self) -> &str {
+        self.max_age_restriction
     }
 }
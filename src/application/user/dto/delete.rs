@@ -0,0 +1,16 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteUser<'a> {
+    id: &'a Uuid,
+}
+
+impl<'a> DeleteUser<'a> {
+    pub const fn new(id: &'a Uuid) -> Self {
+        Self { id }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+}
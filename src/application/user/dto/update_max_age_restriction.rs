@@ -0,0 +1,24 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateUserMaxAgeRestriction<'a> {
+    id: &'a Uuid,
+    max_age_restriction: &'a str,
+}
+
+impl<'a> UpdateUserMaxAgeRestriction<'a> {
+    pub const fn new(id: &'a Uuid, max_age_restriction: &'a str) -> Self {
+        Self {
+            id,
+            max_age_restriction,
+        }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn max_age_restriction(&self) -> &str {
+        self.max_age_restriction
+    }
+}
@@ -1,4 +1,4 @@
-use crate::application::common::exceptions::ApplicationException;
+use crate::application::common::exceptions::{ApplicationException, UnexpectedError};
 
 use std::borrow::Cow;
 use uuid::Uuid;
@@ -51,6 +51,39 @@ impl UserTgIdNotExist {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error("Export data of user with id `{id}` failed: {message}")]
+pub struct UserDataExportFailed {
+    id: Uuid,
+    message: Cow<'static, str>,
+}
+
+impl UserDataExportFailed {
+    pub fn new(id: Uuid, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            id,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown export format `{format}`. Available formats: json, csv")]
+pub struct UnknownExportFormat {
+    format: String,
+}
+
+impl UnknownExportFormat {
+    pub fn new(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+        }
+    }
+}
+
 impl ApplicationException for UserTgIdAlreadyExists {}
 impl ApplicationException for UserIdNotExist {}
 impl ApplicationException for UserTgIdNotExist {}
+impl ApplicationException for UserDataExportFailed {}
+impl UnexpectedError for UserDataExportFailed {}
+impl ApplicationException for UnknownExportFormat {}
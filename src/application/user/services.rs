@@ -0,0 +1,3 @@
+pub mod export_data;
+
+pub use export_data::{export_data, ExportFormat};
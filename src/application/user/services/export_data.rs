@@ -0,0 +1,176 @@
+use crate::{
+    application::{
+        common::traits::UnitOfWork,
+        user::exceptions::{UnknownExportFormat, UserDataExportFailed},
+        user_media_view::dto::GetUserMediaViewHistoryByUserId,
+    },
+    domain::{user::entities::User, user_media_view::entities::UserMediaViewWithMedia},
+};
+
+use serde::Serialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = UnknownExportFormat;
+
+    fn try_from(format: &str) -> Result<Self, Self::Error> {
+        match format.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(UnknownExportFormat::new(format)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Profile {
+    id: String,
+    tg_id: i64,
+    created: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Settings<'a> {
+    language_code: Option<&'a str>,
+    max_age_restriction: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryItem<'a> {
+    viewed: String,
+    media_id: String,
+    url: &'a str,
+    genre: Option<&'a str>,
+    media_type: &'a str,
+    age_restriction: &'a str,
+}
+
+/// Data of the user, which is exported.
+/// # Notes
+/// The bot doesn't store favorites, so they aren't exported. Add them here when they are stored.
+#[derive(Debug, Serialize)]
+struct UserData<'a> {
+    profile: Profile,
+    settings: Settings<'a>,
+    history: Vec<HistoryItem<'a>>,
+}
+
+fn format_date(date: OffsetDateTime) -> Result<String, time::error::Format> {
+    date.format(&Rfc3339)
+}
+
+impl<'a> UserData<'a> {
+    fn new(
+        user: &'a User,
+        history: &'a [UserMediaViewWithMedia],
+    ) -> Result<Self, time::error::Format> {
+        Ok(Self {
+            profile: Profile {
+                id: user.id.to_string(),
+                tg_id: user.tg_id,
+                created: format_date(user.created)?,
+            },
+            settings: Settings {
+                language_code: user.language_code.as_deref(),
+                max_age_restriction: &user.max_age_restriction,
+            },
+            history: history
+                .iter()
+                .map(|UserMediaViewWithMedia { view, media }| {
+                    Ok(HistoryItem {
+                        viewed: format_date(view.created)?,
+                        media_id: media.id.to_string(),
+                        url: &media.url,
+                        genre: media.genre.as_deref(),
+                        media_type: &media.media_type,
+                        age_restriction: &media.age_restriction,
+                    })
+                })
+                .collect::<Result<_, time::error::Format>>()?,
+        })
+    }
+
+    /// Serializes the data to CSV.
+    /// # Notes
+    /// CSV doesn't support nested data, so profile with settings and history are written as two tables separated by an empty line
+    fn to_csv(&self) -> Result<Vec<u8>, csv::Error> {
+        let mut profile_writer = csv::Writer::from_writer(vec![]);
+
+        profile_writer.write_record(["field", "value"])?;
+        profile_writer.write_record(["id", &self.profile.id])?;
+        profile_writer.write_record(["tg_id", &self.profile.tg_id.to_string()])?;
+        profile_writer.write_record(["created", &self.profile.created])?;
+        profile_writer.write_record([
+            "language_code",
+            self.settings.language_code.unwrap_or_default(),
+        ])?;
+        profile_writer.write_record(["max_age_restriction", self.settings.max_age_restriction])?;
+
+        let mut history_writer = csv::Writer::from_writer(vec![]);
+
+        for item in &self.history {
+            history_writer.serialize(item)?;
+        }
+
+        let mut content = profile_writer
+            .into_inner()
+            .map_err(|err| err.into_error())?;
+        content.push(b'\n');
+        content.extend(
+            history_writer
+                .into_inner()
+                .map_err(|err| err.into_error())?,
+        );
+
+        Ok(content)
+    }
+}
+
+/// Collects user's profile, settings and view history and serializes them to the specified format
+/// # Errors
+/// Returns [`UserDataExportFailed`] if the data can't be got from the database or serialized
+pub async fn export_data<UoW>(
+    uow: &mut UoW,
+    user: &User,
+    format: ExportFormat,
+) -> Result<Vec<u8>, UserDataExportFailed>
+where
+    UoW: UnitOfWork,
+{
+    let history = uow
+        .user_media_view_reader()
+        .await
+        .map_err(|err| UserDataExportFailed::new(user.id, err.to_string()))?
+        .get_history_by_user_id(GetUserMediaViewHistoryByUserId::new(
+            &user.id, None, None, None, None,
+        ))
+        .await
+        .map_err(|err| UserDataExportFailed::new(user.id, err.to_string()))?;
+
+    let data = UserData::new(user, &history)
+        .map_err(|err| UserDataExportFailed::new(user.id, err.to_string()))?;
+
+    match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(&data)
+            .map_err(|err| UserDataExportFailed::new(user.id, err.to_string())),
+        ExportFormat::Csv => data
+            .to_csv()
+            .map_err(|err| UserDataExportFailed::new(user.id, err.to_string())),
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
     application::{
-        common::exceptions::RepoKind,
+        common::exceptions::{RepoError, RepoKind},
         user::{
             dto::{GetUserById, GetUserByTgId},
             exceptions::{UserIdNotExist, UserTgIdNotExist},
         },
     },
-    domain::user::entities::User as UserEntity,
+    domain::user::entities::{User as UserEntity, UserStats as UserStatsEntity},
 };
 
 use async_trait::async_trait;
@@ -23,4 +23,6 @@ pub trait UserReader {
         &mut self,
         user: GetUserByTgId,
     ) -> Result<UserEntity, RepoKind<UserTgIdNotExist>>;
+
+    async fn get_stats(&mut self) -> Result<UserStatsEntity, RepoError>;
 }
@@ -1,8 +1,8 @@
 use crate::application::{
     common::exceptions::{RepoError, RepoKind},
     user::{
-        dto::{CreateUser, UpdateUserLanguageCode, UpdateUserShowNsfw},
-        exceptions::UserTgIdAlreadyExists,
+        dto::{CreateUser, DeleteUser, UpdateUserLanguageCode, UpdateUserMaxAgeRestriction},
+        exceptions::{UserIdNotExist, UserTgIdAlreadyExists},
     },
 };
 
@@ -21,6 +21,10 @@ pub trait UserRepo {
         user: UpdateUserLanguageCode<'s>,
     ) -> Result<(), RepoError>;
 
-    async fn update_show_nsfw<'s>(&mut self, user: UpdateUserShowNsfw<'s>)
-        -> Result<(), RepoError>;
+    async fn update_max_age_restriction<'s>(
+        &mut self,
+        user: UpdateUserMaxAgeRestriction<'s>,
+    ) -> Result<(), RepoError>;
+
+    async fn delete<'s>(&mut self, user: DeleteUser<'s>) -> Result<(), RepoKind<UserIdNotExist>>;
 }
@@ -1,4 +1,5 @@
 pub mod create;
+pub mod delete;
 pub mod get_by_id;
 pub mod get_by_media_age_restriction;
 pub mod get_by_media_genre;
@@ -7,8 +8,12 @@ pub mod get_by_media_source_id;
 pub mod get_by_media_type;
 pub mod get_by_user_id;
 pub mod get_by_user_tg_id;
+pub mod get_genre_stats_by_user_id;
+pub mod get_genre_view_stats;
+pub mod get_history_by_user_id;
 
 pub use create::CreateUserMediaView;
+pub use delete::DeleteUserMediaView;
 pub use get_by_id::GetUserMediaViewById;
 pub use get_by_media_age_restriction::GetUserMediaViewByMediaAgeRestriction;
 pub use get_by_media_genre::GetUserMediaViewByMediaGenre;
@@ -17,3 +22,6 @@ pub use get_by_media_source_id::GetUserMediaViewByMediaSourceId;
 pub use get_by_media_type::GetUserMediaViewByMediaType;
 pub use get_by_user_id::GetUserMediaViewByUserId;
 pub use get_by_user_tg_id::GetUserMediaViewByUserTgId;
+pub use get_genre_stats_by_user_id::GetUserMediaViewGenreStatsByUserId;
+pub use get_genre_view_stats::GetUserMediaViewGenreViewStats;
+pub use get_history_by_user_id::GetUserMediaViewHistoryByUserId;
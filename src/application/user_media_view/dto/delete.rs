@@ -0,0 +1,22 @@
+use uuid::Uuid;
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteUserMediaView<'a> {
+    id: &'a Uuid,
+    user_id: &'a Uuid,
+}
+
+impl<'a> DeleteUserMediaView<'a> {
+    pub const fn new(id: &'a Uuid, user_id: &'a Uuid) -> Self {
+        Self { id, user_id }
+    }
+
+    pub const fn id(&self) -> &Uuid {
+        self.id
+    }
+
+    pub const fn user_id(&self) -> &Uuid {
+        self.user_id
+    }
+}
@@ -1,14 +1,14 @@
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct GetUserMediaViewByMediaAgeRestriction {
-    is_sfw: Option<bool>,
+pub struct GetUserMediaViewByMediaAgeRestriction<'a> {
+    age_restriction: &'a str,
 }
 
-impl GetUserMediaViewByMediaAgeRestriction {
-    pub const fn new(is_sfw: Option<bool>) -> Self {
-        Self { is_sfw }
+impl<'a> GetUserMediaViewByMediaAgeRestriction<'a> {
+    pub const fn new(age_restriction: &'a str) -> Self {
+        Self { age_restriction }
     }
 
-    pub const fn is_sfw(&self) -> Option<bool> {
-        self.is_sfw
+    pub const fn age_restriction(&self) -> &str {
+        self.age_restriction
     }
 }
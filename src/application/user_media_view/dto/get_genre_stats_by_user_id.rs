@@ -0,0 +1,16 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetUserMediaViewGenreStatsByUserId<'a> {
+    user_id: &'a Uuid,
+}
+
+impl<'a> GetUserMediaViewGenreStatsByUserId<'a> {
+    pub const fn new(user_id: &'a Uuid) -> Self {
+        Self { user_id }
+    }
+
+    pub const fn user_id(&self) -> &Uuid {
+        self.user_id
+    }
+}
@@ -0,0 +1,18 @@
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetUserMediaViewGenreViewStats<'a> {
+    active_since: &'a OffsetDateTime,
+}
+
+impl<'a> GetUserMediaViewGenreViewStats<'a> {
+    /// # Arguments
+    /// * `active_since` - Users, who viewed media since the time, are active
+    pub const fn new(active_since: &'a OffsetDateTime) -> Self {
+        Self { active_since }
+    }
+
+    pub const fn active_since(&self) -> &OffsetDateTime {
+        self.active_since
+    }
+}
@@ -0,0 +1,48 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetUserMediaViewHistoryByUserId<'a> {
+    user_id: &'a Uuid,
+    genre: Option<&'a str>,
+    media_type: Option<&'a str>,
+    after_id: Option<&'a Uuid>,
+    limit: Option<u64>,
+}
+
+impl<'a> GetUserMediaViewHistoryByUserId<'a> {
+    pub const fn new(
+        user_id: &'a Uuid,
+        genre: Option<&'a str>,
+        media_type: Option<&'a str>,
+        after_id: Option<&'a Uuid>,
+        limit: Option<u64>,
+    ) -> Self {
+        Self {
+            user_id,
+            genre,
+            media_type,
+            after_id,
+            limit,
+        }
+    }
+
+    pub const fn user_id(&self) -> &Uuid {
+        self.user_id
+    }
+
+    pub const fn genre(&self) -> Option<&str> {
+        self.genre
+    }
+
+    pub const fn media_type(&self) -> Option<&str> {
+        self.media_type
+    }
+
+    pub const fn after_id(&self) -> Option<&Uuid> {
+        self.after_id
+    }
+
+    pub const fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+}
@@ -7,11 +7,17 @@ use crate::{
                 GetUserMediaViewByMediaGenre, GetUserMediaViewByMediaId,
                 GetUserMediaViewByMediaSourceId, GetUserMediaViewByMediaType,
                 GetUserMediaViewByUserId, GetUserMediaViewByUserTgId,
+                GetUserMediaViewGenreStatsByUserId, GetUserMediaViewGenreViewStats,
+                GetUserMediaViewHistoryByUserId,
             },
             exceptions::UserMediaViewIdNotExist,
         },
     },
-    domain::user_media_view::entities::UserMediaView as UserMediaViewEntity,
+    domain::user_media_view::entities::{
+        GenreViewStats as GenreViewStatsEntity, UserGenresStats as UserGenresStatsEntity,
+        UserMediaView as UserMediaViewEntity,
+        UserMediaViewWithMedia as UserMediaViewWithMediaEntity,
+    },
 };
 
 use async_trait::async_trait;
@@ -49,13 +55,30 @@ pub trait UserMediaViewReader {
         user_media_view: GetUserMediaViewByMediaType<'s>,
     ) -> Result<Vec<UserMediaViewEntity>, RepoError>;
 
-    async fn get_by_media_age_restriction(
+    async fn get_by_media_age_restriction<'s>(
         &mut self,
-        user_media_view: GetUserMediaViewByMediaAgeRestriction,
+        user_media_view: GetUserMediaViewByMediaAgeRestriction<'s>,
     ) -> Result<Vec<UserMediaViewEntity>, RepoError>;
 
     async fn get_by_media_source_id<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByMediaSourceId<'s>,
     ) -> Result<Vec<UserMediaViewEntity>, RepoError>;
+
+    /// Returns views of the user with their media, the newest first.
+    /// Views after the view with `after_id` are returned, or the first page if the view doesn't exist anymore.
+    async fn get_history_by_user_id<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewHistoryByUserId<'s>,
+    ) -> Result<Vec<UserMediaViewWithMediaEntity>, RepoError>;
+
+    async fn get_genre_stats_by_user_id<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewGenreStatsByUserId<'s>,
+    ) -> Result<UserGenresStatsEntity, RepoError>;
+
+    async fn get_genre_view_stats<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewGenreViewStats<'s>,
+    ) -> Result<Vec<GenreViewStatsEntity>, RepoError>;
 }
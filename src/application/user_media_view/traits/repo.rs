@@ -1,7 +1,8 @@
 use crate::application::{
     common::exceptions::RepoKind,
     user_media_view::{
-        dto::CreateUserMediaView, exceptions::UserMediaViewUserIdAndMediaIdAlreadyExists,
+        dto::{CreateUserMediaView, DeleteUserMediaView},
+        exceptions::{UserMediaViewIdNotExist, UserMediaViewUserIdAndMediaIdAlreadyExists},
     },
 };
 
@@ -14,4 +15,9 @@ pub trait UserMediaViewRepo {
         &mut self,
         user_media_view: CreateUserMediaView<'s>,
     ) -> Result<(), RepoKind<UserMediaViewUserIdAndMediaIdAlreadyExists>>;
+
+    async fn delete<'s>(
+        &mut self,
+        user_media_view: DeleteUserMediaView<'s>,
+    ) -> Result<(), RepoKind<UserMediaViewIdNotExist>>;
 }
@@ -0,0 +1,144 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to the config file. If it isn't passed, `CONFIG_PATH` env or `config.toml`, if it exists, is used.
+    #[arg(short, long, global = true)]
+    pub config: Option<PathBuf>,
+    /// Command to run. If it isn't passed, the bot is started.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Default, Subcommand)]
+pub enum Command {
+    /// Run the bot
+    #[default]
+    Serve,
+    /// Run only the media parser worker
+    Worker,
+    /// Manage database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Export the media catalog in JSON Lines
+    Export {
+        /// File to write to. If it isn't passed, stdout is used.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import the media catalog from JSON Lines
+    Import {
+        /// File to read from. If it isn't passed, stdin is used.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Manage users
+    Users {
+        #[command(subcommand)]
+        action: UsersAction,
+    },
+    /// Manage media
+    Media {
+        #[command(subcommand)]
+        action: MediaAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// Apply pending migrations
+    Run,
+    /// Show migrations and their status
+    Info,
+    /// Show pending migrations without applying them
+    DryRun,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UsersAction {
+    /// Show statistics of users
+    Stats,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MediaAction {
+    /// Delete media of genres, which aren't provided by sources anymore, and their views
+    Prune {
+        /// Genre to delete in addition to the stale ones. Can be passed multiple times.
+        #[arg(long)]
+        genre: Vec<String>,
+        /// Show media to delete without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command, MediaAction, MigrateAction, UsersAction};
+
+    use clap::{CommandFactory as _, Parser as _};
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+
+        assert!(Cli::parse_from(["bot"]).command.is_none());
+        assert!(matches!(
+            Cli::parse_from(["bot", "worker", "--config", "bot.toml"]),
+            Cli { config: Some(config), command: Some(Command::Worker) } if config.to_str() == Some("bot.toml")
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "serve"]).command,
+            Some(Command::Serve)
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "worker"]).command,
+            Some(Command::Worker)
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "migrate", "dry-run"]).command,
+            Some(Command::Migrate {
+                action: MigrateAction::DryRun
+            })
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "export"]).command,
+            Some(Command::Export { output: None })
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "import", "-i", "catalog.jsonl"]).command,
+            Some(Command::Import { input: Some(input) }) if input.to_str() == Some("catalog.jsonl")
+        ));
+        assert!(matches!(
+            Cli::parse_from(["bot", "users", "stats"]).command,
+            Some(Command::Users {
+                action: UsersAction::Stats
+            })
+        ));
+
+        let Some(Command::Media {
+            action: MediaAction::Prune { genre, dry_run },
+        }) = Cli::parse_from([
+            "bot",
+            "media",
+            "prune",
+            "--genre",
+            "neko",
+            "--genre",
+            "waifu",
+            "--dry-run",
+        ])
+        .command
+        else {
+            panic!("Expected `media prune` command");
+        };
+
+        assert_eq!(genre, ["neko", "waifu"]);
+        assert!(dry_run);
+    }
+}
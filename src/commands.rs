@@ -0,0 +1,11 @@
+pub mod catalog;
+pub mod media;
+pub mod migrate;
+pub mod users;
+pub mod worker;
+
+pub use catalog::{export, import};
+pub use media::media;
+pub use migrate::migrate;
+pub use users::users;
+pub use worker::worker;
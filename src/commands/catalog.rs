@@ -0,0 +1,450 @@
+use crate::application::{
+    common::{
+        exceptions::RepoKind,
+        traits::{UnitOfWork, UnitOfWorkFactory},
+    },
+    media::dto::{CreateMedia, GetMediaPage},
+    source::dto::{CreateSource, GetSourceById, GetSourceByNameAndUrl},
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+use time::OffsetDateTime;
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+/// Count of media, which are read from the database per request
+const EXPORT_PAGE_SIZE: u64 = 1000;
+/// Count of media, which are upserted per request
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Line of the catalog file.
+/// Media reference sources by name and url instead of id, so the catalog can be moved between databases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Source(SourceRecord),
+    Media(MediaRecord),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SourceRecord {
+    name: String,
+    url: String,
+    /// Informational, it isn't imported
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    created: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct MediaRecord {
+    url: String,
+    genre: Option<String>,
+    media_type: String,
+    age_restriction: String,
+    source_name: String,
+    source_url: String,
+    /// Informational, it isn't imported
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    created: Option<OffsetDateTime>,
+}
+
+/// Writes all sources and media to the file or stdout, one JSON object per line
+/// # Errors
+/// Returns error if sources or media can't be read or written
+#[instrument(skip(uow_factory))]
+pub async fn export<UoWFactory>(
+    uow_factory: UoWFactory,
+    output: Option<&Path>,
+) -> anyhow::Result<()>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut writer: BufWriter<Box<dyn Write>> = BufWriter::new(match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    });
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let sources = uow.source_reader().await?.get_all().await?;
+
+    for source in &sources {
+        write_record(
+            &mut writer,
+            &Record::Source(SourceRecord {
+                name: source.name.clone(),
+                url: source.url.clone(),
+                created: Some(source.created),
+            }),
+        )?;
+    }
+
+    let mut sources = sources
+        .into_iter()
+        .map(|source| (source.id, source))
+        .collect::<HashMap<_, _>>();
+
+    let mut after_id = None;
+    let mut exported_media_count = 0;
+
+    loop {
+        let media_list = uow
+            .media_reader()
+            .await?
+            .get_page(GetMediaPage::new(after_id.as_ref(), EXPORT_PAGE_SIZE))
+            .await?;
+
+        let Some(last_media) = media_list.last() else {
+            break;
+        };
+        after_id = Some(last_media.id);
+
+        for media in media_list {
+            let source = match sources.entry(media.source_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                // The source is created after sources are read, for example, by the worker in another process
+                Entry::Vacant(entry) => {
+                    let source = uow
+                        .source_reader()
+                        .await?
+                        .get_by_id(GetSourceById::new(entry.key()))
+                        .await?;
+
+                    write_record(
+                        &mut writer,
+                        &Record::Source(SourceRecord {
+                            name: source.name.clone(),
+                            url: source.url.clone(),
+                            created: Some(source.created),
+                        }),
+                    )?;
+
+                    entry.insert(source)
+                }
+            };
+
+            write_record(
+                &mut writer,
+                &Record::Media(MediaRecord {
+                    url: media.url,
+                    genre: media.genre,
+                    media_type: media.media_type,
+                    age_restriction: media.age_restriction,
+                    source_name: source.name.clone(),
+                    source_url: source.url.clone(),
+                    created: Some(media.created),
+                }),
+            )?;
+
+            exported_media_count += 1;
+        }
+    }
+
+    writer.flush()?;
+
+    event!(
+        Level::INFO,
+        exported_source_count = sources.len(),
+        exported_media_count,
+        "Catalog exported",
+    );
+
+    Ok(())
+}
+
+fn write_record(writer: &mut impl Write, record: &Record) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+
+    Ok(())
+}
+
+/// Reads sources and media from the file or stdin, one JSON object per line.
+/// Sources are created if they don't exist.
+/// Media are created or updated if media with the same url and genre exist.
+/// # Errors
+/// Returns error if a line can't be parsed or sources or media can't be saved
+#[instrument(skip(uow_factory))]
+pub async fn import<UoWFactory>(uow_factory: UoWFactory, input: Option<&Path>) -> anyhow::Result<()>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let mut source_ids = HashMap::new();
+    let mut batch = vec![];
+    let mut media_count = 0;
+    let mut created_media_count = 0;
+    let mut duplicate_media_count = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Record = serde_json::from_str(&line)
+            .map_err(|err| anyhow::anyhow!("Invalid record on line {}: {err}", index + 1))?;
+
+        match record {
+            Record::Source(source) => {
+                get_or_create_source(&mut uow, &mut source_ids, source.name, source.url).await?;
+            }
+            Record::Media(media) => {
+                let source_id = get_or_create_source(
+                    &mut uow,
+                    &mut source_ids,
+                    media.source_name.clone(),
+                    media.source_url.clone(),
+                )
+                .await?;
+
+                batch.push((source_id, media));
+                media_count += 1;
+
+                if batch.len() >= IMPORT_BATCH_SIZE {
+                    let (created_count, duplicate_count) =
+                        upsert_media(&mut uow, &mut batch).await?;
+
+                    created_media_count += created_count;
+                    duplicate_media_count += duplicate_count;
+                }
+            }
+        }
+    }
+
+    let (created_count, duplicate_count) = upsert_media(&mut uow, &mut batch).await?;
+
+    created_media_count += created_count;
+    duplicate_media_count += duplicate_count;
+
+    let updated_media_count = media_count - created_media_count - duplicate_media_count;
+
+    println!(
+        "Imported {source_count} sources and {media_count} media: \
+        {created_media_count} created, {updated_media_count} updated, \
+        {duplicate_media_count} duplicates skipped",
+        source_count = source_ids.len(),
+    );
+
+    event!(
+        Level::INFO,
+        media_count,
+        created_media_count,
+        updated_media_count,
+        duplicate_media_count,
+        "Catalog imported",
+    );
+
+    Ok(())
+}
+
+/// Returns id of the source with the name and url. If the source doesn't exist, it's created.
+async fn get_or_create_source<UoW>(
+    uow: &mut UoW,
+    source_ids: &mut HashMap<(String, String), Uuid>,
+    name: String,
+    url: String,
+) -> anyhow::Result<Uuid>
+where
+    UoW: UnitOfWork,
+{
+    if let Some(source_id) = source_ids.get(&(name.clone(), url.clone())) {
+        return Ok(*source_id);
+    }
+
+    let source_id = Uuid::new_v4();
+
+    let create_source_result = uow
+        .source_repo()
+        .await?
+        .create(CreateSource::new(&source_id, &name, &url))
+        .await;
+
+    let source_id = match create_source_result {
+        Ok(()) => {
+            uow.commit().await?;
+
+            source_id
+        }
+        Err(RepoKind::Exception(_)) => {
+            uow.rollback().await?;
+
+            uow.source_reader()
+                .await?
+                .get_by_name_and_url(GetSourceByNameAndUrl::new(&name, &url))
+                .await?
+                .id
+        }
+        Err(RepoKind::Unexpected(err)) => {
+            uow.rollback().await?;
+
+            return Err(err.into());
+        }
+    };
+
+    source_ids.insert((name, url), source_id);
+
+    Ok(source_id)
+}
+
+/// Removes media with the same url and genre except the last one.
+/// Postgres doesn't allow to update the same row twice in one upsert.
+/// # Returns
+/// Count of removed media
+fn dedup_by_url_and_genre(batch: &mut Vec<(Uuid, MediaRecord)>) -> u64 {
+    let mut seen = HashSet::new();
+    let len = batch.len();
+
+    batch.reverse();
+    batch.retain(|(_, media)| seen.insert((media.url.clone(), media.genre.clone())));
+    batch.reverse();
+
+    (len - batch.len()) as u64
+}
+
+/// Upserts media of the batch and clears it
+/// # Returns
+/// Count of created media and count of media, which are skipped as duplicates of later media in the batch
+async fn upsert_media<UoW>(
+    uow: &mut UoW,
+    batch: &mut Vec<(Uuid, MediaRecord)>,
+) -> anyhow::Result<(u64, u64)>
+where
+    UoW: UnitOfWork,
+{
+    let duplicate_media_count = dedup_by_url_and_genre(batch);
+
+    let media_ids = batch.iter().map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+    let media_list = batch
+        .iter()
+        .zip(&media_ids)
+        .map(|((source_id, media), media_id)| {
+            CreateMedia::new(
+                media_id,
+                &media.url,
+                media.genre.as_deref(),
+                &media.media_type,
+                &media.age_restriction,
+                source_id,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let upsert_result = uow.media_repo().await?.upsert_many(&media_list).await;
+
+    let created_media_count = match upsert_result {
+        Ok(created_media_count) => {
+            uow.commit().await?;
+
+            created_media_count
+        }
+        Err(err) => {
+            uow.rollback().await?;
+
+            return Err(err.into());
+        }
+    };
+
+    batch.clear();
+
+    Ok((created_media_count, duplicate_media_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedup_by_url_and_genre, MediaRecord, Record, SourceRecord};
+
+    use uuid::Uuid;
+
+    fn media_record(url: &str, genre: &str, age_restriction: &str) -> MediaRecord {
+        MediaRecord {
+            url: url.to_owned(),
+            genre: Some(genre.to_owned()),
+            media_type: "img".to_owned(),
+            age_restriction: age_restriction.to_owned(),
+            source_name: "nekos.best.v2".to_owned(),
+            source_url: "https://nekos.best/api/v2".to_owned(),
+            created: None,
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let record = Record::Media(media_record("https://nekos.best/1.png", "neko", "sfw"));
+
+        let line = serde_json::to_string(&record).unwrap();
+
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with(r#"{"type":"media","#));
+        assert_eq!(serde_json::from_str::<Record>(&line).unwrap(), record);
+
+        let record: Record = serde_json::from_str(
+            r#"{"type":"source","name":"waifu.pics","url":"https://waifu.pics"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            record,
+            Record::Source(SourceRecord {
+                name: "waifu.pics".to_owned(),
+                url: "https://waifu.pics".to_owned(),
+                created: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dedup_by_url_and_genre() {
+        let source_id = Uuid::new_v4();
+
+        let mut batch = vec![
+            (
+                source_id,
+                media_record("https://nekos.best/1.png", "neko", "sfw"),
+            ),
+            (
+                source_id,
+                media_record("https://nekos.best/2.png", "neko", "sfw"),
+            ),
+            (
+                source_id,
+                media_record("https://nekos.best/1.png", "waifu", "sfw"),
+            ),
+            (
+                source_id,
+                media_record("https://nekos.best/1.png", "neko", "nsfw"),
+            ),
+        ];
+
+        assert_eq!(dedup_by_url_and_genre(&mut batch), 1);
+
+        assert_eq!(
+            batch
+                .iter()
+                .map(|(_, media)| (
+                    media.url.as_str(),
+                    media.genre.as_deref(),
+                    media.age_restriction.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("https://nekos.best/2.png", Some("neko"), "sfw"),
+                ("https://nekos.best/1.png", Some("waifu"), "sfw"),
+                ("https://nekos.best/1.png", Some("neko"), "nsfw"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    application::{
+        common::traits::{UnitOfWork as _, UnitOfWorkFactory},
+        media::dto::DeleteMediaByInfo,
+    },
+    cli::MediaAction,
+    domain::{media::entities::GenreStats, media_parser::entities::Genre},
+};
+
+use tracing::{event, instrument, Level};
+
+/// Returns genres of the catalog, which aren't provided by sources or are passed explicitly
+fn genres_to_prune<'a>(
+    genres_stats: &'a [GenreStats],
+    provided_genres: &[&Genre],
+    extra_genres: &[String],
+) -> Vec<&'a GenreStats> {
+    genres_stats
+        .iter()
+        .filter(|stats| {
+            extra_genres.contains(&stats.genre)
+                || !provided_genres.iter().any(|genre| {
+                    genre.name() == stats.genre
+                        && genre.media_type().as_str() == stats.media_type
+                        && genre.age_restriction().as_str() == stats.age_restriction
+                })
+        })
+        .collect()
+}
+
+/// Runs the media command
+/// # Arguments
+/// * `provided_genres` - Genres, which are provided by the configured sources
+/// # Errors
+/// Returns error if media can't be read or deleted
+#[instrument(skip(uow_factory, provided_genres))]
+pub async fn media<UoWFactory>(
+    uow_factory: UoWFactory,
+    provided_genres: &[&Genre],
+    action: MediaAction,
+) -> anyhow::Result<()>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    match action {
+        MediaAction::Prune { genre, dry_run } => {
+            let genres_stats = uow.media_reader().await?.get_genre_stats().await?.0;
+            let genres_to_prune = genres_to_prune(&genres_stats, provided_genres, &genre);
+
+            if genres_to_prune.is_empty() {
+                println!("No media to prune");
+
+                return Ok(());
+            }
+
+            if dry_run {
+                for stats in genres_to_prune {
+                    println!("Would delete {stats}");
+                }
+
+                return Ok(());
+            }
+
+            for stats in genres_to_prune {
+                let deleted_count = uow
+                    .media_repo()
+                    .await?
+                    .delete_by_info(DeleteMediaByInfo::new(
+                        Some(&stats.genre),
+                        &stats.media_type,
+                        &stats.age_restriction,
+                    ))
+                    .await?;
+
+                println!("Deleted {deleted_count} media of {stats}");
+            }
+
+            uow.commit().await?;
+
+            event!(Level::INFO, "Media pruned");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::genres_to_prune;
+
+    use crate::domain::{media::entities::GenreStats, media_parser::entities::Genre};
+
+    fn genre_stats(genre: &str, media_type: &str, age_restriction: &str) -> GenreStats {
+        GenreStats {
+            total: 1,
+            genre: genre.to_owned(),
+            media_type: media_type.to_owned(),
+            age_restriction: age_restriction.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_genres_to_prune() {
+        let neko = Genre::new_sfw_image("neko");
+        let hug = Genre::new_sfw_gif("hug");
+
+        let genres_stats = [
+            genre_stats("neko", "img", "sfw"),
+            genre_stats("neko", "img", "nsfw"),
+            genre_stats("hug", "gif", "sfw"),
+            genre_stats("trap", "img", "nsfw"),
+        ];
+
+        let genres = genres_to_prune(&genres_stats, &[&neko, &hug], &[]);
+        assert_eq!(genres, [&genres_stats[1], &genres_stats[3]]);
+
+        let genres = genres_to_prune(&genres_stats, &[&neko, &hug], &["hug".to_owned()]);
+        assert_eq!(
+            genres,
+            [&genres_stats[1], &genres_stats[2], &genres_stats[3]]
+        );
+    }
+}
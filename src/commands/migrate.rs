@@ -0,0 +1,40 @@
+use crate::{cli::MigrateAction, infrastructure::database::migrations};
+
+use sqlx::{migrate::MigrateError, PgPool};
+use tracing::{event, instrument, Level};
+
+/// Runs the migrate command
+/// # Errors
+/// Returns error if migrations can't be listed or applied
+#[instrument(skip(pool))]
+pub async fn migrate(pool: &PgPool, action: MigrateAction) -> Result<(), MigrateError> {
+    match action {
+        MigrateAction::Run => {
+            migrations::run(pool).await?;
+
+            event!(Level::INFO, "Migrations applied");
+        }
+        MigrateAction::Info => {
+            for migration in migrations::info(pool).await? {
+                println!("{migration}");
+            }
+        }
+        MigrateAction::DryRun => {
+            let pending_migrations = migrations::info(pool)
+                .await?
+                .into_iter()
+                .filter(|migration| !migration.applied)
+                .collect::<Vec<_>>();
+
+            if pending_migrations.is_empty() {
+                println!("No pending migrations");
+            }
+
+            for migration in pending_migrations {
+                println!("Would apply {migration}");
+            }
+        }
+    }
+
+    Ok(())
+}
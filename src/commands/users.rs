@@ -0,0 +1,27 @@
+use crate::{
+    application::common::traits::{UnitOfWork as _, UnitOfWorkFactory},
+    cli::UsersAction,
+};
+
+use tracing::instrument;
+
+/// Runs the users command
+/// # Errors
+/// Returns error if the statistics can't be read
+#[instrument(skip(uow_factory))]
+pub async fn users<UoWFactory>(uow_factory: UoWFactory, action: UsersAction) -> anyhow::Result<()>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    match action {
+        UsersAction::Stats => {
+            let stats = uow.user_reader().await?.get_stats().await?;
+
+            println!("{stats}");
+        }
+    }
+
+    Ok(())
+}
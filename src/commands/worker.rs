@@ -0,0 +1,83 @@
+use crate::infrastructure::{
+    database::SqlxUnitOfWorkFactory,
+    media_deduplicator::{run_deduplication, MediaDeduplicator},
+    media_parser::{sources, worker, Controller, Scheduling, SourcesReloader},
+    media_validator::{run_validation, MediaValidator},
+    media_verifier::{run_verification, MediaVerifier},
+    shutdown::{self, Shutdown},
+};
+
+use sqlx::{PgPool, Pool};
+use std::{sync::Arc, time::Duration};
+use tracing::{event, instrument, Level};
+
+/// Runs the media parser worker with reloading of its sources, and the media validator, the media deduplicator and the media verifier, if they're passed,
+/// without the bot until the shutdown signal.
+/// On shutdown, parsed media and results of started checks are saved before the connection pool is closed, if it takes less than the shutdown timeout.
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all)]
+pub async fn worker(
+    pool: PgPool,
+    sources_reloader: Arc<SourcesReloader>,
+    sources_reload_interval: Duration,
+    scheduling: Scheduling,
+    shutdown_timeout: Duration,
+    media_validator: Option<MediaValidator>,
+    media_deduplicator: Option<MediaDeduplicator>,
+    media_verifier: Option<MediaVerifier>,
+) {
+    let uow_factory = SqlxUnitOfWorkFactory::new(pool.clone());
+
+    let shutdown = Shutdown::default();
+
+    // Commands are sent only by handlers of the bot, so sources are controlled here by their settings in the database
+    shutdown.spawn(worker::run_pollings(
+        sources_reloader.subscribe(),
+        uow_factory.clone(),
+        Controller::default(),
+        scheduling,
+        shutdown.token().clone(),
+    ));
+
+    if let Some(media_validator) = media_validator {
+        let (uow_factory, token) = (uow_factory.clone(), shutdown.token().clone());
+
+        shutdown.spawn(async move {
+            if let Err(err) = run_validation(media_validator, uow_factory, token).await {
+                event!(Level::ERROR, %err, "Media validator stopped with error");
+            }
+        });
+    }
+    if let Some(media_deduplicator) = media_deduplicator {
+        let (uow_factory, token) = (uow_factory.clone(), shutdown.token().clone());
+
+        shutdown.spawn(async move {
+            if let Err(err) = run_deduplication(media_deduplicator, uow_factory, token).await {
+                event!(Level::ERROR, %err, "Media deduplicator stopped with error");
+            }
+        });
+    }
+    if let Some(media_verifier) = media_verifier {
+        let (uow_factory, token) = (uow_factory.clone(), shutdown.token().clone());
+
+        shutdown.spawn(async move {
+            if let Err(err) = run_verification(media_verifier, uow_factory, token).await {
+                event!(Level::ERROR, %err, "Media verifier stopped with error");
+            }
+        });
+    }
+
+    tokio::select! {
+        () = sources::run_watching(sources_reloader.clone(), sources_reload_interval) => {
+            event!(Level::WARN, "Media parser sources watching stopped");
+        }
+        () = shutdown::signal() => {
+            event!(Level::INFO, "Media parser worker stopped by shutdown signal");
+        }
+    }
+
+    // Tasks, which didn't finish, can still use connections, so the pool is closed only after all of them
+    if shutdown.run(shutdown_timeout).await {
+        Pool::close(&pool).await;
+    }
+}
@@ -1,12 +1,25 @@
 use std::{
     borrow::Cow,
     env::{self, VarError},
-    num::ParseIntError,
-    str::ParseBoolError,
+    fmt::{self, Display},
+    fs, io,
+    net::SocketAddr,
+    num::{NonZeroU32, NonZeroUsize},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
 };
 
+/// Path to the config file, which is used if the path isn't passed and the file exists
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Language of texts of the bot, which is the only one supported yet
+const DEFAULT_LANGUAGE: &str = "en";
+
 pub struct Bot {
+    /// Token is required only to run the bot, so it's empty for other commands, if it isn't set
     pub token: String,
+    pub admin_ids: Vec<i64>,
 }
 
 pub struct Database {
@@ -15,6 +28,10 @@ pub struct Database {
     pub user: String,
     pub password: String,
     pub db: String,
+    pub run_migrations: bool,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
 }
 
 impl Database {
@@ -30,102 +47,780 @@ impl Database {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeFlavor {
+    /// All tasks and handlers are run on the main thread
+    CurrentThread,
+    /// Tasks and handlers are run on a pool of worker threads
+    MultiThread,
+}
+
+impl FromStr for RuntimeFlavor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "current_thread" => Ok(Self::CurrentThread),
+            "multi_thread" => Ok(Self::MultiThread),
+            _ => Err(format!(
+                "unknown flavor `{s}`, expected `current_thread` or `multi_thread`"
+            )),
+        }
+    }
+}
+
+pub struct Runtime {
+    pub flavor: RuntimeFlavor,
+    /// Count of worker threads of the multi-thread runtime. If it isn't set, it's the count of CPU cores.
+    pub worker_threads: Option<NonZeroUsize>,
+}
+
 pub struct MediaParserWorker {
     pub start_worker: bool,
+    pub deduplicate_media: bool,
+    pub max_hash_distance: u32,
+    pub validate_media: bool,
+    pub stock_target: Option<i64>,
+    pub active_users_period: Duration,
+    pub stock_polling_interval: Duration,
+    pub duplicates_max_backoff: Duration,
+}
+
+pub struct Sources {
+    /// Path to the sources config. If it isn't set, the default sources are used.
+    pub config_path: Option<PathBuf>,
+    pub reload_interval: Duration,
+}
+
+#[allow(clippy::struct_field_names)]
+pub struct RateLimits {
+    pub media_validation_requests_per_second: NonZeroU32,
+    pub media_verifier_requests_per_second: NonZeroU32,
+    pub media_deduplication_requests_per_second: NonZeroU32,
+}
+
+pub struct MediaVerifier {
+    pub start_verifier: bool,
+    pub recheck_interval: Duration,
+}
+
+pub struct Metrics {
+    pub enabled: bool,
+    pub address: SocketAddr,
+}
+
+pub struct Health {
+    pub enabled: bool,
+    pub address: SocketAddr,
+    pub max_poll_age: Duration,
+    pub max_worker_inactivity: Duration,
+}
+
+pub struct Webhook {
+    pub enabled: bool,
+    pub url: String,
+    pub path: String,
+    pub address: SocketAddr,
+    pub secret_token: Option<String>,
+    pub certificate_path: Option<PathBuf>,
+}
+
+pub struct Shutdown {
+    pub timeout: Duration,
+}
+
+pub struct Localization {
+    /// Language of texts for users, whose language isn't supported.
+    /// Texts are in English only yet, so it's a placeholder for translations.
+    pub default_language: String,
 }
 
 pub struct Config {
+    pub runtime: Runtime,
     pub bot: Bot,
     pub database: Database,
     pub media_parser_worker: MediaParserWorker,
+    pub sources: Sources,
+    pub rate_limits: RateLimits,
+    pub media_verifier: MediaVerifier,
+    pub metrics: Metrics,
+    pub health: Health,
+    pub webhook: Webhook,
+    pub shutdown: Shutdown,
+    pub localization: Localization,
+}
+
+impl Config {
+    /// Checks values, which can't be checked by their types
+    /// # Arguments
+    /// * `require_bot_token` - If `true`, the bot token should be set. Only the bot needs it.
+    /// # Errors
+    /// Returns error with the key of the first invalid value
+    pub fn validate(&self, require_bot_token: bool) -> Result<(), ErrorKind> {
+        if require_bot_token && self.bot.token.trim().is_empty() {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("bot.token", "BOT_TOKEN").to_string().into(),
+                message: "should not be empty, set the token from @BotFather in the config file, \
+                    `BOT_TOKEN` or a file passed by `BOT_TOKEN_FILE`"
+                    .into(),
+            });
+        }
+        if self.database.max_connections == 0 {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("database.max_connections", "DATABASE_MAX_CONNECTIONS")
+                    .to_string()
+                    .into(),
+                message: "should be greater than 0".into(),
+            });
+        }
+        if self.database.min_connections > self.database.max_connections {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("database.min_connections", "DATABASE_MIN_CONNECTIONS")
+                    .to_string()
+                    .into(),
+                message: "should be less than or equal to `database.max_connections`".into(),
+            });
+        }
+        if self
+            .media_parser_worker
+            .stock_target
+            .is_some_and(|stock_target| stock_target < 0)
+        {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("worker.stock_target", "MEDIA_PARSER_STOCK_TARGET")
+                    .to_string()
+                    .into(),
+                message: "should not be negative".into(),
+            });
+        }
+        // URL is required only if webhook is enabled
+        if self.webhook.enabled && self.webhook.url.is_empty() {
+            return Err(ErrorKind::Missing {
+                key: Key::new("webhook.url", "WEBHOOK_URL").to_string().into(),
+            });
+        }
+        if !self.webhook.path.starts_with('/') {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("webhook.path", "WEBHOOK_PATH").to_string().into(),
+                message: "should start with `/`".into(),
+            });
+        }
+        if self.localization.default_language != DEFAULT_LANGUAGE {
+            return Err(ErrorKind::Invalid {
+                key: Key::new("localization.default_language", "DEFAULT_LANGUAGE")
+                    .to_string()
+                    .into(),
+                message: format!("only `{DEFAULT_LANGUAGE}` is supported yet").into(),
+            });
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum ErrorKind {
+    #[error("error reading config file {path}: {source}")]
+    File { source: io::Error, path: PathBuf },
+    #[error("error parsing config file {path}: {source}")]
+    Toml {
+        source: toml::de::Error,
+        path: PathBuf,
+    },
     #[error("env error: {source} for key {key}")]
     Env {
         source: VarError,
         key: Cow<'static, str>,
     },
-    #[error(transparent)]
-    ParseInt(#[from] ParseIntError),
-    #[error(transparent)]
-    ParseBool(#[from] ParseBoolError),
+    #[error("error reading secret file {path} for key {key}: {source}")]
+    SecretFile {
+        source: io::Error,
+        key: Cow<'static, str>,
+        path: PathBuf,
+    },
+    #[error("missing value for key {key}")]
+    Missing { key: Cow<'static, str> },
+    #[error("invalid value for key {key}: {message}")]
+    Invalid {
+        key: Cow<'static, str>,
+        message: Cow<'static, str>,
+    },
 }
 
-pub fn read_config_from_env() -> Result<Config, ErrorKind> {
-    Ok(Config {
-        bot: Bot {
-            token: env::var("BOT_TOKEN").map_err(|err| ErrorKind::Env {
-                source: err,
-                key: "BOT_TOKEN".into(),
-            })?,
-        },
-        database: Database {
-            host: env::var("POSTGRES_HOST").map_err(|err| ErrorKind::Env {
-                source: err,
-                key: "POSTGRES_HOST".into(),
-            })?,
-            port: match env::var("POSTGRES_PORT") {
-                Ok(port) => port.parse()?,
-                Err(err) => match err {
-                    VarError::NotPresent => 5432,
-                    VarError::NotUnicode(_) => {
-                        return Err(ErrorKind::Env {
-                            source: err,
-                            key: "POSTGRES_PORT".into(),
-                        })
-                    }
-                },
-            },
-            user: match env::var("POSTGRES_USER") {
-                Ok(user) => user,
-                Err(err) => match err {
-                    VarError::NotPresent => env::var("USER").map_err(|err| ErrorKind::Env {
+/// Key of a value, which can be set in the config file or in env
+#[derive(Debug, Clone, Copy)]
+struct Key {
+    /// Dotted path to the value in the config file, for example, `database.port`
+    path: &'static str,
+    /// Name of the env variable, which overrides the value
+    env: &'static str,
+}
+
+impl Key {
+    const fn new(path: &'static str, env: &'static str) -> Self {
+        Self { path, env }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` (`{}`)", self.path, self.env)
+    }
+}
+
+/// Layers of the config. Values are looked up in order:
+/// env variable, file from `<env variable>_FILE` env variable, config file.
+/// Empty env variables are treated as unset, so they don't override the config file.
+struct Layers {
+    file: toml::Table,
+}
+
+impl Layers {
+    /// Reads the config file.
+    /// If the path isn't passed, the `CONFIG_PATH` env variable or the default path is used.
+    /// The default file is optional, but the file with the passed path should exist.
+    fn read(path: Option<&Path>) -> Result<Self, ErrorKind> {
+        let path = match path {
+            Some(path) => Some(path.to_owned()),
+            None => match env::var("CONFIG_PATH") {
+                Ok(path) if !path.is_empty() => Some(PathBuf::from(path)),
+                Ok(_) | Err(VarError::NotPresent) => None,
+                Err(err) => {
+                    return Err(ErrorKind::Env {
                         source: err,
-                        key: "POSTGRES_USER and USER".into(),
-                    })?,
-                    VarError::NotUnicode(_) => {
-                        return Err(ErrorKind::Env {
-                            source: err,
-                            key: "POSTGRES_USER".into(),
-                        })
-                    }
-                },
+                        key: "CONFIG_PATH".into(),
+                    })
+                }
             },
-            password: env::var("POSTGRES_PASSWORD").map_err(|err| ErrorKind::Env {
-                source: err,
-                key: "POSTGRES_PASSWORD".into(),
-            })?,
-            db: match env::var("POSTGRES_DB") {
-                Ok(db) => db,
-                Err(err) => match err {
-                    VarError::NotPresent => env::var("USER").map_err(|err| ErrorKind::Env {
-                        source: err,
-                        key: "POSTGRES_DB and USER".into(),
-                    })?,
-                    VarError::NotUnicode(_) => {
-                        return Err(ErrorKind::Env {
-                            source: err,
-                            key: "POSTGRES_DB".into(),
-                        })
-                    }
-                },
+        };
+
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => (PathBuf::from(DEFAULT_CONFIG_PATH), false),
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(err) => return Err(ErrorKind::File { source: err, path }),
+        };
+
+        Self::parse(&content).map_err(|err| ErrorKind::Toml { source: err, path })
+    }
+
+    fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        Ok(Self {
+            file: content.parse()?,
+        })
+    }
+
+    fn env(name: &str) -> Result<Option<String>, VarError> {
+        match env::var(name) {
+            Ok(value) if !value.is_empty() => Ok(Some(value)),
+            Ok(_) | Err(VarError::NotPresent) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns value from the config file as string, arrays are joined with commas
+    fn file_value(&self, key: Key) -> Result<Option<String>, ErrorKind> {
+        let mut segments = key.path.split('.');
+        let Some(mut value) = segments.next().and_then(|segment| self.file.get(segment)) else {
+            return Ok(None);
+        };
+        for segment in segments {
+            match value.get(segment) {
+                Some(nested) => value = nested,
+                None => return Ok(None),
+            }
+        }
+
+        let scalar = |value: &toml::Value| match value {
+            toml::Value::String(value) => Ok(value.clone()),
+            toml::Value::Integer(value) => Ok(value.to_string()),
+            toml::Value::Float(value) => Ok(value.to_string()),
+            toml::Value::Boolean(value) => Ok(value.to_string()),
+            _ => Err(ErrorKind::Invalid {
+                key: key.to_string().into(),
+                message: format!("unsupported type `{}`", value.type_str()).into(),
+            }),
+        };
+
+        match value {
+            toml::Value::Array(values) => values
+                .iter()
+                .map(scalar)
+                .collect::<Result<Vec<_>, _>>()
+                .map(|values| Some(values.join(","))),
+            value => scalar(value).map(Some),
+        }
+    }
+
+    /// Returns raw value of the key from the first layer, which contains it
+    fn raw(&self, key: Key) -> Result<Option<String>, ErrorKind> {
+        let env_error = |err, name: String| ErrorKind::Env {
+            source: err,
+            key: name.into(),
+        };
+
+        if let Some(value) = Self::env(key.env).map_err(|err| env_error(err, key.env.to_owned()))? {
+            return Ok(Some(value));
+        }
+
+        let file_env = format!("{}_FILE", key.env);
+        if let Some(path) = Self::env(&file_env).map_err(|err| env_error(err, file_env))? {
+            let path = PathBuf::from(path);
+
+            return match fs::read_to_string(&path) {
+                // Secret files usually end with a newline, which isn't a part of the value
+                Ok(value) => Ok(Some(value.trim_end_matches(['\n', '\r']).to_owned())),
+                Err(err) => Err(ErrorKind::SecretFile {
+                    source: err,
+                    key: key.to_string().into(),
+                    path,
+                }),
+            };
+        }
+
+        self.file_value(key)
+    }
+
+    fn get<T>(&self, key: Key) -> Result<Option<T>, ErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        // Values aren't trimmed, because spaces can be a part of secrets
+        self.raw(key)?
+            .map(|value| {
+                value.parse().map_err(|err: T::Err| ErrorKind::Invalid {
+                    key: key.to_string().into(),
+                    message: err.to_string().into(),
+                })
+            })
+            .transpose()
+    }
+
+    fn get_or<T>(&self, key: Key, default: T) -> Result<T, ErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key).map(|value| value.unwrap_or(default))
+    }
+
+    fn require<T>(&self, key: Key) -> Result<T, ErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key)?.ok_or_else(|| ErrorKind::Missing {
+            key: key.to_string().into(),
+        })
+    }
+
+    /// Returns duration, which is set in seconds
+    fn get_secs_or(&self, key: Key, default: Duration) -> Result<Duration, ErrorKind> {
+        self.get(key)
+            .map(|secs| secs.map_or(default, Duration::from_secs))
+    }
+
+    /// Returns list of values, which are separated by commas in env and are an array in the config file
+    fn get_list<T>(&self, key: Key) -> Result<Vec<T>, ErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(values) = self.raw(key)? else {
+            return Ok(vec![]);
+        };
+
+        values
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value.parse().map_err(|err: T::Err| ErrorKind::Invalid {
+                    key: key.to_string().into(),
+                    message: err.to_string().into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns value or value of the `USER` env variable, like `psql` does
+    fn get_or_user(&self, key: Key) -> Result<String, ErrorKind> {
+        match self.get(key)? {
+            Some(value) => Ok(value),
+            None => match Self::env("USER") {
+                Ok(Some(user)) => Ok(user),
+                Ok(None) | Err(_) => Err(ErrorKind::Missing {
+                    key: format!("{key} or `USER`").into(),
+                }),
             },
+        }
+    }
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self {
+            file: toml::Table::new(),
+        }
+    }
+}
+
+fn read_database_config(layers: &Layers) -> Result<Database, ErrorKind> {
+    Ok(Database {
+        host: layers.require(Key::new("database.host", "POSTGRES_HOST"))?,
+        port: layers.get_or(Key::new("database.port", "POSTGRES_PORT"), 5432)?,
+        user: layers.get_or_user(Key::new("database.user", "POSTGRES_USER"))?,
+        password: layers.require(Key::new("database.password", "POSTGRES_PASSWORD"))?,
+        db: layers.get_or_user(Key::new("database.db", "POSTGRES_DB"))?,
+        run_migrations: layers
+            .get_or(Key::new("database.run_migrations", "RUN_MIGRATIONS"), false)?,
+        max_connections: layers.get_or(
+            Key::new("database.max_connections", "DATABASE_MAX_CONNECTIONS"),
+            10,
+        )?,
+        min_connections: layers.get_or(
+            Key::new("database.min_connections", "DATABASE_MIN_CONNECTIONS"),
+            0,
+        )?,
+        acquire_timeout: layers.get_secs_or(
+            Key::new("database.acquire_timeout", "DATABASE_ACQUIRE_TIMEOUT"),
+            Duration::from_secs(30),
+        )?,
+    })
+}
+
+fn read_media_parser_worker_config(layers: &Layers) -> Result<MediaParserWorker, ErrorKind> {
+    Ok(MediaParserWorker {
+        start_worker: layers.get_or(Key::new("worker.start", "START_MEDIA_PARSER_WORKER"), true)?,
+        deduplicate_media: layers.get_or(
+            Key::new("worker.deduplicate_media", "MEDIA_DEDUPLICATION"),
+            false,
+        )?,
+        max_hash_distance: layers.get_or(
+            Key::new(
+                "worker.max_hash_distance",
+                "MEDIA_DEDUPLICATION_MAX_DISTANCE",
+            ),
+            4,
+        )?,
+        validate_media: layers
+            .get_or(Key::new("worker.validate_media", "MEDIA_VALIDATION"), true)?,
+        stock_target: layers.get(Key::new("worker.stock_target", "MEDIA_PARSER_STOCK_TARGET"))?,
+        active_users_period: layers.get_secs_or(
+            Key::new(
+                "worker.active_users_period",
+                "MEDIA_PARSER_ACTIVE_USERS_PERIOD",
+            ),
+            Duration::from_secs(7 * 24 * 60 * 60),
+        )?,
+        stock_polling_interval: layers.get_secs_or(
+            Key::new(
+                "worker.stock_polling_interval",
+                "MEDIA_PARSER_STOCK_POLLING_INTERVAL",
+            ),
+            Duration::from_secs(300),
+        )?,
+        duplicates_max_backoff: layers.get_secs_or(
+            Key::new(
+                "worker.duplicates_max_backoff",
+                "MEDIA_PARSER_DUPLICATES_MAX_BACKOFF",
+            ),
+            Duration::from_secs(3600),
+        )?,
+    })
+}
+
+fn read_health_config(layers: &Layers) -> Result<Health, ErrorKind> {
+    Ok(Health {
+        enabled: layers.get_or(Key::new("health.enabled", "HEALTH_ENABLED"), false)?,
+        address: layers.get_or(
+            Key::new("health.address", "HEALTH_ADDRESS"),
+            SocketAddr::from(([0, 0, 0, 0], 8080)),
+        )?,
+        max_poll_age: layers.get_secs_or(
+            Key::new("health.max_poll_age", "HEALTH_MAX_POLL_AGE"),
+            Duration::from_secs(120),
+        )?,
+        max_worker_inactivity: layers.get_secs_or(
+            Key::new(
+                "health.max_worker_inactivity",
+                "HEALTH_MAX_WORKER_INACTIVITY",
+            ),
+            Duration::from_secs(900),
+        )?,
+    })
+}
+
+fn read_webhook_config(layers: &Layers) -> Result<Webhook, ErrorKind> {
+    Ok(Webhook {
+        enabled: layers.get_or(Key::new("webhook.enabled", "WEBHOOK_ENABLED"), false)?,
+        url: layers.get_or(Key::new("webhook.url", "WEBHOOK_URL"), String::new())?,
+        path: layers.get_or(
+            Key::new("webhook.path", "WEBHOOK_PATH"),
+            "/webhook".to_owned(),
+        )?,
+        address: layers.get_or(
+            Key::new("webhook.address", "WEBHOOK_ADDRESS"),
+            SocketAddr::from(([0, 0, 0, 0], 8443)),
+        )?,
+        secret_token: layers.get(Key::new("webhook.secret_token", "WEBHOOK_SECRET_TOKEN"))?,
+        certificate_path: layers.get(Key::new(
+            "webhook.certificate_path",
+            "WEBHOOK_CERTIFICATE_PATH",
+        ))?,
+    })
+}
+
+fn read_config_from_layers(layers: &Layers) -> Result<Config, ErrorKind> {
+    Ok(Config {
+        runtime: Runtime {
+            flavor: layers.get_or(
+                Key::new("runtime.flavor", "RUNTIME_FLAVOR"),
+                RuntimeFlavor::MultiThread,
+            )?,
+            worker_threads: layers
+                .get(Key::new("runtime.worker_threads", "RUNTIME_WORKER_THREADS"))?,
         },
-        media_parser_worker: MediaParserWorker {
-            start_worker: match env::var("START_MEDIA_PARSER_WORKER") {
-                Ok(start_worker) => start_worker.parse()?,
-                Err(err) => match err {
-                    VarError::NotPresent => true,
-                    VarError::NotUnicode(_) => {
-                        return Err(ErrorKind::Env {
-                            source: err,
-                            key: "START_MEDIA_PARSER_WORKER".into(),
-                        })
-                    }
-                },
-            },
+        bot: Bot {
+            token: layers.get_or(Key::new("bot.token", "BOT_TOKEN"), String::new())?,
+            admin_ids: layers.get_list(Key::new("bot.admin_ids", "ADMIN_IDS"))?,
+        },
+        database: read_database_config(layers)?,
+        media_parser_worker: read_media_parser_worker_config(layers)?,
+        sources: Sources {
+            config_path: layers.get(Key::new("sources.config", "MEDIA_PARSER_SOURCES_CONFIG"))?,
+            reload_interval: layers.get_secs_or(
+                Key::new(
+                    "sources.reload_interval",
+                    "MEDIA_PARSER_SOURCES_RELOAD_INTERVAL",
+                ),
+                Duration::from_secs(10),
+            )?,
+        },
+        rate_limits: RateLimits {
+            media_validation_requests_per_second: layers.get_or(
+                Key::new(
+                    "rate_limits.media_validation_requests_per_second",
+                    "MEDIA_VALIDATION_REQUESTS_PER_SECOND",
+                ),
+                NonZeroU32::new(5).unwrap(),
+            )?,
+            media_verifier_requests_per_second: layers.get_or(
+                Key::new(
+                    "rate_limits.media_verifier_requests_per_second",
+                    "MEDIA_VERIFIER_REQUESTS_PER_SECOND",
+                ),
+                NonZeroU32::new(2).unwrap(),
+            )?,
+            media_deduplication_requests_per_second: layers.get_or(
+                Key::new(
+                    "rate_limits.media_deduplication_requests_per_second",
+                    "MEDIA_DEDUPLICATION_REQUESTS_PER_SECOND",
+                ),
+                NonZeroU32::new(1).unwrap(),
+            )?,
+        },
+        media_verifier: MediaVerifier {
+            start_verifier: layers
+                .get_or(Key::new("verifier.start", "START_MEDIA_VERIFIER"), false)?,
+            recheck_interval: layers.get_secs_or(
+                Key::new(
+                    "verifier.recheck_interval",
+                    "MEDIA_VERIFIER_RECHECK_INTERVAL",
+                ),
+                Duration::from_secs(7 * 24 * 60 * 60),
+            )?,
+        },
+        metrics: Metrics {
+            enabled: layers.get_or(Key::new("metrics.enabled", "METRICS_ENABLED"), false)?,
+            address: layers.get_or(
+                Key::new("metrics.address", "METRICS_ADDRESS"),
+                SocketAddr::from(([0, 0, 0, 0], 9000)),
+            )?,
+        },
+        health: read_health_config(layers)?,
+        webhook: read_webhook_config(layers)?,
+        shutdown: Shutdown {
+            timeout: layers.get_secs_or(
+                Key::new("shutdown.timeout", "SHUTDOWN_TIMEOUT"),
+                Duration::from_secs(30),
+            )?,
+        },
+        localization: Localization {
+            default_language: layers.get_or(
+                Key::new("localization.default_language", "DEFAULT_LANGUAGE"),
+                DEFAULT_LANGUAGE.to_owned(),
+            )?,
         },
     })
 }
+
+/// Reads the config from the config file and env, which overrides it, and validates it.
+/// If the path isn't passed, the `CONFIG_PATH` env variable or `config.toml`, if it exists, is used.
+/// # Arguments
+/// * `path` - Path to the config file.
+/// * `require_bot_token` - If `true`, the bot token should be set.
+/// # Errors
+/// Returns error if the config file can't be read or a value is missing or invalid
+pub fn read_config(path: Option<&Path>, require_bot_token: bool) -> Result<Config, ErrorKind> {
+    let config = read_config_from_layers(&Layers::read(path)?)?;
+    config.validate(require_bot_token)?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_config_from_layers, ErrorKind, Key, Layers};
+
+    use std::{env, fs, time::Duration};
+
+    #[test]
+    fn test_layers() {
+        let layers = Layers::parse(
+            r#"
+            [bot]
+            token = "file-token"
+            admin_ids = [1, 2]
+
+            [database]
+            port = 5433
+            host = "file-host"
+            acquire_timeout = 5
+            "#,
+        )
+        .unwrap();
+
+        env::set_var("TEST_LAYERS_DATABASE_HOST", "env-host");
+        env::set_var("TEST_LAYERS_DATABASE_PORT", "");
+
+        let secret_path = env::temp_dir().join("test_layers_bot_token");
+        fs::write(&secret_path, " secret-token \n").unwrap();
+        env::set_var("TEST_LAYERS_BOT_TOKEN_FILE", &secret_path);
+
+        // Env overrides the config file, empty env is unset
+        assert_eq!(
+            layers
+                .require::<String>(Key::new("database.host", "TEST_LAYERS_DATABASE_HOST"))
+                .unwrap(),
+            "env-host",
+        );
+        assert_eq!(
+            layers
+                .get_or::<i16>(Key::new("database.port", "TEST_LAYERS_DATABASE_PORT"), 5432)
+                .unwrap(),
+            5433,
+        );
+        // Secret file overrides the config file, and only its trailing newline is trimmed
+        assert_eq!(
+            layers
+                .require::<String>(Key::new("bot.token", "TEST_LAYERS_BOT_TOKEN"))
+                .unwrap(),
+            " secret-token ",
+        );
+        assert_eq!(
+            layers
+                .get_list::<i64>(Key::new("bot.admin_ids", "TEST_LAYERS_ADMIN_IDS"))
+                .unwrap(),
+            [1, 2],
+        );
+        assert_eq!(
+            layers
+                .get_secs_or(
+                    Key::new("database.acquire_timeout", "TEST_LAYERS_ACQUIRE_TIMEOUT"),
+                    Duration::from_secs(30),
+                )
+                .unwrap(),
+            Duration::from_secs(5),
+        );
+        assert_eq!(
+            layers
+                .get_secs_or(
+                    Key::new("shutdown.timeout", "TEST_LAYERS_SHUTDOWN_TIMEOUT"),
+                    Duration::from_secs(30),
+                )
+                .unwrap(),
+            Duration::from_secs(30),
+        );
+
+        // Errors name the key
+        let err = layers
+            .get::<bool>(Key::new("database.host", "TEST_LAYERS_DATABASE_HOST"))
+            .unwrap_err();
+        assert!(matches!(err, ErrorKind::Invalid { .. }));
+        assert!(err
+            .to_string()
+            .contains("`database.host` (`TEST_LAYERS_DATABASE_HOST`)"));
+
+        let err = layers
+            .require::<String>(Key::new(
+                "database.password",
+                "TEST_LAYERS_DATABASE_PASSWORD",
+            ))
+            .unwrap_err();
+        assert!(matches!(err, ErrorKind::Missing { .. }));
+        assert!(err.to_string().contains("`database.password`"));
+
+        fs::remove_file(secret_path).unwrap();
+    }
+
+    #[test]
+    fn test_validate_empty_token() {
+        let layers = Layers::parse(
+            r#"
+            [bot]
+            token = " "
+
+            [database]
+            host = "localhost"
+            user = "postgres"
+            password = "postgres"
+            db = "postgres"
+            "#,
+        )
+        .unwrap();
+
+        let config = read_config_from_layers(&layers).unwrap();
+
+        let err = config.validate(true).unwrap_err();
+        assert!(matches!(err, ErrorKind::Invalid { .. }));
+        assert!(err.to_string().contains("`bot.token` (`BOT_TOKEN`)"));
+        assert!(err.to_string().contains("`BOT_TOKEN_FILE`"));
+
+        // Commands other than `serve` don't need the token
+        config.validate(false).unwrap();
+    }
+
+    #[test]
+    fn test_validate_default_language() {
+        let layers = Layers::parse(
+            r#"
+            [database]
+            host = "localhost"
+            user = "postgres"
+            password = "postgres"
+            db = "postgres"
+
+            [localization]
+            default_language = "de"
+            "#,
+        )
+        .unwrap();
+
+        let err = read_config_from_layers(&layers)
+            .unwrap()
+            .validate(false)
+            .unwrap_err();
+        assert!(matches!(err, ErrorKind::Invalid { .. }));
+        assert!(err
+            .to_string()
+            .contains("`localization.default_language` (`DEFAULT_LANGUAGE`)"));
+    }
+}
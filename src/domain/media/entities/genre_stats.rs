@@ -5,17 +5,17 @@ pub struct GenreStats {
     pub total: i64,
     pub genre: String,
     pub media_type: String,
-    pub is_sfw: bool,
+    pub age_restriction: String,
 }
 
 impl Display for GenreStats {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "/{genre}_{media_type}_{is_sfw}: {total}",
+            "/{genre}_{media_type}_{age_restriction}: {total}",
             genre = self.genre,
             media_type = self.media_type,
-            is_sfw = if self.is_sfw { "sfw" } else { "nsfw" },
+            age_restriction = self.age_restriction,
             total = self.total
         )
     }
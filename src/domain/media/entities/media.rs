@@ -1,3 +1,5 @@
+use crate::domain::media::value_objects::SendMethod;
+
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -8,7 +10,24 @@ pub struct Media {
     pub url: String,
     pub genre: Option<String>,
     pub media_type: String,
-    pub is_sfw: Option<bool>,
+    pub age_restriction: String,
     pub source_id: Uuid,
     pub created: OffsetDateTime,
+    /// MIME type of the content. It's `None` if the media isn't validated yet.
+    pub mime: Option<String>,
+    pub size: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+impl Media {
+    pub fn send_method(&self) -> SendMethod {
+        SendMethod::from_file_info(
+            &self.media_type,
+            self.mime.as_deref(),
+            self.size,
+            self.width,
+            self.height,
+        )
+    }
 }
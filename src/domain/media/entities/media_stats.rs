@@ -5,8 +5,10 @@ pub struct MediaStats {
     pub total: i64,
     pub gif: i64,
     pub image: i64,
+    pub video: i64,
     pub unknown: i64,
     pub sfw: i64,
+    pub questionable: i64,
     pub nsfw: i64,
 }
 
@@ -14,8 +16,15 @@ impl Display for MediaStats {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Total: {}\nGIF: {}\nImage: {}\nUnknown: {}\nSFW: {}\nNSFW: {}",
-            self.total, self.gif, self.image, self.unknown, self.sfw, self.nsfw
+            "Total: {}\nGIF: {}\nImage: {}\nVideo: {}\nUnknown: {}\nSFW: {}\nQuestionable: {}\nNSFW: {}",
+            self.total,
+            self.gif,
+            self.image,
+            self.video,
+            self.unknown,
+            self.sfw,
+            self.questionable,
+            self.nsfw
         )
     }
 }
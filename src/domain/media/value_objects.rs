@@ -0,0 +1,5 @@
+pub mod media_status;
+pub mod send_method;
+
+pub use media_status::MediaStatus;
+pub use send_method::SendMethod;
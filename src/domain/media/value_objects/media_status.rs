@@ -0,0 +1,28 @@
+use std::fmt::Display;
+
+/// Status of a media link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaStatus {
+    /// Media is available or isn't checked yet
+    Active,
+    /// Media isn't available by the url anymore, so it isn't sent to users
+    Broken,
+    /// Media content isn't supported by Telegram, for example, it isn't an image or it's too big, so it isn't sent to users
+    Invalid,
+}
+
+impl MediaStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Broken => "broken",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+impl Display for MediaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::domain::media_parser::value_objects::MediaType;
+
+use std::fmt::Display;
+
+/// Max size of a photo, which Telegram downloads by url
+pub const MAX_PHOTO_SIZE: i64 = 5 * 1024 * 1024;
+/// Max size of other files, which Telegram downloads by url
+pub const MAX_FILE_SIZE: i64 = 20 * 1024 * 1024;
+/// Max sum of width and height of a photo
+const MAX_PHOTO_DIMENSIONS_SUM: i32 = 10_000;
+/// Max ratio of the longest side of a photo to the shortest one
+const MAX_PHOTO_ASPECT_RATIO: i32 = 20;
+
+/// Telegram method, which is used to send media by url
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendMethod {
+    Photo,
+    Animation,
+    Video,
+    Document,
+}
+
+impl SendMethod {
+    /// Returns the method to send media of the type with the file info.
+    /// Media without a known MIME type are sent as documents, same as before media validation, except videos,
+    /// because Telegram doesn't send videos as documents by url.
+    /// MP4 files are sent as animations, because GIFs of many sources are MP4 files without sound, except videos.
+    pub fn from_file_info(
+        media_type: &str,
+        mime: Option<&str>,
+        size: Option<i64>,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Self {
+        let is_video = media_type == MediaType::Video.as_str();
+
+        match mime {
+            Some("video/mp4") | None if is_video => Self::Video,
+            Some("image/gif" | "video/mp4") => Self::Animation,
+            Some("image/jpeg" | "image/png" | "image/webp") => {
+                let fits_size = size.map_or(true, |size| size <= MAX_PHOTO_SIZE);
+                let fits_dimensions = match (width, height) {
+                    (Some(width), Some(height)) if width > 0 && height > 0 => {
+                        width + height <= MAX_PHOTO_DIMENSIONS_SUM
+                            && width.max(height) <= width.min(height) * MAX_PHOTO_ASPECT_RATIO
+                    }
+                    _ => true,
+                };
+
+                if fits_size && fits_dimensions {
+                    Self::Photo
+                } else {
+                    Self::Document
+                }
+            }
+            _ => Self::Document,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Photo => "photo",
+            Self::Animation => "animation",
+            Self::Video => "video",
+            Self::Document => "document",
+        }
+    }
+}
+
+impl Display for SendMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SendMethod, MAX_PHOTO_SIZE};
+
+    #[test]
+    fn test_send_method_from_file_info() {
+        assert_eq!(
+            SendMethod::from_file_info("img", None, None, None, None),
+            SendMethod::Document
+        );
+        assert_eq!(
+            SendMethod::from_file_info("gif", Some("image/gif"), Some(1024), Some(100), Some(100)),
+            SendMethod::Animation
+        );
+        assert_eq!(
+            SendMethod::from_file_info("gif", Some("video/mp4"), None, None, None),
+            SendMethod::Animation
+        );
+        assert_eq!(
+            SendMethod::from_file_info("img", Some("image/png"), Some(1024), Some(800), Some(600)),
+            SendMethod::Photo
+        );
+        assert_eq!(
+            SendMethod::from_file_info("img", Some("image/jpeg"), None, None, None),
+            SendMethod::Photo
+        );
+        // Too big photos and photos with unsupported dimensions are sent as documents
+        assert_eq!(
+            SendMethod::from_file_info(
+                "img",
+                Some("image/jpeg"),
+                Some(MAX_PHOTO_SIZE + 1),
+                None,
+                None
+            ),
+            SendMethod::Document
+        );
+        assert_eq!(
+            SendMethod::from_file_info("img", Some("image/png"), None, Some(8000), Some(4000)),
+            SendMethod::Document
+        );
+        assert_eq!(
+            SendMethod::from_file_info("img", Some("image/png"), None, Some(2100), Some(100)),
+            SendMethod::Document
+        );
+        assert_eq!(
+            SendMethod::from_file_info("img", Some("application/pdf"), None, None, None),
+            SendMethod::Document
+        );
+        assert_eq!(
+            SendMethod::from_file_info("video", Some("video/mp4"), Some(1024), None, None),
+            SendMethod::Video
+        );
+        assert_eq!(
+            SendMethod::from_file_info("video", None, None, None, None),
+            SendMethod::Video
+        );
+        // Video genres can contain GIFs, if the media validator didn't retag them yet
+        assert_eq!(
+            SendMethod::from_file_info("video", Some("image/gif"), None, None, None),
+            SendMethod::Animation
+        );
+    }
+}
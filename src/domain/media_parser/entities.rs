@@ -1,7 +1,9 @@
+pub mod fetch_result;
 pub mod genre;
 pub mod genres;
 pub mod media;
 
+pub use fetch_result::FetchResult;
 pub use genre::Genre;
 pub use genres::Genres;
 pub use media::Media;
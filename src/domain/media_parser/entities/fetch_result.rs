@@ -0,0 +1,34 @@
+use super::{Genre, Media};
+
+use std::time::Duration;
+
+/// Result of fetching a media list of the genre from a source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchResult {
+    Success {
+        genre: Genre,
+        media_list: Vec<Media>,
+        elapsed: Duration,
+    },
+    Failure {
+        genre: Genre,
+        error: String,
+        elapsed: Duration,
+    },
+}
+
+impl FetchResult {
+    /// Returns the genre of the fetch
+    pub const fn genre(&self) -> &Genre {
+        match self {
+            Self::Success { genre, .. } | Self::Failure { genre, .. } => genre,
+        }
+    }
+
+    /// Returns the time spent on the fetch
+    pub const fn elapsed(&self) -> Duration {
+        match self {
+            Self::Success { elapsed, .. } | Self::Failure { elapsed, .. } => *elapsed,
+        }
+    }
+}
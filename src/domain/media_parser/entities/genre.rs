@@ -9,7 +9,7 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
 pub struct Genre {
     name: Cow<'static, GenreName>,
     media_type: MediaType,
@@ -56,6 +56,17 @@ impl Genre {
         Genre::new(name, MediaType::Image, age_restriction)
     }
 
+    /// Creates a new video genre
+    /// # Arguments
+    /// * `name` - The name of the genre
+    /// * `age_restriction` - The age restriction of the genre
+    pub fn new_video(
+        name: impl Into<Cow<'static, GenreName>>,
+        age_restriction: AgeRestriction,
+    ) -> Self {
+        Genre::new(name, MediaType::Video, age_restriction)
+    }
+
     /// Creates a new sfw gif genre
     /// # Arguments
     /// * `name` - The name of the genre
@@ -83,6 +94,20 @@ impl Genre {
     pub fn new_nsfw_image(name: impl Into<Cow<'static, GenreName>>) -> Self {
         Genre::new_image(name, AgeRestriction::Nsfw)
     }
+
+    /// Creates a new sfw video genre
+    /// # Arguments
+    /// * `name` - The name of the genre
+    pub fn new_sfw_video(name: impl Into<Cow<'static, GenreName>>) -> Self {
+        Genre::new_video(name, AgeRestriction::Sfw)
+    }
+
+    /// Creates a new nsfw video genre
+    /// # Arguments
+    /// * `name` - The name of the genre
+    pub fn new_nsfw_video(name: impl Into<Cow<'static, GenreName>>) -> Self {
+        Genre::new_video(name, AgeRestriction::Nsfw)
+    }
 }
 
 /// Macro to create a vector of sfw gif genres
@@ -125,6 +150,26 @@ macro_rules! vec_new_nsfw_image {
 
 pub use vec_new_nsfw_image;
 
+/// Macro to create a vector of sfw video genres
+#[macro_export]
+macro_rules! vec_new_sfw_video {
+    ($($name:expr),* $(,)?) => {
+        vec![$(Genre::new_sfw_video($name),)*]
+    };
+}
+
+pub use vec_new_sfw_video;
+
+/// Macro to create a vector of nsfw video genres
+#[macro_export]
+macro_rules! vec_new_nsfw_video {
+    ($($name:expr),* $(,)?) => {
+        vec![$(Genre::new_nsfw_video($name),)*]
+    };
+}
+
+pub use vec_new_nsfw_video;
+
 impl Genre {
     /// Returns the name of the genre
     pub fn name(&self) -> &GenreName {
@@ -148,6 +193,11 @@ impl Genre {
         self.age_restriction.is_sfw()
     }
 
+    /// Returns `true` if the age restriction is [`AgeRestriction::Questionable`]
+    pub const fn is_questionable(&self) -> bool {
+        self.age_restriction.is_questionable()
+    }
+
     /// Returns `true` if the age restriction is [`AgeRestriction::Nsfw`]
     pub const fn is_nsfw(&self) -> bool {
         self.age_restriction.is_nsfw()
@@ -168,6 +218,11 @@ impl Genre {
         self.media_type.is_image()
     }
 
+    /// Returns `true` if the media type is [`MediaType::Video`]
+    pub const fn is_video(&self) -> bool {
+        self.media_type.is_video()
+    }
+
     /// Returns `true` if the media type is [`MediaType::Unknown`]
     pub const fn media_type_is_unknown(&self) -> bool {
         self.media_type.is_unknown()
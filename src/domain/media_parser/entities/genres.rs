@@ -85,6 +85,13 @@ impl Genres {
         self.filter_media_type(MediaType::Image)
     }
 
+    /// Filters the genres by video media type
+    /// # Returns
+    /// The filtered genres
+    pub fn videos(&self) -> Vec<Genre> {
+        self.filter_media_type(MediaType::Video)
+    }
+
     /// Filters the genres by sfw age restriction
     /// # Returns
     /// The filtered genres
@@ -126,6 +133,20 @@ impl Genres {
     pub fn nsfw_images(&self) -> Vec<Genre> {
         self.filter(Some(MediaType::Image), Some(AgeRestriction::Nsfw))
     }
+
+    /// Filters the genres by sfw video media type
+    /// # Returns
+    /// The filtered genres
+    pub fn sfw_videos(&self) -> Vec<Genre> {
+        self.filter(Some(MediaType::Video), Some(AgeRestriction::Sfw))
+    }
+
+    /// Filters the genres by nsfw video media type
+    /// # Returns
+    /// The filtered genres
+    pub fn nsfw_videos(&self) -> Vec<Genre> {
+        self.filter(Some(MediaType::Video), Some(AgeRestriction::Nsfw))
+    }
 }
 
 impl Genres {
@@ -197,6 +218,15 @@ impl Genres {
         self.contains_filter_media_type(genre, MediaType::Image)
     }
 
+    /// Checks if the video genres contains the given genre
+    /// # Arguments
+    /// * `genre` - The genre to check
+    /// # Returns
+    /// `true` if the video genres contains the given genre, `false` otherwise
+    pub fn contains_videos(&self, genre: &Genre) -> bool {
+        self.contains_filter_media_type(genre, MediaType::Video)
+    }
+
     /// Checks if the sfw genres contains the given genre
     /// # Arguments
     /// * `genre` - The genre to check
@@ -3,10 +3,13 @@ use crate::domain::media_parser::exceptions::AgeRestrictionParse as AgeRestricti
 use serde::Deserialize;
 use std::fmt::Display;
 
-/// Age restriction of a media
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+/// Age restriction of a media.
+/// Levels are ordered from the least to the most restrictive: [`AgeRestriction::Sfw`], [`AgeRestriction::Questionable`], [`AgeRestriction::Nsfw`].
+/// [`AgeRestriction::Unknown`] is treated as the most restrictive level, because we can't be sure that the media is safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum AgeRestriction {
     Sfw,
+    Questionable,
     Nsfw,
     Unknown,
 }
@@ -17,6 +20,11 @@ impl AgeRestriction {
         matches!(self, Self::Sfw)
     }
 
+    /// Returns `true` if the age restriction is [`AgeRestriction::Questionable`]
+    pub const fn is_questionable(self) -> bool {
+        matches!(self, Self::Questionable)
+    }
+
     /// Returns `true` if the age restriction is [`AgeRestriction::Nsfw`]
     pub const fn is_nsfw(self) -> bool {
         matches!(self, Self::Nsfw)
@@ -28,22 +36,48 @@ impl AgeRestriction {
     }
 }
 
-impl Display for AgeRestriction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl AgeRestriction {
+    /// Returns the restriction level, the higher the level, the more restrictive the age restriction
+    pub const fn level(self) -> u8 {
+        match self {
+            Self::Sfw => 0,
+            Self::Questionable => 1,
+            Self::Nsfw | Self::Unknown => 2,
+        }
+    }
+
+    /// Returns `true` if the age restriction is allowed for a user with the given maximum age restriction
+    /// # Arguments
+    /// * `max_age_restriction` - The maximum age restriction chosen by the user
+    pub const fn is_allowed_by(self, max_age_restriction: Self) -> bool {
+        self.level() <= max_age_restriction.level()
+    }
+}
+
+impl AgeRestriction {
+    pub const fn as_str(self) -> &'static str {
         match self {
-            AgeRestriction::Sfw => write!(f, "sfw"),
-            AgeRestriction::Nsfw => write!(f, "nsfw"),
-            AgeRestriction::Unknown => write!(f, "unknown"),
+            Self::Sfw => "sfw",
+            Self::Questionable => "questionable",
+            Self::Nsfw => "nsfw",
+            Self::Unknown => "unknown",
         }
     }
 }
 
+impl Display for AgeRestriction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl<'a> TryFrom<&'a str> for AgeRestriction {
     type Error = AgeRestrictionParseError<'a>;
 
     fn try_from(raw_age_restriction: &'a str) -> Result<Self, Self::Error> {
         match raw_age_restriction {
             "sfw" => Ok(Self::Sfw),
+            "questionable" => Ok(Self::Questionable),
             "nsfw" => Ok(Self::Nsfw),
             "" | "unknown" => Ok(Self::Unknown),
             _ => Err(AgeRestrictionParseError::new(
@@ -64,6 +98,10 @@ mod tests {
         assert!(!AgeRestriction::Sfw.is_nsfw());
         assert!(!AgeRestriction::Sfw.is_unknown());
 
+        assert!(AgeRestriction::Questionable.is_questionable());
+        assert!(!AgeRestriction::Questionable.is_sfw());
+        assert!(!AgeRestriction::Questionable.is_nsfw());
+
         assert!(!AgeRestriction::Nsfw.is_sfw());
         assert!(AgeRestriction::Nsfw.is_nsfw());
         assert!(!AgeRestriction::Nsfw.is_unknown());
@@ -73,12 +111,34 @@ mod tests {
         assert!(AgeRestriction::Unknown.is_unknown());
     }
 
+    #[test]
+    fn test_age_restriction_is_allowed_by() {
+        assert!(AgeRestriction::Sfw.is_allowed_by(AgeRestriction::Sfw));
+        assert!(!AgeRestriction::Questionable.is_allowed_by(AgeRestriction::Sfw));
+        assert!(!AgeRestriction::Nsfw.is_allowed_by(AgeRestriction::Sfw));
+        assert!(!AgeRestriction::Unknown.is_allowed_by(AgeRestriction::Sfw));
+
+        assert!(AgeRestriction::Sfw.is_allowed_by(AgeRestriction::Questionable));
+        assert!(AgeRestriction::Questionable.is_allowed_by(AgeRestriction::Questionable));
+        assert!(!AgeRestriction::Nsfw.is_allowed_by(AgeRestriction::Questionable));
+        assert!(!AgeRestriction::Unknown.is_allowed_by(AgeRestriction::Questionable));
+
+        assert!(AgeRestriction::Sfw.is_allowed_by(AgeRestriction::Nsfw));
+        assert!(AgeRestriction::Questionable.is_allowed_by(AgeRestriction::Nsfw));
+        assert!(AgeRestriction::Nsfw.is_allowed_by(AgeRestriction::Nsfw));
+        assert!(AgeRestriction::Unknown.is_allowed_by(AgeRestriction::Nsfw));
+    }
+
     #[test]
     fn test_age_restriction_from_str() {
         assert_eq!(
             AgeRestriction::try_from("sfw").unwrap(),
             AgeRestriction::Sfw
         );
+        assert_eq!(
+            AgeRestriction::try_from("questionable").unwrap(),
+            AgeRestriction::Questionable
+        );
         assert_eq!(
             AgeRestriction::try_from("nsfw").unwrap(),
             AgeRestriction::Nsfw
@@ -91,5 +151,6 @@ mod tests {
             AgeRestriction::try_from("").unwrap(),
             AgeRestriction::Unknown
         );
+        assert!(AgeRestriction::try_from("test").is_err());
     }
 }
@@ -3,10 +3,11 @@ use crate::domain::media_parser::exceptions::MediaTypeParse as MediaTypeParseErr
 use serde::Deserialize;
 use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum MediaType {
     Gif,
     Image,
+    Video,
     Unknown,
 }
 
@@ -21,6 +22,11 @@ impl MediaType {
         matches!(self, Self::Image)
     }
 
+    /// Returns `true` if the media type is [`MediaType::Video`]
+    pub const fn is_video(self) -> bool {
+        matches!(self, Self::Video)
+    }
+
     /// Returns `true` if the media type is [`MediaType::Unknown`]
     pub const fn is_unknown(self) -> bool {
         matches!(self, Self::Unknown)
@@ -32,6 +38,7 @@ impl MediaType {
         match self {
             Self::Gif => "gif",
             Self::Image => "img",
+            Self::Video => "video",
             Self::Unknown => "unknown",
         }
     }
@@ -50,6 +57,7 @@ impl<'a> TryFrom<&'a str> for MediaType {
         match raw_media_type {
             "gif" => Ok(Self::Gif),
             "img" => Ok(Self::Image),
+            "video" => Ok(Self::Video),
             "" | "unknown" => Ok(Self::Unknown),
             _ => Err(MediaTypeParseError::new(
                 raw_media_type,
@@ -67,6 +75,7 @@ mod tests {
     fn test_media_type() {
         assert!(MediaType::Gif.is_gif());
         assert!(MediaType::Image.is_image());
+        assert!(MediaType::Video.is_video());
         assert!(MediaType::Unknown.is_unknown());
     }
 
@@ -74,6 +83,7 @@ mod tests {
     fn test_media_type_from_str() {
         assert_eq!(MediaType::try_from("gif").unwrap(), MediaType::Gif);
         assert_eq!(MediaType::try_from("img").unwrap(), MediaType::Image);
+        assert_eq!(MediaType::try_from("video").unwrap(), MediaType::Video);
         assert_eq!(MediaType::try_from("unknown").unwrap(), MediaType::Unknown);
         assert_eq!(MediaType::try_from("").unwrap(), MediaType::Unknown);
         assert!(MediaType::try_from("test").is_err());
@@ -1,3 +1,7 @@
 pub mod source;
+pub mod source_fetch_stats;
+pub mod source_paused_genre;
 
 pub use source::Source;
+pub use source_fetch_stats::SourceFetchStats;
+pub use source_paused_genre::SourcePausedGenre;
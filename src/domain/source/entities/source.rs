@@ -1,3 +1,4 @@
+use std::time::Duration;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -6,5 +7,14 @@ pub struct Source {
     pub id: Uuid,
     pub name: String,
     pub url: String,
+    pub enabled: bool,
+    pub polling_interval_ms: i64,
     pub created: OffsetDateTime,
 }
+
+impl Source {
+    /// Returns time between fetches of genres of the source
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(u64::try_from(self.polling_interval_ms).unwrap_or_default())
+    }
+}
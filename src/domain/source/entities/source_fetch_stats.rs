@@ -0,0 +1,174 @@
+use std::fmt::{self, Display, Formatter};
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFetchStats {
+    pub source_name: String,
+    pub source_enabled: bool,
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    pub success_count: i64,
+    pub failure_count: i64,
+    pub new_media_count: i64,
+    pub duplicate_media_count: i64,
+    pub total_latency_ms: i64,
+    pub last_latency_ms: i64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<OffsetDateTime>,
+    pub last_success_at: Option<OffsetDateTime>,
+}
+
+impl SourceFetchStats {
+    /// Returns average latency of fetches in milliseconds
+    pub const fn average_latency_ms(&self) -> i64 {
+        let count = self.success_count + self.failure_count;
+
+        if count == 0 {
+            0
+        } else {
+            self.total_latency_ms / count
+        }
+    }
+
+    /// Returns `true` if the last fetch failed
+    pub fn is_failing(&self) -> bool {
+        match (self.last_error_at, self.last_success_at) {
+            (Some(last_error_at), Some(last_success_at)) => last_error_at > last_success_at,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Returns time of the last fetch, successful or not
+    pub fn last_fetch_at(&self) -> Option<OffsetDateTime> {
+        self.last_error_at.max(self.last_success_at)
+    }
+}
+
+impl Display for SourceFetchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{status} {source_name} /{genre}_{media_type}_{age_restriction}: \
+            {success_count} ok, {failure_count} failed, \
+            {new_media_count} new, {duplicate_media_count} duplicates, \
+            {average_latency_ms}ms avg",
+            status = if !self.source_enabled {
+                "⏸"
+            } else if self.is_failing() {
+                "❌"
+            } else {
+                "✅"
+            },
+            source_name = self.source_name,
+            genre = self.genre,
+            media_type = self.media_type,
+            age_restriction = self.age_restriction,
+            success_count = self.success_count,
+            failure_count = self.failure_count,
+            new_media_count = self.new_media_count,
+            duplicate_media_count = self.duplicate_media_count,
+            average_latency_ms = self.average_latency_ms(),
+        )?;
+
+        if let Some(last_success_at) = self.last_success_at {
+            write!(f, "\nLast success: {last_success_at}")?;
+        }
+        if let (true, Some(last_error)) = (self.is_failing(), &self.last_error) {
+            write!(f, "\nLast error: {last_error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceFetchStats;
+
+    use time::{Duration, OffsetDateTime};
+
+    fn fetch_stats() -> SourceFetchStats {
+        SourceFetchStats {
+            source_name: "test".to_owned(),
+            source_enabled: true,
+            genre: "neko".to_owned(),
+            media_type: "gif".to_owned(),
+            age_restriction: "sfw".to_owned(),
+            success_count: 3,
+            failure_count: 1,
+            new_media_count: 10,
+            duplicate_media_count: 20,
+            total_latency_ms: 400,
+            last_latency_ms: 100,
+            last_error: None,
+            last_error_at: None,
+            last_success_at: None,
+        }
+    }
+
+    #[test]
+    fn test_average_latency() {
+        let stats = fetch_stats();
+
+        assert_eq!(stats.average_latency_ms(), 100);
+
+        let stats = SourceFetchStats {
+            success_count: 0,
+            failure_count: 0,
+            total_latency_ms: 0,
+            ..fetch_stats()
+        };
+
+        assert_eq!(stats.average_latency_ms(), 0);
+    }
+
+    #[test]
+    fn test_is_failing() {
+        let now = OffsetDateTime::now_utc();
+
+        assert!(!fetch_stats().is_failing());
+        assert!(SourceFetchStats {
+            last_error_at: Some(now),
+            ..fetch_stats()
+        }
+        .is_failing());
+        assert!(SourceFetchStats {
+            last_error_at: Some(now),
+            last_success_at: Some(now - Duration::minutes(1)),
+            ..fetch_stats()
+        }
+        .is_failing());
+        assert!(!SourceFetchStats {
+            last_error_at: Some(now - Duration::minutes(1)),
+            last_success_at: Some(now),
+            ..fetch_stats()
+        }
+        .is_failing());
+    }
+
+    #[test]
+    fn test_last_fetch_at() {
+        let now = OffsetDateTime::now_utc();
+
+        assert_eq!(fetch_stats().last_fetch_at(), None);
+        assert_eq!(
+            SourceFetchStats {
+                last_error_at: Some(now - Duration::minutes(1)),
+                last_success_at: Some(now),
+                ..fetch_stats()
+            }
+            .last_fetch_at(),
+            Some(now)
+        );
+        assert_eq!(
+            SourceFetchStats {
+                last_error_at: Some(now),
+                ..fetch_stats()
+            }
+            .last_fetch_at(),
+            Some(now)
+        );
+    }
+}
@@ -0,0 +1,7 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePausedGenre {
+    pub source_name: String,
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+}
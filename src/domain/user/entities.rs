@@ -1,3 +1,5 @@
 pub mod user;
+pub mod user_stats;
 
 pub use user::User;
+pub use user_stats::UserStats;
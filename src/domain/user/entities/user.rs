@@ -1,3 +1,5 @@
+use crate::domain::media_parser::value_objects::AgeRestriction;
+
 use telers::extractors::FromContext;
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -8,6 +10,18 @@ pub struct User {
     pub id: Uuid,
     pub tg_id: i64,
     pub language_code: Option<String>,
-    pub show_nsfw: Option<bool>,
+    /// The most restrictive age restriction of media, which the user allowed to show
+    pub max_age_restriction: String,
     pub created: OffsetDateTime,
 }
+
+impl User {
+    /// Returns the parsed maximum age restriction.
+    /// Unknown values fall back to [`AgeRestriction::Sfw`], so the user sees only safe media.
+    pub fn max_age_restriction(&self) -> AgeRestriction {
+        match AgeRestriction::try_from(self.max_age_restriction.as_str()) {
+            Ok(AgeRestriction::Unknown) | Err(_) => AgeRestriction::Sfw,
+            Ok(age_restriction) => age_restriction,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserStats {
+    pub total: i64,
+    pub show_questionable: i64,
+    pub show_nsfw: i64,
+    pub created_last_day: i64,
+    pub created_last_week: i64,
+    /// Users, who viewed media in the last week
+    pub active_last_week: i64,
+}
+
+impl Display for UserStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Total: {}\nShow questionable: {}\nShow NSFW: {}\nNew in the last day: {}\nNew in the last week: {}\nActive in the last week: {}",
+            self.total,
+            self.show_questionable,
+            self.show_nsfw,
+            self.created_last_day,
+            self.created_last_week,
+            self.active_last_week,
+        )
+    }
+}
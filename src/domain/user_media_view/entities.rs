@@ -1,3 +1,11 @@
+pub mod genre_view_stats;
+pub mod user_genre_stats;
+pub mod user_genres_stats;
 pub mod user_media_view;
+pub mod user_media_view_with_media;
 
+pub use genre_view_stats::GenreViewStats;
+pub use user_genre_stats::UserGenreStats;
+pub use user_genres_stats::UserGenresStats;
 pub use user_media_view::UserMediaView;
+pub use user_media_view_with_media::UserMediaViewWithMedia;
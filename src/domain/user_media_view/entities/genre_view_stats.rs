@@ -0,0 +1,11 @@
+/// Views of media of a genre by active users
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenreViewStats {
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    /// Max count of media of the genre viewed by one active user
+    pub max_viewed: i64,
+    /// Count of views of media of the genre by active users since they're active
+    pub recent_views: i64,
+}
@@ -0,0 +1,31 @@
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserGenreStats {
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    pub total: i64,
+    pub viewed: i64,
+}
+
+impl UserGenreStats {
+    /// Returns count of media of the genre, which the user hasn't viewed yet
+    pub const fn unviewed(&self) -> i64 {
+        self.total - self.viewed
+    }
+}
+
+impl Display for UserGenreStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "/{genre}_{media_type}_{age_restriction}: {viewed} viewed, {unviewed} left",
+            genre = self.genre,
+            media_type = self.media_type,
+            age_restriction = self.age_restriction,
+            viewed = self.viewed,
+            unviewed = self.unviewed(),
+        )
+    }
+}
@@ -0,0 +1,118 @@
+use super::UserGenreStats;
+
+use std::{
+    cmp::Reverse,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserGenresStats(pub Vec<UserGenreStats>);
+
+impl UserGenresStats {
+    /// Returns total count of media viewed by the user
+    pub fn viewed(&self) -> i64 {
+        self.0.iter().map(|genre| genre.viewed).sum()
+    }
+
+    /// Returns count of media with the media type viewed by the user
+    pub fn viewed_by_media_type(&self, media_type: &str) -> i64 {
+        self.0
+            .iter()
+            .filter(|genre| genre.media_type == media_type)
+            .map(|genre| genre.viewed)
+            .sum()
+    }
+
+    /// Returns the genre with the most views of the user.
+    /// If the user hasn't viewed anything, returns `None`
+    pub fn favourite(&self) -> Option<&UserGenreStats> {
+        self.0
+            .iter()
+            .filter(|genre| genre.viewed > 0)
+            .max_by_key(|genre| genre.viewed)
+    }
+
+    /// Returns the count of genres with the most views of the user, sorted by views.
+    /// Genres with the same count of views keep their order.
+    pub fn most_viewed(&self, count: usize) -> Self {
+        let mut genres = self.0.clone();
+        genres.sort_by_key(|genre| Reverse(genre.viewed));
+        genres.truncate(count);
+
+        Self(genres)
+    }
+}
+
+impl From<Vec<UserGenreStats>> for UserGenresStats {
+    fn from(genres: Vec<UserGenreStats>) -> Self {
+        Self(genres)
+    }
+}
+
+impl Display for UserGenresStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for genre in &self.0 {
+            writeln!(f, "{genre}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserGenreStats, UserGenresStats};
+
+    fn genre_stats(genre: &str, media_type: &str, total: i64, viewed: i64) -> UserGenreStats {
+        UserGenreStats {
+            genre: genre.to_owned(),
+            media_type: media_type.to_owned(),
+            age_restriction: "sfw".to_owned(),
+            total,
+            viewed,
+        }
+    }
+
+    #[test]
+    fn test_user_genres_stats() {
+        let stats = UserGenresStats::from(vec![
+            genre_stats("neko", "gif", 10, 3),
+            genre_stats("hug", "gif", 5, 5),
+            genre_stats("neko", "img", 20, 1),
+            genre_stats("kiss", "img", 7, 0),
+        ]);
+
+        assert_eq!(stats.viewed(), 9);
+        assert_eq!(stats.viewed_by_media_type("gif"), 8);
+        assert_eq!(stats.viewed_by_media_type("img"), 1);
+        assert_eq!(stats.viewed_by_media_type("unknown"), 0);
+        assert_eq!(
+            stats.favourite().map(|genre| genre.genre.as_str()),
+            Some("hug")
+        );
+        assert_eq!(stats.0[0].unviewed(), 7);
+        assert_eq!(stats.0[1].unviewed(), 0);
+        assert_eq!(
+            stats
+                .most_viewed(3)
+                .0
+                .iter()
+                .map(|genre| (genre.genre.as_str(), genre.media_type.as_str()))
+                .collect::<Vec<_>>(),
+            [("hug", "gif"), ("neko", "gif"), ("neko", "img")],
+        );
+        assert_eq!(stats.most_viewed(10).0.len(), 4);
+    }
+
+    #[test]
+    fn test_user_genres_stats_without_views() {
+        let stats = UserGenresStats::from(vec![genre_stats("neko", "gif", 10, 0)]);
+
+        assert_eq!(stats.viewed(), 0);
+        assert_eq!(stats.favourite(), None);
+
+        let stats = UserGenresStats::from(vec![]);
+
+        assert_eq!(stats.viewed(), 0);
+        assert_eq!(stats.favourite(), None);
+    }
+}
@@ -0,0 +1,8 @@
+use super::UserMediaView;
+use crate::domain::media::entities::Media;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserMediaViewWithMedia {
+    pub view: UserMediaView,
+    pub media: Media,
+}
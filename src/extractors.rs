@@ -1,5 +1,9 @@
 pub mod media_parser_sources;
+pub mod shutdown;
 pub mod uow_factory;
 
-pub use media_parser_sources::MediaParserSourceWrapper;
+pub use media_parser_sources::{
+    MediaParserControllerWrapper, MediaParserSourceWrapper, MediaParserSourcesReloaderWrapper,
+};
+pub use shutdown::ShutdownWrapper;
 pub use uow_factory::UoWFactoryWrapper;
@@ -1,4 +1,7 @@
-use crate::application::media_parser::traits::Source;
+use crate::{
+    application::media_parser::traits::Source,
+    infrastructure::media_parser::{Controller, SourcesReloader},
+};
 
 use std::sync::Arc;
 use telers::FromContext;
@@ -12,3 +15,23 @@ impl From<Vec<Arc<dyn Source>>> for MediaParserSourceWrapper {
         Self(sources)
     }
 }
+
+#[derive(FromContext)]
+#[context(key = "media_parser_sources_reloader", from = Arc<SourcesReloader>)]
+pub struct MediaParserSourcesReloaderWrapper(pub Arc<SourcesReloader>);
+
+impl From<Arc<SourcesReloader>> for MediaParserSourcesReloaderWrapper {
+    fn from(reloader: Arc<SourcesReloader>) -> Self {
+        Self(reloader)
+    }
+}
+
+#[derive(FromContext)]
+#[context(key = "media_parser_controller", from = Controller)]
+pub struct MediaParserControllerWrapper(pub Controller);
+
+impl From<Controller> for MediaParserControllerWrapper {
+    fn from(controller: Controller) -> Self {
+        Self(controller)
+    }
+}
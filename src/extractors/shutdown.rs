@@ -0,0 +1,13 @@
+use crate::infrastructure::shutdown::Shutdown;
+
+use telers::FromContext;
+
+#[derive(FromContext)]
+#[context(key = "shutdown", from = Shutdown)]
+pub struct ShutdownWrapper(pub Shutdown);
+
+impl From<Shutdown> for ShutdownWrapper {
+    fn from(shutdown: Shutdown) -> Self {
+        Self(shutdown)
+    }
+}
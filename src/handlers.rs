@@ -1,3 +1,5 @@
+pub mod admin;
+pub mod history;
 pub mod media;
 pub mod source;
 pub mod start;
@@ -0,0 +1,547 @@
+use crate::{
+    application::{
+        common::traits::{UnitOfWork, UnitOfWorkFactory},
+        media_parser::traits::Source,
+        source::dto::{UpdateSourceEnabled, UpdateSourceGenrePaused, UpdateSourcePollingInterval},
+    },
+    domain::media_parser::entities::Genre,
+    extractors::{
+        MediaParserControllerWrapper, MediaParserSourceWrapper, MediaParserSourcesReloaderWrapper,
+        UoWFactoryWrapper,
+    },
+    infrastructure::media_parser::{
+        sources::ErrorKind as SourcesErrorKind, ControlCommand, Controller,
+    },
+};
+
+use std::{sync::Arc, time::Duration};
+use telers::{
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    filters::CommandObject,
+    methods::SendMessage,
+    types::{MessageText, ReplyParameters},
+    Bot,
+};
+use tracing::{event, instrument, Level, Span};
+
+/// Max length of a text message in Telegram
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// Note for replies to commands, which aren't received by workers
+const WORKER_NOT_RUNNING_NOTE: &str =
+    "The worker isn't running in the bot process, so the change is applied when it reads settings of sources";
+
+/// Finds the source by the first argument of a command and its genre by the second one, if it's passed.
+/// Returns text of the reply, if the source or the genre isn't found.
+fn find_source_and_genre(
+    sources: &[Arc<dyn Source>],
+    args: &[&str],
+) -> Result<(Arc<dyn Source>, Option<Genre>), String> {
+    let Some(source_name) = args.first() else {
+        return Err(format!(
+            "Pass name of the source. Sources: {}",
+            sources
+                .iter()
+                .map(|source| source.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    };
+
+    let Some(source) = sources.iter().find(|source| source.name() == *source_name) else {
+        return Err(format!("Unknown source `{source_name}`"));
+    };
+
+    let Some(raw_genre) = args.get(1) else {
+        return Ok((source.clone(), None));
+    };
+
+    match Genre::try_from(*raw_genre) {
+        Ok(genre) if source.genres().contains(&genre) => Ok((source.clone(), Some(genre))),
+        _ => Err(format!(
+            "Unknown genre `{raw_genre}` of source `{source_name}`. \
+            Genres are in the same format as commands, for example, `neko_img_sfw`"
+        )),
+    }
+}
+
+/// Joins entries into texts, which fit into a message
+fn split_entries(entries: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut texts = vec![];
+    let mut text = String::new();
+
+    for entry in entries {
+        if !text.is_empty() && text.chars().count() + entry.chars().count() + 2 > MAX_MESSAGE_LENGTH
+        {
+            texts.push(std::mem::take(&mut text));
+        }
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&entry);
+    }
+    if !text.is_empty() {
+        texts.push(text);
+    }
+
+    texts
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn source_stats<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let only_failing = CommandObject::extract(&text).map_or(false, |command| {
+        command.args.first().map_or(false, |arg| arg == "failing")
+    });
+
+    event!(Level::DEBUG, only_failing, "Getting source fetch stats");
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let fetch_stats = uow
+        .source_reader()
+        .await
+        .map_err(HandlerError::new)?
+        .get_fetch_stats()
+        .await
+        .map_err(HandlerError::new)?;
+
+    drop(uow);
+
+    let texts = split_entries(
+        fetch_stats
+            .iter()
+            .filter(|stats| !only_failing || stats.is_failing())
+            .map(ToString::to_string),
+    );
+
+    if texts.is_empty() {
+        bot.send(
+            SendMessage::new(chat.id(), "No fetch runs found")
+                .reply_parameters(ReplyParameters::new(message_id)),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    event!(
+        Level::TRACE,
+        messages = texts.len(),
+        "Sending source fetch stats"
+    );
+
+    for text in texts {
+        bot.send(
+            SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)),
+        )
+        .await?;
+    }
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn reload_sources(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    MediaParserSourcesReloaderWrapper(reloader): MediaParserSourcesReloaderWrapper,
+) -> HandlerResult {
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    event!(Level::DEBUG, "Reloading sources");
+
+    let text = match reloader.reload().await {
+        Ok(sources) => {
+            let mut text = "Sources reloaded:".to_owned();
+
+            for source in sources.all() {
+                text.push_str(&format!(
+                    "\n{name}: {count} genres",
+                    name = source.name(),
+                    count = source.genres().len(),
+                ));
+            }
+
+            text
+        }
+        Err(SourcesErrorKind::PathNotSet) => {
+            "Sources config isn't set. Set `MEDIA_PARSER_SOURCES_CONFIG` in env to reload sources"
+                .to_owned()
+        }
+        Err(err) => {
+            event!(Level::ERROR, %err, "Error reloading sources");
+
+            format!("Error reloading sources, previous sources are kept: {err}")
+        }
+    };
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Pauses or resumes the source or only its genre.
+/// The flag of the source and paused genres are saved in the database, so they're kept after restart of the worker.
+async fn set_source_paused<UoWFactory>(
+    uow_factory: &UoWFactory,
+    sources: &[Arc<dyn Source>],
+    controller: &Controller,
+    args: &[&str],
+    paused: bool,
+) -> Result<String, HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let (source, genre) = match find_source_and_genre(sources, args) {
+        Ok(source_and_genre) => source_and_genre,
+        Err(text) => return Ok(text),
+    };
+
+    let action = if paused { "paused" } else { "resumed" };
+
+    event!(
+        Level::DEBUG,
+        source = source.name(),
+        ?genre,
+        paused,
+        "Updating source"
+    );
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let mut text = if let Some(ref genre) = genre {
+        uow.source_repo()
+            .await
+            .map_err(HandlerError::new)?
+            .update_genre_paused(UpdateSourceGenrePaused::new(
+                source.name(),
+                genre.name(),
+                genre.media_type().as_str(),
+                genre.age_restriction().as_str(),
+                paused,
+            ))
+            .await
+            .map_err(HandlerError::new)?;
+
+        format!(
+            "Genre `{genre}` of source `{source_name}` {action}",
+            source_name = source.name(),
+        )
+    } else {
+        uow.source_repo()
+            .await
+            .map_err(HandlerError::new)?
+            .update_enabled(UpdateSourceEnabled::new(source.name(), !paused))
+            .await
+            .map_err(HandlerError::new)?;
+
+        format!(
+            "Source `{source_name}` {action}",
+            source_name = source.name()
+        )
+    };
+
+    uow.commit().await.map_err(HandlerError::new)?;
+
+    let source_name = source.name().to_owned();
+    let command = if paused {
+        ControlCommand::Pause { source_name, genre }
+    } else {
+        ControlCommand::Resume { source_name, genre }
+    };
+
+    if !controller.send(command) {
+        text.push('\n');
+        text.push_str(WORKER_NOT_RUNNING_NOTE);
+    }
+
+    Ok(text)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn pause_source<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    MediaParserSourceWrapper(sources): MediaParserSourceWrapper,
+    MediaParserControllerWrapper(controller): MediaParserControllerWrapper,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let command = CommandObject::extract(&text);
+    let args = command
+        .as_ref()
+        .map(|command| command.args.iter().map(|arg| &**arg).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let text = set_source_paused(&uow_factory, &sources, &controller, &args, true).await?;
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn resume_source<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    MediaParserSourceWrapper(sources): MediaParserSourceWrapper,
+    MediaParserControllerWrapper(controller): MediaParserControllerWrapper,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let command = CommandObject::extract(&text);
+    let args = command
+        .as_ref()
+        .map(|command| command.args.iter().map(|arg| &**arg).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let text = set_source_paused(&uow_factory, &sources, &controller, &args, false).await?;
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn refresh_source(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    MediaParserSourceWrapper(sources): MediaParserSourceWrapper,
+    MediaParserControllerWrapper(controller): MediaParserControllerWrapper,
+) -> HandlerResult {
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let command = CommandObject::extract(&text);
+    let args = command
+        .as_ref()
+        .map(|command| command.args.iter().map(|arg| &**arg).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let text = match find_source_and_genre(&sources, &args) {
+        Ok((source, genre)) => {
+            event!(
+                Level::DEBUG,
+                source = source.name(),
+                ?genre,
+                "Refreshing source"
+            );
+
+            let text = match genre {
+                Some(ref genre) => format!(
+                    "Genre `{genre}` of source `{source_name}` is fetched next",
+                    source_name = source.name(),
+                ),
+                None => format!(
+                    "Genres of source `{source_name}` are fetched next",
+                    source_name = source.name(),
+                ),
+            };
+
+            let command = ControlCommand::Refresh {
+                source_name: source.name().to_owned(),
+                genre,
+            };
+
+            if controller.send(command) {
+                text
+            } else {
+                // Refresh isn't saved, so it's lost, if the worker isn't running in the process
+                "The worker isn't running in the bot process, so the source can't be refreshed"
+                    .to_owned()
+            }
+        }
+        Err(text) => text,
+    };
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn source_polling_interval<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    MediaParserSourceWrapper(sources): MediaParserSourceWrapper,
+    MediaParserControllerWrapper(controller): MediaParserControllerWrapper,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let command = CommandObject::extract(&text);
+    let args = command
+        .as_ref()
+        .map(|command| command.args.iter().map(|arg| &**arg).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let polling_interval = args
+        .get(1)
+        .and_then(|raw_interval| raw_interval.parse().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok());
+
+    // The second argument is the interval, so only the source is found
+    let source = find_source_and_genre(&sources, &args[..args.len().min(1)]);
+
+    let text = match (source, polling_interval) {
+        (Err(text), _) => text,
+        (Ok(_), None) => "Pass time between fetches of genres of the source in seconds, \
+            for example, `/interval api.waifu.pics 2.5`"
+            .to_owned(),
+        (Ok((source, _)), Some(polling_interval)) => {
+            event!(
+                Level::DEBUG,
+                source = source.name(),
+                ?polling_interval,
+                "Updating polling interval"
+            );
+
+            let mut uow = uow_factory.new_unit_of_work();
+
+            uow.source_repo()
+                .await
+                .map_err(HandlerError::new)?
+                .update_polling_interval(UpdateSourcePollingInterval::new(
+                    source.name(),
+                    i64::try_from(polling_interval.as_millis()).unwrap_or(i64::MAX),
+                ))
+                .await
+                .map_err(HandlerError::new)?;
+
+            uow.commit().await.map_err(HandlerError::new)?;
+
+            let mut text = format!(
+                "Polling interval of source `{source_name}` changed to {polling_interval:?}",
+                source_name = source.name(),
+            );
+
+            if !controller.send(ControlCommand::SetPollingInterval {
+                source_name: source.name().to_owned(),
+                polling_interval,
+            }) {
+                text.push('\n');
+                text.push_str(WORKER_NOT_RUNNING_NOTE);
+            }
+
+            text
+        }
+    };
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_source_and_genre, split_entries, MAX_MESSAGE_LENGTH};
+
+    use crate::{
+        application::media_parser::traits::Source,
+        domain::media_parser::entities::Genre,
+        infrastructure::media_parser::{NekosBest, WaifuPics},
+    };
+
+    use std::sync::Arc;
+
+    #[test]
+    fn test_split_entries() {
+        assert!(split_entries(Vec::<String>::new()).is_empty());
+        assert_eq!(
+            split_entries(["a".to_owned(), "b".to_owned()]),
+            ["a\n\nb".to_owned()]
+        );
+
+        let entry = "a".repeat(MAX_MESSAGE_LENGTH / 2);
+        let texts = split_entries([entry.clone(), entry.clone(), entry.clone()]);
+
+        assert_eq!(texts.len(), 3);
+        assert!(texts
+            .iter()
+            .all(|text| text.chars().count() <= MAX_MESSAGE_LENGTH));
+    }
+
+    #[test]
+    fn test_find_source_and_genre() {
+        let sources: Vec<Arc<dyn Source>> = vec![
+            Arc::new(NekosBest::default()),
+            Arc::new(WaifuPics::default()),
+        ];
+
+        let (source, genre) = find_source_and_genre(&sources, &["api.waifu.pics"]).unwrap();
+
+        assert_eq!(source.name(), "api.waifu.pics");
+        assert!(genre.is_none());
+
+        let (source, genre) =
+            find_source_and_genre(&sources, &["api.waifu.pics", "neko_img_sfw"]).unwrap();
+
+        assert_eq!(source.name(), "api.waifu.pics");
+        assert_eq!(genre, Some(Genre::new_sfw_image("neko")));
+
+        assert!(find_source_and_genre(&sources, &[]).is_err());
+        assert!(find_source_and_genre(&sources, &["unknown"]).is_err());
+        assert!(find_source_and_genre(&sources, &["api.waifu.pics", "unknown_img_sfw"]).is_err());
+        assert!(find_source_and_genre(&sources, &["api.waifu.pics", "neko"]).is_err());
+    }
+}
@@ -0,0 +1,560 @@
+use crate::{
+    application::{
+        common::{
+            exceptions::RepoKind,
+            traits::{UnitOfWork, UnitOfWorkFactory},
+        },
+        media::dto::GetMediaById,
+        user_media_view::dto::{
+            DeleteUserMediaView, GetUserMediaViewById, GetUserMediaViewHistoryByUserId,
+        },
+    },
+    domain::{
+        media_parser::value_objects::{AgeRestriction, MediaType},
+        user::entities::User as UserEntity,
+        user_media_view::entities::UserMediaViewWithMedia,
+    },
+    extractors::UoWFactoryWrapper,
+    handlers::media::send_media,
+};
+
+use anyhow::anyhow;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use telers::{
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    filters::CommandObject,
+    methods::{AnswerCallbackQuery, EditMessageText, SendMessage},
+    types::{
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage,
+        MessageText, ReplyParameters, User,
+    },
+    Bot,
+};
+use time::OffsetDateTime;
+use tracing::{event, field, instrument, Level, Span};
+use uuid::Uuid;
+
+const PAGE_SIZE: u64 = 5;
+/// Placeholder for an empty part of callback data
+const EMPTY_PART: &str = "-";
+/// Telegram rejects callback data longer than this count of bytes
+const CALLBACK_DATA_MAX_LEN: usize = 64;
+pub const HISTORY_PAGE_PREFIX: &str = "h:";
+/// Length of a view id in base64 without padding
+const ENCODED_ID_LEN: usize = 22;
+/// Length of the longest media type, which is accepted by the command
+const MEDIA_TYPE_MAX_LEN: usize = "unknown".len();
+/// Max length of a genre in bytes, so the page callback data fits into the limit with the longest id and media type
+const GENRE_MAX_LEN: usize =
+    CALLBACK_DATA_MAX_LEN - HISTORY_PAGE_PREFIX.len() - ENCODED_ID_LEN - MEDIA_TYPE_MAX_LEN - 2;
+
+/// History filters and cursor, which are passed between pages in callback data.
+/// Format: `h:{after_id in base64 or -} {genre or -} {media_type or -}`
+struct HistoryPage<'a> {
+    after_id: Option<Uuid>,
+    genre: Option<&'a str>,
+    media_type: Option<&'a str>,
+}
+
+impl<'a> HistoryPage<'a> {
+    fn parse(callback_data: &'a str) -> Option<Self> {
+        let mut parts = callback_data.strip_prefix(HISTORY_PAGE_PREFIX)?.split(' ');
+
+        let after_id = match parts.next()? {
+            EMPTY_PART => None,
+            after_id => Some(Uuid::from_slice(&URL_SAFE_NO_PAD.decode(after_id).ok()?).ok()?),
+        };
+        let genre = parts.next().filter(|genre| *genre != EMPTY_PART);
+        let media_type = parts.next().filter(|media_type| *media_type != EMPTY_PART);
+
+        Some(Self {
+            after_id,
+            genre,
+            media_type,
+        })
+    }
+
+    fn callback_data(&self) -> String {
+        format!(
+            "{HISTORY_PAGE_PREFIX}{after_id} {genre} {media_type}",
+            after_id = self.after_id.map_or_else(
+                || EMPTY_PART.to_owned(),
+                |id| URL_SAFE_NO_PAD.encode(id.as_bytes()),
+            ),
+            genre = self.genre.unwrap_or(EMPTY_PART),
+            media_type = self.media_type.unwrap_or(EMPTY_PART),
+        )
+    }
+}
+
+fn format_date(date: OffsetDateTime) -> String {
+    format!(
+        "{year}-{month:02}-{day:02} {hour:02}:{minute:02}",
+        year = date.year(),
+        month = u8::from(date.month()),
+        day = date.day(),
+        hour = date.hour(),
+        minute = date.minute(),
+    )
+}
+
+/// Get the history page and build its text with keyboard
+async fn history_page<UoWFactory>(
+    uow_factory: &UoWFactory,
+    db_user_id: &Uuid,
+    page: &HistoryPage<'_>,
+) -> Result<(String, InlineKeyboardMarkup), HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    // We get one more view to know if there is a next page
+    let mut views = uow
+        .user_media_view_reader()
+        .await
+        .map_err(HandlerError::new)?
+        .get_history_by_user_id(GetUserMediaViewHistoryByUserId::new(
+            db_user_id,
+            page.genre,
+            page.media_type,
+            page.after_id.as_ref(),
+            Some(PAGE_SIZE + 1),
+        ))
+        .await
+        .map_err(HandlerError::new)?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let has_next_page = views.len() > PAGE_SIZE as usize;
+    #[allow(clippy::cast_possible_truncation)]
+    views.truncate(PAGE_SIZE as usize);
+
+    if views.is_empty() {
+        let text = if page.after_id.is_some() {
+            "No more views in your history"
+        } else if page.genre.is_some() || page.media_type.is_some() {
+            "No views found in your history with these filters"
+        } else {
+            "Your history is empty"
+        };
+
+        return Ok((
+            text.to_owned(),
+            InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new()),
+        ));
+    }
+
+    let mut text = String::from("Your history (UTC):\n\n");
+    let mut keyboard = Vec::with_capacity(views.len() + 1);
+
+    for (index, UserMediaViewWithMedia { view, media }) in views.iter().enumerate() {
+        let number = index + 1;
+
+        text.push_str(&format!(
+            "{number}. /{genre}_{media_type}_{age_restriction} {date}\n",
+            genre = media.genre.as_deref().unwrap_or("unknown"),
+            media_type = media.media_type,
+            age_restriction = media.age_restriction,
+            date = format_date(view.created),
+        ));
+
+        keyboard.push(vec![
+            InlineKeyboardButton::new(format!("{number}. Send again"))
+                .callback_data(format!("history send {}", view.id.simple())),
+            InlineKeyboardButton::new(format!("{number}. Forget"))
+                .callback_data(format!("history forget {}", view.id.simple())),
+        ]);
+    }
+
+    text.push_str("\nForgotten media can be shown to you again");
+
+    let mut navigation = Vec::with_capacity(2);
+
+    if page.after_id.is_some() {
+        navigation.push(
+            InlineKeyboardButton::new("⏮ First page").callback_data(
+                HistoryPage {
+                    after_id: None,
+                    ..*page
+                }
+                .callback_data(),
+            ),
+        );
+    }
+    if has_next_page {
+        // `unwrap` is safe here, because we checked that `views` isn't empty
+        let last_view = views.last().unwrap();
+
+        navigation.push(
+            InlineKeyboardButton::new("Next ▶").callback_data(
+                HistoryPage {
+                    after_id: Some(last_view.view.id),
+                    ..*page
+                }
+                .callback_data(),
+            ),
+        );
+    }
+    if !navigation.is_empty() {
+        keyboard.push(navigation);
+    }
+
+    Ok((text, InlineKeyboardMarkup::new(keyboard)))
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn history<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    UserEntity { id: db_user_id, .. }: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let args = CommandObject::extract(&text).map(|command| command.args);
+    let args = args.as_deref().unwrap_or_default();
+
+    let genre = args.first().map(AsRef::<str>::as_ref);
+    let media_type = args.get(1).map(AsRef::<str>::as_ref);
+
+    if let Some(media_type) = media_type {
+        if let Err(err) = MediaType::try_from(media_type) {
+            event!(Level::DEBUG, %err, "Failed to parse media type");
+
+            bot.send(
+                SendMessage::new(
+                    chat.id(),
+                    format!("{err}\n\nUsage: /history [genre] [gif|img|video]"),
+                )
+                .reply_parameters(ReplyParameters::new(message_id)),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    }
+
+    // Genre is passed in callback data, so we limit its length to fit into the callback data limit
+    if genre.map_or(false, |genre| {
+        genre.len() > GENRE_MAX_LEN || genre == EMPTY_PART
+    }) {
+        bot.send(
+            SendMessage::new(chat.id(), "Unknown genre")
+                .reply_parameters(ReplyParameters::new(message_id)),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    event!(Level::DEBUG, genre, media_type, "Getting history");
+
+    let (text, reply_markup) = history_page(
+        &uow_factory,
+        &db_user_id,
+        &HistoryPage {
+            after_id: None,
+            genre,
+            media_type,
+        },
+    )
+    .await?;
+
+    bot.send(
+        SendMessage::new(chat.id(), text)
+            .reply_parameters(ReplyParameters::new(message_id))
+            .reply_markup(reply_markup),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id))]
+pub async fn history_page_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        message: maybe_inaccessible_message,
+        ..
+    }: CallbackQuery,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    UserEntity { id: db_user_id, .. }: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let Some(page) = HistoryPage::parse(callback_data) else {
+        return Err(HandlerError::new(anyhow!(
+            "Unknown callback data. Callback data: {callback_data}",
+        )));
+    };
+
+    let (chat_id, message_id) =
+        if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
+            (message.chat().id(), message.id())
+        } else {
+            event!(
+                Level::WARN,
+                "Callback query doesn't have chat id. Message is too old",
+            );
+
+            bot.send(
+                AnswerCallbackQuery::new(callback_query_id)
+                    .text("Message is too old. Please, send the command again"),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        };
+
+    event!(Level::DEBUG, after_id = ?page.after_id, "Getting history page");
+
+    let (text, reply_markup) = history_page(&uow_factory, &db_user_id, &page).await?;
+
+    bot.send(
+        EditMessageText::new(text)
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .reply_markup(reply_markup),
+    )
+    .await?;
+
+    bot.send(AnswerCallbackQuery::new(callback_query_id))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id, view_id))]
+pub async fn history_send_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        message: maybe_inaccessible_message,
+        ..
+    }: CallbackQuery,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    user: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let Some(Ok(view_id)) = callback_data
+        .strip_prefix("history send ")
+        .map(Uuid::parse_str)
+    else {
+        return Err(HandlerError::new(anyhow!(
+            "Unknown callback data. Callback data: {callback_data}",
+        )));
+    };
+
+    Span::current().record("view_id", field::display(view_id));
+
+    let chat_id =
+        if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
+            message.chat().id()
+        } else {
+            event!(
+                Level::WARN,
+                "Callback query doesn't have chat id. Message is too old",
+            );
+
+            bot.send(
+                AnswerCallbackQuery::new(callback_query_id)
+                    .text("Message is too old. Please, send the command again"),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        };
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let view = match uow
+        .user_media_view_reader()
+        .await
+        .map_err(HandlerError::new)?
+        .get_by_id(GetUserMediaViewById::new(&view_id))
+        .await
+    {
+        Ok(view) if view.user_id == user.id => view,
+        Ok(_) | Err(RepoKind::Exception(_)) => {
+            event!(Level::DEBUG, "User media view not found");
+
+            bot.send(
+                AnswerCallbackQuery::new(callback_query_id)
+                    .text("This media isn't in your history anymore"),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+        Err(RepoKind::Unexpected(err)) => {
+            event!(Level::ERROR, %err, "Failed to get user media view");
+
+            return Err(HandlerError::new(err));
+        }
+    };
+
+    let media = uow
+        .media_reader()
+        .await
+        .map_err(HandlerError::new)?
+        .get_by_id(GetMediaById::new(&view.media_id))
+        .await
+        .map_err(HandlerError::new)?;
+
+    drop(uow);
+
+    let age_restriction =
+        AgeRestriction::try_from(media.age_restriction.as_str()).unwrap_or(AgeRestriction::Unknown);
+
+    if !age_restriction.is_allowed_by(user.max_age_restriction()) {
+        event!(Level::DEBUG, %age_restriction, "Age restriction isn't allowed");
+
+        bot.send(
+            AnswerCallbackQuery::new(callback_query_id)
+                .text("This content is disabled. You can change age restriction in the settings"),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    event!(Level::DEBUG, ?media, "Sending media again");
+
+    send_media(&bot, chat_id, &media, None, None).await?;
+
+    bot.send(AnswerCallbackQuery::new(callback_query_id))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id, view_id))]
+pub async fn history_forget_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        ..
+    }: CallbackQuery,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    UserEntity { id: db_user_id, .. }: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let Some(Ok(view_id)) = callback_data
+        .strip_prefix("history forget ")
+        .map(Uuid::parse_str)
+    else {
+        return Err(HandlerError::new(anyhow!(
+            "Unknown callback data. Callback data: {callback_data}",
+        )));
+    };
+
+    Span::current().record("view_id", field::display(view_id));
+
+    event!(Level::DEBUG, "Forgetting user media view");
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let res = uow
+        .user_media_view_repo()
+        .await
+        .map_err(HandlerError::new)?
+        .delete(DeleteUserMediaView::new(&view_id, &db_user_id))
+        .await;
+
+    let text = match res {
+        Ok(()) => {
+            uow.commit().await.map_err(HandlerError::new)?;
+
+            event!(Level::DEBUG, "User media view deleted");
+
+            "Forgotten! This media can be shown to you again"
+        }
+        Err(RepoKind::Exception(_)) => {
+            uow.rollback().await.map_err(HandlerError::new)?;
+
+            event!(Level::DEBUG, "User media view already deleted");
+
+            "This media is already forgotten"
+        }
+        Err(RepoKind::Unexpected(err)) => {
+            uow.rollback().await.map_err(HandlerError::new)?;
+
+            event!(Level::ERROR, %err, "Failed to delete user media view");
+
+            return Err(HandlerError::new(err));
+        }
+    };
+
+    bot.send(AnswerCallbackQuery::new(callback_query_id).text(text))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryPage, CALLBACK_DATA_MAX_LEN, GENRE_MAX_LEN, MEDIA_TYPE_MAX_LEN};
+
+    use uuid::Uuid;
+
+    #[test]
+    fn test_history_page_callback_data() {
+        let genre = "g".repeat(GENRE_MAX_LEN);
+        let media_type = "u".repeat(MEDIA_TYPE_MAX_LEN);
+        let page = HistoryPage {
+            after_id: Some(Uuid::from_u128(u128::MAX)),
+            genre: Some(&genre),
+            media_type: Some(&media_type),
+        };
+
+        let callback_data = page.callback_data();
+        assert!(callback_data.len() <= CALLBACK_DATA_MAX_LEN);
+
+        let parsed = HistoryPage::parse(&callback_data).unwrap();
+        assert_eq!(parsed.after_id, page.after_id);
+        assert_eq!(parsed.genre, page.genre);
+        assert_eq!(parsed.media_type, page.media_type);
+
+        let callback_data = HistoryPage {
+            after_id: None,
+            genre: None,
+            media_type: None,
+        }
+        .callback_data();
+        assert_eq!(callback_data, "h:- - -");
+
+        let parsed = HistoryPage::parse(&callback_data).unwrap();
+        assert!(parsed.after_id.is_none() && parsed.genre.is_none() && parsed.media_type.is_none());
+    }
+}
@@ -4,81 +4,148 @@ use crate::{
             exceptions::RepoKind,
             traits::{UnitOfWork, UnitOfWorkFactory},
         },
-        media::dto::GetMediaByInfoUnviewedByUser,
+        media::dto::{GetMediaByInfoUnviewedByUser, MarkMediaChecked},
         media_parser::traits::Source,
         user_media_view::dto::CreateUserMediaView,
     },
-    domain::media_parser::entities::Genre,
-    domain::user::entities::User as UserEntity,
-    extractors::{MediaParserSourceWrapper, UoWFactoryWrapper},
+    domain::{
+        media::{
+            entities::{GenresStats, Media as MediaEntity},
+            value_objects::{MediaStatus, SendMethod},
+        },
+        media_parser::{
+            entities::Genre,
+            value_objects::{AgeRestriction, MediaType},
+        },
+        user::entities::User as UserEntity,
+    },
+    extractors::{MediaParserSourceWrapper, ShutdownWrapper, UoWFactoryWrapper},
+    infrastructure::shutdown::Shutdown,
 };
 
+use anyhow::anyhow;
+use metrics::counter;
+use std::sync::Arc;
 use telers::{
-    errors::HandlerError,
+    errors::{HandlerError, SessionErrorKind, TelegramErrorKind},
     event::{telegram::HandlerResult, EventReturn},
     filters::CommandObject,
-    methods::{SendDocument, SendMessage},
+    methods::{
+        AnswerCallbackQuery, EditMessageText, SendAnimation, SendDocument, SendMessage, SendPhoto,
+        SendVideo,
+    },
     types::{
-        Chat, InputFile, KeyboardButton, Message, MessageText, ReplyKeyboardMarkup,
-        ReplyKeyboardRemove, ReplyParameters,
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+        MaybeInaccessibleMessage, Message, MessageText, ReplyKeyboardRemove, ReplyParameters, User,
     },
     Bot,
 };
 use tracing::{event, field, instrument, Level, Span};
 use uuid::Uuid;
 
-#[instrument(skip_all, fields(message_id, user_id))]
-pub async fn gifs(
-    bot: Bot,
-    message: Message,
-    MediaParserSourceWrapper(media_parser_sources): MediaParserSourceWrapper,
-) -> HandlerResult {
-    Span::current()
-        .record("message_id", message.id())
-        .record("user_id", message.from_id());
-
-    event!(Level::DEBUG, "Getting genres");
-
-    let genres = media_parser_sources
+/// Max count of media, which can be sent by one request
+const MAX_MEDIA_COUNT: u64 = 30;
+/// Count of media, which is sent by the "Next ×5" button
+const NEXT_MANY_MEDIA_COUNT: u64 = 5;
+/// Max count of media, which are rejected by Telegram, after which other media aren't tried
+const MAX_BROKEN_MEDIA_COUNT: u64 = 5;
+/// Count of genres on one page of the genre browser
+const GENRES_PER_PAGE: usize = 10;
+/// Count of genre buttons in one row of the genre browser
+const GENRES_PER_ROW: usize = 2;
+
+/// Returns genres of all sources with the media type and age restriction, sorted by name and without duplicates
+fn unique_genres(
+    media_parser_sources: &[Arc<dyn Source>],
+    media_type: MediaType,
+    age_restriction: AgeRestriction,
+) -> Vec<Genre> {
+    let mut genres = media_parser_sources
         .iter()
-        .map(Source::genres)
-        .collect::<Box<[_]>>();
+        .flat_map(|source| {
+            source
+                .genres()
+                .filter(Some(media_type), Some(age_restriction))
+        })
+        .collect::<Vec<_>>();
+    genres.sort_by(|a, b| a.name().cmp(b.name()));
+    genres.dedup();
+    genres
+}
 
-    let mut sfw_genres = genres
+/// Creates text with commands of all genres with the media type
+/// # Arguments
+/// * `media_type_name` - The name of the media type to show in the text, for example, "GIFs"
+fn genres_text(
+    media_parser_sources: &[Arc<dyn Source>],
+    media_type: MediaType,
+    media_type_name: &str,
+) -> String {
+    let sfw_genres = unique_genres(media_parser_sources, media_type, AgeRestriction::Sfw)
         .iter()
-        .flat_map(|genres| genres.sfw_gifs())
         .map(|genre| format!("/{genre}"))
         .collect::<Vec<_>>();
-    sfw_genres.sort();
-    sfw_genres.dedup();
-
-    let mut nsfw_genres = genres
+    let questionable_genres = unique_genres(
+        media_parser_sources,
+        media_type,
+        AgeRestriction::Questionable,
+    )
+    .iter()
+    .map(|genre| format!("/{genre}"))
+    .collect::<Vec<_>>();
+    let nsfw_genres = unique_genres(media_parser_sources, media_type, AgeRestriction::Nsfw)
         .iter()
-        .flat_map(|genres| genres.nsfw_gifs())
         .map(|genre| format!("/{genre}"))
         .collect::<Vec<_>>();
-    nsfw_genres.sort();
-    nsfw_genres.dedup();
 
-    let text = format!(
-        "{sfw_genres}\n\nNot safe for work:\n{nsfw_genres}",
+    event!(
+        Level::TRACE,
+        ?sfw_genres,
+        ?questionable_genres,
+        ?nsfw_genres,
+        "Genres collected"
+    );
+
+    format!(
+        "{sfw_genres}{questionable_genres}\n\nNot safe for work:\n{nsfw_genres}\n\nYou can also browse genres with buttons: /genres",
         sfw_genres = if sfw_genres.is_empty() {
-            "No SFW GIFs available".to_string()
+            format!("No SFW {media_type_name} available")
+        } else {
+            sfw_genres.join(" ")
+        },
+        // Questionable genres are provided only by some sources, so the section is hidden if there are no such genres
+        questionable_genres = if questionable_genres.is_empty() {
+            String::new()
         } else {
-            sfw_genres.join(" ").to_string()
+            format!("\n\nQuestionable:\n{}", questionable_genres.join(" "))
         },
         nsfw_genres = if nsfw_genres.is_empty() {
-            "No NSFW GIFs available".to_string()
+            format!("No NSFW {media_type_name} available")
         } else {
-            let mut text = nsfw_genres.join(" ").to_string();
+            let mut text = nsfw_genres.join(" ");
             text.push_str(
                 "\n\n* We don't guarantee that SFW media is really SFW, so don't check it on the bus and if you're younger than 18 y.o. ^_^",
             );
             text
         }
-    );
+    )
+}
 
-    event!(Level::TRACE, ?sfw_genres, ?nsfw_genres, "Sending genres");
+#[instrument(skip_all, fields(message_id, user_id))]
+pub async fn gifs(
+    bot: Bot,
+    message: Message,
+    MediaParserSourceWrapper(media_parser_sources): MediaParserSourceWrapper,
+) -> HandlerResult {
+    Span::current()
+        .record("message_id", message.id())
+        .record("user_id", message.from_id());
+
+    event!(Level::DEBUG, "Getting genres");
+
+    let text = genres_text(&media_parser_sources, MediaType::Gif, "GIFs");
+
+    event!(Level::TRACE, "Sending genres");
 
     bot.send(
         SendMessage::new(message.chat().id(), text)
@@ -102,46 +169,9 @@ pub async fn images(
 
     event!(Level::DEBUG, "Getting genres");
 
-    let genres = media_parser_sources
-        .iter()
-        .map(Source::genres)
-        .collect::<Box<[_]>>();
+    let text = genres_text(&media_parser_sources, MediaType::Image, "images");
 
-    let mut sfw_genres = genres
-        .iter()
-        .flat_map(|genres| genres.sfw_images())
-        .map(|genre| format!("/{genre}"))
-        .collect::<Vec<_>>();
-    sfw_genres.sort();
-    sfw_genres.dedup();
-
-    let mut nsfw_genres = genres
-        .iter()
-        .flat_map(|genres| genres.nsfw_images())
-        .map(|genre| format!("/{genre}"))
-        .collect::<Vec<_>>();
-    nsfw_genres.sort();
-    nsfw_genres.dedup();
-
-    let text = format!(
-        "{sfw_genres}\n\nNot safe for work:\n{nsfw_genres}",
-        sfw_genres = if sfw_genres.is_empty() {
-            "No SFW images available".to_string()
-        } else {
-            sfw_genres.join(" ").to_string()
-        },
-        nsfw_genres = if nsfw_genres.is_empty() {
-            "No NSFW images available".to_string()
-        } else {
-            let mut text = nsfw_genres.join(" ").to_string();
-            text.push_str(
-                "\n\n* We don't guarantee that SFW media is really SFW, so don't check it on the bus and if you're younger than 18 y.o. ^_^",
-            );
-            text
-        }
-    );
-
-    event!(Level::TRACE, ?sfw_genres, ?nsfw_genres, "Sending genres");
+    event!(Level::TRACE, "Sending genres");
 
     bot.send(
         SendMessage::new(message.chat().id(), text)
@@ -153,232 +183,524 @@ pub async fn images(
     Ok(EventReturn::Finish)
 }
 
-#[instrument(skip_all, fields(%message_id, user_id, genre))]
-pub async fn genre<UoWFactory>(
+#[instrument(skip_all, fields(message_id, user_id))]
+pub async fn videos(
     bot: Bot,
-    MessageText {
-        id: message_id,
-        text,
-        from,
-        chat,
-        ..
-    }: MessageText,
-    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
-    UserEntity {
-        id: db_user_id,
-        show_nsfw,
-        ..
-    }: UserEntity,
-) -> HandlerResult
-where
-    UoWFactory: UnitOfWorkFactory,
-{
-    Span::current().record("user_id", from.map(|user| user.id));
+    message: Message,
+    MediaParserSourceWrapper(media_parser_sources): MediaParserSourceWrapper,
+) -> HandlerResult {
+    Span::current()
+        .record("message_id", message.id())
+        .record("user_id", message.from_id());
 
-    let Some(CommandObject {
-        args,
-        command: genre,
-        ..
-    }) = CommandObject::extract(&text)
-    else {
-        event!(Level::DEBUG, text, "Not a command");
+    event!(Level::DEBUG, "Getting genres");
 
-        return Ok(EventReturn::Finish);
-    };
+    let text = genres_text(&media_parser_sources, MediaType::Video, "videos");
 
-    event!(Level::DEBUG, "Parsing genre");
+    event!(Level::TRACE, "Sending genres");
 
-    let genre: Genre = match genre.as_ref().try_into() {
-        Ok(genre) => genre,
-        Err(err) => {
-            event!(Level::DEBUG, %err, genre, "Failed to parse genre");
+    bot.send(
+        SendMessage::new(message.chat().id(), text)
+            .reply_parameters(ReplyParameters::new(message.id()))
+            .reply_markup(ReplyKeyboardRemove::new(true)),
+    )
+    .await?;
 
-            bot.send(
-                SendMessage::new(chat.id(), err.to_string())
-                    .reply_parameters(ReplyParameters::new(message_id)),
-            )
-            .await?;
+    Ok(EventReturn::Finish)
+}
 
-            return Ok(EventReturn::Finish);
+/// Steps of the genre browser, which are passed between steps in callback data.
+/// Format: `genres`, `genres {media_type}` and `genres {media_type} {age_restriction} {page}`
+#[derive(Debug)]
+enum GenresStep {
+    MediaType,
+    AgeRestriction {
+        media_type: MediaType,
+    },
+    Genres {
+        media_type: MediaType,
+        age_restriction: AgeRestriction,
+        page: usize,
+    },
+}
+
+impl GenresStep {
+    fn parse(callback_data: &str) -> Option<Self> {
+        let mut parts = callback_data.split(' ');
+
+        if parts.next()? != "genres" {
+            return None;
         }
-    };
 
-    let show_nsfw = show_nsfw.map_or(false, |show_nsfw| show_nsfw);
+        let Some(media_type) = parts.next() else {
+            return Some(Self::MediaType);
+        };
+        let media_type = MediaType::try_from(media_type).ok()?;
 
-    if !show_nsfw && genre.is_nsfw() {
-        event!(Level::DEBUG, "NSFW content is disabled");
+        let Some(age_restriction) = parts.next() else {
+            return Some(Self::AgeRestriction { media_type });
+        };
+        let age_restriction = AgeRestriction::try_from(age_restriction).ok()?;
+        let page = parts.next()?.parse().ok()?;
+
+        Some(Self::Genres {
+            media_type,
+            age_restriction,
+            page,
+        })
+    }
+}
 
-        bot.send(
-            SendMessage::new(
-                chat.id(),
-                "NSFW content is disabled. You can enable it in the settings.\n\n/settings",
-            )
-            .reply_parameters(ReplyParameters::new(message_id)),
-        )
-        .await?;
+fn media_type_step() -> (String, InlineKeyboardMarkup) {
+    (
+        "Choose media type".to_owned(),
+        InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::new("GIFs").callback_data(format!("genres {}", MediaType::Gif)),
+            InlineKeyboardButton::new("Images")
+                .callback_data(format!("genres {}", MediaType::Image)),
+            InlineKeyboardButton::new("Videos")
+                .callback_data(format!("genres {}", MediaType::Video)),
+        ]]),
+    )
+}
 
-        return Ok(EventReturn::Finish);
+/// Creates buttons of age restrictions, which are allowed by the maximum age restriction of the user
+fn age_restriction_step(
+    media_type: MediaType,
+    max_age_restriction: AgeRestriction,
+) -> (String, InlineKeyboardMarkup) {
+    let buttons = [
+        (AgeRestriction::Sfw, "SFW"),
+        (AgeRestriction::Questionable, "Questionable (18+)"),
+        (AgeRestriction::Nsfw, "NSFW (18+)"),
+    ]
+    .into_iter()
+    .filter(|(age_restriction, _)| age_restriction.is_allowed_by(max_age_restriction))
+    .map(|(age_restriction, text)| {
+        InlineKeyboardButton::new(text)
+            .callback_data(format!("genres {media_type} {age_restriction} 0"))
+    })
+    .collect();
+
+    (
+        "Choose age restriction".to_owned(),
+        InlineKeyboardMarkup::new([
+            buttons,
+            vec![InlineKeyboardButton::new("« Back").callback_data("genres")],
+        ]),
+    )
+}
+
+/// Creates the page of genres with buttons to get media of the genre.
+/// Each genre button shows count of media of the genre.
+fn genres_step(
+    genres: &[Genre],
+    genres_stats: &GenresStats,
+    media_type: MediaType,
+    age_restriction: AgeRestriction,
+    page: usize,
+    back_callback_data: String,
+) -> (String, InlineKeyboardMarkup) {
+    let pages = genres.len().div_ceil(GENRES_PER_PAGE).max(1);
+    let page = page.min(pages - 1);
+
+    let mut keyboard = genres
+        .iter()
+        .skip(page * GENRES_PER_PAGE)
+        .take(GENRES_PER_PAGE)
+        .map(|genre| {
+            let total = genres_stats
+                .0
+                .iter()
+                .find(|stats| {
+                    stats.genre == genre.name()
+                        && stats.media_type == media_type.as_str()
+                        && stats.age_restriction == age_restriction.as_str()
+                })
+                .map_or(0, |stats| stats.total);
+
+            // Genre buttons use the same callback data as "Next" buttons, so media is delivered by the same handler
+            InlineKeyboardButton::new(format!("{name} ({total})", name = genre.name()))
+                .callback_data(format!("media next 1 {genre}"))
+        })
+        .collect::<Vec<_>>()
+        .chunks(GENRES_PER_ROW)
+        .map(<[_]>::to_vec)
+        .collect::<Vec<_>>();
+
+    let mut navigation = Vec::with_capacity(3);
+    if page > 0 {
+        navigation.push(InlineKeyboardButton::new("◀").callback_data(format!(
+            "genres {media_type} {age_restriction} {page}",
+            page = page - 1,
+        )));
+    }
+    navigation.push(InlineKeyboardButton::new("« Back").callback_data(back_callback_data));
+    if page + 1 < pages {
+        navigation.push(InlineKeyboardButton::new("▶").callback_data(format!(
+            "genres {media_type} {age_restriction} {page}",
+            page = page + 1,
+        )));
     }
+    keyboard.push(navigation);
 
-    #[allow(clippy::cast_sign_loss)]
-    let count_media = if let Some(Ok(count)) = args.first().map(|arg| arg.parse::<i8>()) {
-        if count > 30 {
-            30
-        } else if count <= 0 {
-            1
-        } else {
-            count
-        }
+    let text = if genres.is_empty() {
+        "No genres available".to_owned()
     } else {
-        1
-    } as u64;
+        format!("Choose genre (page {page} of {pages})", page = page + 1,)
+    };
 
-    event!(Level::DEBUG, count = count_media, "Getting media");
+    (text, InlineKeyboardMarkup::new(keyboard))
+}
 
+async fn get_genres_stats<UoWFactory>(uow_factory: &UoWFactory) -> Result<GenresStats, HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
     let mut uow = uow_factory.new_unit_of_work();
 
-    let media_group = uow
+    let genres_stats = uow
         .media_reader()
         .await
         .map_err(HandlerError::new)?
-        .get_by_info_unviewed_by_user(GetMediaByInfoUnviewedByUser::new(
-            &db_user_id,
-            Some(genre.name()),
-            genre.media_type().as_str(),
-            Some(genre.is_sfw()),
-            None,
-            Some(count_media),
-        ))
+        .get_genre_stats()
         .await
         .map_err(HandlerError::new)?;
 
-    let media_group_len = media_group.len();
+    Ok(genres_stats)
+}
 
-    if media_group_len == 0 {
-        event!(Level::DEBUG, "No media found for genre");
+#[instrument(skip_all, fields(message_id, user_id))]
+pub async fn genres(bot: Bot, message: Message) -> HandlerResult {
+    Span::current()
+        .record("message_id", message.id())
+        .record("user_id", message.from_id());
 
-        bot.send(
-            SendMessage::new(chat.id(), "No media found for genre")
-                .reply_parameters(ReplyParameters::new(message_id)),
-        )
-        .await?;
-    } else if media_group_len == 1 {
-        // `unwrap` is safe here, because we checked that `media_group_len` is equal to 1
-        let media = media_group
-            .first()
-            .expect("Media group is empty, but it shouldn't be");
-
-        Span::current().record("media_id", field::display(media.id));
-
-        event!(Level::DEBUG, ?media, "Sending media");
-
-        let reply_markup = match chat {
-            Chat::Private(_) => Some(
-                ReplyKeyboardMarkup::new([[KeyboardButton::new(format!("/{genre}",))]])
-                    .resize_keyboard(true)
-                    .one_time_keyboard(false),
-            ),
-            _ => None,
+    event!(Level::DEBUG, "Sending genre browser");
+
+    let (text, reply_markup) = media_type_step();
+
+    bot.send(
+        SendMessage::new(message.chat().id(), text)
+            .reply_parameters(ReplyParameters::new(message.id()))
+            .reply_markup(reply_markup),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id))]
+pub async fn genres_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        message: maybe_inaccessible_message,
+        ..
+    }: CallbackQuery,
+    MediaParserSourceWrapper(media_parser_sources): MediaParserSourceWrapper,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    user: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let Some(step) = GenresStep::parse(callback_data) else {
+        return Err(HandlerError::new(anyhow!(
+            "Unknown callback data. Callback data: {callback_data}",
+        )));
+    };
+
+    let (chat_id, message_id) =
+        if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
+            (message.chat().id(), message.id())
+        } else {
+            event!(
+                Level::WARN,
+                "Callback query doesn't have chat id. Message is too old",
+            );
+
+            bot.send(
+                AnswerCallbackQuery::new(callback_query_id)
+                    .text("Message is too old. Please, send the command again"),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
         };
 
-        match reply_markup {
-            Some(ref markup) => {
-                bot.send(
-                    SendDocument::new(chat.id(), InputFile::url(&media.url))
-                        .reply_parameters(ReplyParameters::new(message_id))
-                        .reply_markup(markup.clone()),
+    event!(Level::DEBUG, ?step, "Getting genre browser step");
+
+    let max_age_restriction = user.max_age_restriction();
+
+    let (text, reply_markup) =
+        match step {
+            GenresStep::MediaType => media_type_step(),
+            // Age restriction step is skipped if the user allowed only SFW content, because only SFW genres are available
+            GenresStep::AgeRestriction { media_type } if !max_age_restriction.is_sfw() => {
+                age_restriction_step(media_type, max_age_restriction)
+            }
+            GenresStep::AgeRestriction { media_type } => {
+                let genres = unique_genres(&media_parser_sources, media_type, AgeRestriction::Sfw);
+                let genres_stats = get_genres_stats(&uow_factory).await?;
+
+                genres_step(
+                    &genres,
+                    &genres_stats,
+                    media_type,
+                    AgeRestriction::Sfw,
+                    0,
+                    "genres".to_owned(),
                 )
+            }
+            GenresStep::Genres {
+                age_restriction, ..
+            } if !age_restriction.is_allowed_by(max_age_restriction) => {
+                event!(Level::DEBUG, %age_restriction, "Age restriction isn't allowed");
+
+                bot.send(AnswerCallbackQuery::new(callback_query_id).text(
+                    "This content is disabled. You can change age restriction in the settings",
+                ))
                 .await?;
+
+                return Ok(EventReturn::Finish);
             }
-            None => {
-                bot.send(
-                    SendDocument::new(chat.id(), InputFile::url(&media.url))
-                        .reply_parameters(ReplyParameters::new(message_id))
-                        .reply_markup(ReplyKeyboardRemove::new(true)),
+            GenresStep::Genres {
+                media_type,
+                age_restriction,
+                page,
+            } => {
+                let genres = unique_genres(&media_parser_sources, media_type, age_restriction);
+                let genres_stats = get_genres_stats(&uow_factory).await?;
+
+                genres_step(
+                    &genres,
+                    &genres_stats,
+                    media_type,
+                    age_restriction,
+                    page,
+                    if max_age_restriction.is_sfw() {
+                        "genres".to_owned()
+                    } else {
+                        format!("genres {media_type}")
+                    },
                 )
-                .await?;
             }
-        }
+        };
 
-        let res = uow
-            .user_media_view_repo()
-            .await
-            .map_err(HandlerError::new)?
-            .create(CreateUserMediaView::new(
-                &Uuid::new_v4(),
-                &db_user_id,
-                &media.id,
-            ))
-            .await;
+    bot.send(
+        EditMessageText::new(text)
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .reply_markup(reply_markup),
+    )
+    .await?;
 
-        match res {
-            Ok(()) => {
-                uow.commit().await.map_err(HandlerError::new)?;
+    bot.send(AnswerCallbackQuery::new(callback_query_id))
+        .await?;
 
-                event!(Level::DEBUG, "User media view created");
-            }
+    Ok(EventReturn::Finish)
+}
 
-            Err(RepoKind::Unexpected(err)) => {
-                uow.rollback().await.map_err(HandlerError::new)?;
+/// Creates inline keyboard with buttons to get next media of the genre.
+/// Callback data format: `media next {count} {genre}`
+fn next_media_markup(genre: &Genre) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new([[
+        InlineKeyboardButton::new("Next ▶").callback_data(format!("media next 1 {genre}")),
+        InlineKeyboardButton::new(format!("Next ×{NEXT_MANY_MEDIA_COUNT}"))
+            .callback_data(format!("media next {NEXT_MANY_MEDIA_COUNT} {genre}")),
+    ]])
+}
 
-                event!(Level::ERROR, %err, "Failed to create user media view");
+/// Checks if Telegram rejected the media, because it can't get the file by the url
+fn is_media_url_rejected(message: &str) -> bool {
+    let message = message.to_lowercase();
 
-                return Err(HandlerError::new(err));
-            }
-            Err(RepoKind::Exception(_)) => {
-                uow.rollback().await.map_err(HandlerError::new)?;
+    ["http url", "web page content", "wrong file identifier"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
 
-                event!(Level::WARN, "User media view already exists");
-            }
+/// Sends the media by url with the method, which matches its content
+/// # Errors
+/// Returns error if Telegram rejects the media
+pub async fn send_media(
+    bot: &Bot,
+    chat_id: i64,
+    media: &MediaEntity,
+    reply_parameters: Option<ReplyParameters>,
+    reply_markup: Option<InlineKeyboardMarkup>,
+) -> Result<(), SessionErrorKind> {
+    let file = InputFile::url(&media.url);
+
+    match media.send_method() {
+        SendMethod::Photo => {
+            bot.send(
+                SendPhoto::new(chat_id, file)
+                    .reply_parameters_option(reply_parameters)
+                    .reply_markup_option(reply_markup),
+            )
+            .await?;
         }
-    } else {
+        SendMethod::Animation => {
+            bot.send(
+                SendAnimation::new(chat_id, file)
+                    .reply_parameters_option(reply_parameters)
+                    .reply_markup_option(reply_markup),
+            )
+            .await?;
+        }
+        SendMethod::Video => {
+            bot.send(
+                SendVideo::new(chat_id, file)
+                    .reply_parameters_option(reply_parameters)
+                    .reply_markup_option(reply_markup),
+            )
+            .await?;
+        }
+        SendMethod::Document => {
+            bot.send(
+                SendDocument::new(chat_id, file)
+                    .reply_parameters_option(reply_parameters)
+                    .reply_markup_option(reply_markup),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends media of the genre, which the user hasn't viewed yet, and marks them as viewed.
+/// The last media is sent with buttons to get next media of the same genre.
+/// If Telegram rejects the media url, the media is marked as broken and another media is sent instead.
+/// After the shutdown is started, other media aren't sent, so every sent media is marked as viewed before the pool is closed.
+/// # Returns
+/// Count of sent media
+#[allow(clippy::too_many_arguments)]
+async fn send_genre_media<UoWFactory>(
+    bot: &Bot,
+    uow_factory: &UoWFactory,
+    shutdown: &Shutdown,
+    chat_id: i64,
+    reply_parameters: Option<ReplyParameters>,
+    db_user_id: &Uuid,
+    genre: &Genre,
+    count_media: u64,
+) -> Result<usize, HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let mut sent_count = 0;
+    let mut broken_count = 0;
+
+    while sent_count < count_media
+        && broken_count <= MAX_BROKEN_MEDIA_COUNT
+        && !shutdown.is_started()
+    {
         event!(
             Level::DEBUG,
-            count = media_group_len,
-            ?media_group,
-            "Sending media group",
+            count = count_media - sent_count,
+            "Getting media"
         );
 
-        let reply_parameters = ReplyParameters::new(message_id);
-        let reply_markup = match chat {
-            Chat::Private(_) => Some(
-                ReplyKeyboardMarkup::new([[KeyboardButton::new(format!("/{genre}",))]])
-                    .resize_keyboard(true)
-                    .one_time_keyboard(false),
-            ),
-            _ => None,
-        };
+        let media_group = uow
+            .media_reader()
+            .await
+            .map_err(HandlerError::new)?
+            .get_by_info_unviewed_by_user(GetMediaByInfoUnviewedByUser::new(
+                db_user_id,
+                Some(genre.name()),
+                genre.media_type().as_str(),
+                genre.age_restriction().as_str(),
+                None,
+                Some(count_media - sent_count),
+            ))
+            .await
+            .map_err(HandlerError::new)?;
+
+        let media_group_len = media_group.len();
+
+        if media_group_len == 0 {
+            break;
+        }
+
+        event!(Level::DEBUG, count = media_group_len, "Sending media");
 
         // We don't use media group here, because telegram doesn't support sending media group with gifs.
-        for media in media_group {
+        for (index, media) in media_group.iter().enumerate() {
+            if shutdown.is_started() {
+                event!(Level::INFO, sent_count, "Shutdown, stop sending media");
+
+                break;
+            }
+
+            Span::current().record("media_id", field::display(media.id));
+
             event!(Level::DEBUG, ?media, "Sending media");
 
-            match reply_markup {
-                Some(ref markup) => {
-                    bot.send(
-                        SendDocument::new(chat.id(), InputFile::url(&media.url))
-                            .reply_parameters(reply_parameters.clone())
-                            .reply_markup(markup.clone()),
-                    )
-                    .await?;
-                }
-                None => {
-                    bot.send(
-                        SendDocument::new(chat.id(), InputFile::url(&media.url))
-                            .reply_parameters(reply_parameters.clone())
-                            .reply_markup(ReplyKeyboardRemove::new(true)),
-                    )
-                    .await?;
+            // Buttons are added only to the last media, so they are always under the latest message
+            let reply_markup = (index + 1 == media_group_len).then(|| next_media_markup(genre));
+
+            match send_media(bot, chat_id, media, reply_parameters.clone(), reply_markup).await {
+                Ok(()) => {}
+                Err(SessionErrorKind::Telegram(TelegramErrorKind::BadRequest { message }))
+                    if is_media_url_rejected(&message) =>
+                {
+                    event!(Level::WARN, %message, "Telegram rejected media url");
+
+                    counter!("media_broken_total", "genre" => genre.name().to_owned()).increment(1);
+
+                    let res = uow
+                        .media_repo()
+                        .await
+                        .map_err(HandlerError::new)?
+                        .mark_checked(MarkMediaChecked::new(
+                            &media.id,
+                            Some(MediaStatus::Broken.as_str()),
+                        ))
+                        .await;
+
+                    match res {
+                        Ok(()) => uow.commit().await.map_err(HandlerError::new)?,
+                        Err(err) => {
+                            uow.rollback().await.map_err(HandlerError::new)?;
+
+                            event!(Level::ERROR, %err, "Failed to mark media as broken");
+                        }
+                    }
+
+                    broken_count += 1;
+
+                    continue;
                 }
+                Err(err) => return Err(err.into()),
             }
 
+            counter!(
+                "media_sent_total",
+                "genre" => genre.name().to_owned(),
+                "media_type" => genre.media_type().as_str(),
+                "send_method" => media.send_method().as_str(),
+            )
+            .increment(1);
+
+            sent_count += 1;
+
             let res = uow
                 .user_media_view_repo()
                 .await
                 .map_err(HandlerError::new)?
                 .create(CreateUserMediaView::new(
                     &Uuid::new_v4(),
-                    &db_user_id,
+                    db_user_id,
                     &media.id,
                 ))
                 .await;
@@ -406,5 +728,207 @@ where
         }
     }
 
+    if sent_count == 0 && !shutdown.is_started() {
+        event!(Level::DEBUG, "No media found for genre");
+
+        let mut method = SendMessage::new(chat_id, "No media found for genre");
+        if let Some(reply_parameters) = reply_parameters {
+            method = method.reply_parameters(reply_parameters);
+        }
+
+        bot.send(method).await?;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    Ok(sent_count as usize)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id, genre, media_id))]
+pub async fn genre<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    ShutdownWrapper(shutdown): ShutdownWrapper,
+    user: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let Some(CommandObject {
+        args,
+        command: genre,
+        ..
+    }) = CommandObject::extract(&text)
+    else {
+        event!(Level::DEBUG, text, "Not a command");
+
+        return Ok(EventReturn::Finish);
+    };
+
+    event!(Level::DEBUG, "Parsing genre");
+
+    let genre: Genre = match genre.as_ref().try_into() {
+        Ok(genre) => genre,
+        Err(err) => {
+            event!(Level::DEBUG, %err, genre, "Failed to parse genre");
+
+            bot.send(
+                SendMessage::new(chat.id(), err.to_string())
+                    .reply_parameters(ReplyParameters::new(message_id)),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    if !genre
+        .age_restriction()
+        .is_allowed_by(user.max_age_restriction())
+    {
+        event!(Level::DEBUG, "Age restriction isn't allowed");
+
+        bot.send(
+            SendMessage::new(
+                chat.id(),
+                "This content is disabled. You can change age restriction in the settings.\n\n/settings",
+            )
+            .reply_parameters(ReplyParameters::new(message_id)),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    let count_media = match args.first().map(|arg| arg.parse::<u64>()) {
+        Some(Ok(count)) => count.clamp(1, MAX_MEDIA_COUNT),
+        _ => 1,
+    };
+
+    send_genre_media(
+        &bot,
+        &uow_factory,
+        &shutdown,
+        chat.id(),
+        Some(ReplyParameters::new(message_id)),
+        &user.id,
+        &genre,
+        count_media,
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id, genre, media_id))]
+pub async fn genre_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        message: maybe_inaccessible_message,
+        ..
+    }: CallbackQuery,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    ShutdownWrapper(shutdown): ShutdownWrapper,
+    user: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let Some((count_media, genre)) = callback_data
+        .strip_prefix("media next ")
+        .and_then(|data| data.split_once(' '))
+        .and_then(|(count, genre)| {
+            Some((count.parse::<u64>().ok()?, Genre::try_from(genre).ok()?))
+        })
+    else {
+        return Err(HandlerError::new(anyhow!(
+            "Unknown callback data. Callback data: {callback_data}",
+        )));
+    };
+
+    Span::current().record("genre", field::display(&genre));
+
+    let chat_id =
+        if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
+            message.chat().id()
+        } else {
+            event!(
+                Level::WARN,
+                "Callback query doesn't have chat id. Message is too old",
+            );
+
+            bot.send(
+                AnswerCallbackQuery::new(callback_query_id)
+                    .text(format!("Message is too old. Please, send /{genre}")),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        };
+
+    if !genre
+        .age_restriction()
+        .is_allowed_by(user.max_age_restriction())
+    {
+        event!(Level::DEBUG, "Age restriction isn't allowed");
+
+        bot.send(
+            AnswerCallbackQuery::new(callback_query_id)
+                .text("This content is disabled. You can change age restriction in the settings"),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    // Answer before sending media to stop the loading animation on the button as soon as possible
+    bot.send(AnswerCallbackQuery::new(callback_query_id))
+        .await?;
+
+    send_genre_media(
+        &bot,
+        &uow_factory,
+        &shutdown,
+        chat_id,
+        None,
+        &user.id,
+        &genre,
+        count_media.clamp(1, MAX_MEDIA_COUNT),
+    )
+    .await?;
+
     Ok(EventReturn::Finish)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_media_url_rejected;
+
+    #[test]
+    fn test_is_media_url_rejected() {
+        assert!(is_media_url_rejected(
+            "Bad Request: wrong file identifier/HTTP URL specified"
+        ));
+        assert!(is_media_url_rejected(
+            "Bad Request: failed to get HTTP URL content"
+        ));
+        assert!(is_media_url_rejected(
+            "Bad Request: wrong type of the web page content"
+        ));
+        assert!(!is_media_url_rejected("Bad Request: chat not found"));
+    }
+}
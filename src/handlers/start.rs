@@ -16,10 +16,13 @@ pub async fn start(bot: Bot, message: Message) -> HandlerResult {
 
     let text = format!(
         "Hi, {first_name}!\n\n\
-        Get an anime GIF or image by genre!\n\
+        Get an anime GIF, image or video by genre!\n\
         /gifs\n\
-        /images\n\n\
-        /stats\n\n\
+        /images\n\
+        /videos\n\
+        /genres\n\n\
+        /stats\n\
+        /history\n\n\
         You can also pass media count you want to get. For example:\n\
         /neko_img_sfw 5\n",
         first_name = match message.from() {
@@ -1,33 +1,68 @@
 use crate::{
-    application::common::traits::{UnitOfWork, UnitOfWorkFactory},
+    application::{
+        common::traits::{UnitOfWork, UnitOfWorkFactory},
+        user_media_view::dto::GetUserMediaViewGenreStatsByUserId,
+    },
+    domain::{
+        media_parser::value_objects::{AgeRestriction, MediaType},
+        user::entities::User as UserEntity,
+    },
     extractors::UoWFactoryWrapper,
 };
 
 use telers::{
     errors::HandlerError,
     event::{telegram::HandlerResult, EventReturn},
+    filters::CommandObject,
     methods::SendMessage,
     types::{MessageText, ReplyParameters},
     Bot,
 };
 use tracing::{event, instrument, Level, Span};
 
+/// Max count of genres in statistics of the user, so the text fits into a message
+const MAX_USER_GENRES: usize = 30;
+
 #[instrument(skip_all, fields(%message_id))]
 pub async fn stats<UoWFactory>(
     bot: Bot,
     MessageText {
         id: message_id,
+        text,
         from,
         chat,
         ..
     }: MessageText,
     UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    user: UserEntity,
 ) -> HandlerResult
 where
     UoWFactory: UnitOfWorkFactory,
 {
     Span::current().record("user_id", from.map(|user| user.id));
 
+    let is_user_stats = CommandObject::extract(&text).map_or(false, |command| {
+        command.args.first().map_or(false, |arg| arg == "me")
+    });
+
+    let text = if is_user_stats {
+        user_stats(&uow_factory, &user).await?
+    } else {
+        media_stats(&uow_factory).await?
+    };
+
+    event!(Level::TRACE, "Sending stats");
+
+    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+async fn media_stats<UoWFactory>(uow_factory: &UoWFactory) -> Result<String, HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
     event!(Level::DEBUG, "Getting media stats");
 
     let mut uow = uow_factory.new_unit_of_work();
@@ -44,12 +79,71 @@ where
         .await
         .map_err(HandlerError::new)?;
 
-    let text = format!("Media statistics:\n\n{media_stats}\n\n{genre_stats}");
+    Ok(format!(
+        "Media statistics:\n\n{media_stats}\n\n{genre_stats}\n\
+        Your statistics: /stats me"
+    ))
+}
 
-    event!(Level::TRACE, "Sending media stats");
+async fn user_stats<UoWFactory>(
+    uow_factory: &UoWFactory,
+    user: &UserEntity,
+) -> Result<String, HandlerError>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    event!(Level::DEBUG, "Getting user stats");
 
-    bot.send(SendMessage::new(chat.id(), text).reply_parameters(ReplyParameters::new(message_id)))
-        .await?;
+    let mut uow = uow_factory.new_unit_of_work();
 
-    Ok(EventReturn::Finish)
+    let mut genres_stats = uow
+        .user_media_view_reader()
+        .await
+        .map_err(HandlerError::new)?
+        .get_genre_stats_by_user_id(GetUserMediaViewGenreStatsByUserId::new(&user.id))
+        .await
+        .map_err(HandlerError::new)?;
+
+    // Genres without views, which aren't allowed by the age restriction, aren't interesting for the user
+    let max_age_restriction = user.max_age_restriction();
+    genres_stats.0.retain(|genre| {
+        genre.viewed > 0
+            || AgeRestriction::try_from(genre.age_restriction.as_str())
+                .is_ok_and(|age_restriction| age_restriction.is_allowed_by(max_age_restriction))
+    });
+
+    let hidden_genres_count = genres_stats.0.len().saturating_sub(MAX_USER_GENRES);
+    let hidden_genres = if hidden_genres_count > 0 {
+        format!("And {hidden_genres_count} more genres, check them in /genres\n")
+    } else {
+        String::new()
+    };
+
+    Ok(format!(
+        "Your statistics:\n\n\
+        First seen: {first_seen}\n\
+        Viewed: {viewed}\n\
+        GIF: {gif}\n\
+        Image: {image}\n\
+        Video: {video}\n\
+        Favourite genre: {favourite}\n\n\
+        {most_viewed_genres}\
+        {hidden_genres}",
+        first_seen = user.created.date(),
+        viewed = genres_stats.viewed(),
+        gif = genres_stats.viewed_by_media_type(MediaType::Gif.as_str()),
+        image = genres_stats.viewed_by_media_type(MediaType::Image.as_str()),
+        video = genres_stats.viewed_by_media_type(MediaType::Video.as_str()),
+        most_viewed_genres = genres_stats.most_viewed(MAX_USER_GENRES),
+        favourite = genres_stats.favourite().map_or_else(
+            || "-".to_owned(),
+            |genre| format!(
+                "/{genre}_{media_type}_{age_restriction} ({viewed})",
+                genre = genre.genre,
+                media_type = genre.media_type,
+                age_restriction = genre.age_restriction,
+                viewed = genre.viewed,
+            ),
+        ),
+    ))
 }
@@ -1,9 +1,15 @@
 use crate::{
     application::{
-        common::traits::{UnitOfWork, UnitOfWorkFactory},
-        user::dto::UpdateUserShowNsfw,
+        common::{
+            exceptions::RepoKind,
+            traits::{UnitOfWork, UnitOfWorkFactory},
+        },
+        user::{
+            dto::{DeleteUser, UpdateUserMaxAgeRestriction},
+            services::{export_data, ExportFormat},
+        },
     },
-    domain::user::entities::User as UserEntity,
+    domain::{media_parser::value_objects::AgeRestriction, user::entities::User as UserEntity},
     extractors::UoWFactoryWrapper,
 };
 
@@ -11,10 +17,11 @@ use anyhow::anyhow;
 use telers::{
     errors::HandlerError,
     event::{telegram::HandlerResult, EventReturn},
-    methods::{AnswerCallbackQuery, DeleteMessage, SendMessage},
+    filters::CommandObject,
+    methods::{AnswerCallbackQuery, DeleteMessage, SendDocument, SendMessage},
     types::{
-        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage,
-        Message, ReplyParameters, User,
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+        MaybeInaccessibleMessage, Message, MessageText, ReplyParameters, User,
     },
     Bot,
 };
@@ -30,7 +37,7 @@ pub async fn settings(bot: Bot, message: Message) -> HandlerResult {
         SendMessage::new(message.chat().id(), "Settings")
             .reply_parameters(ReplyParameters::new(message.id()))
             .reply_markup(InlineKeyboardMarkup::new([[InlineKeyboardButton::new(
-                "Change age restriction (SFW / NSFW)",
+                "Change age restriction",
             )
             .callback_data("user update_age_restriction")]])),
     )
@@ -71,7 +78,7 @@ pub async fn settings_callback(
         SendMessage::new(chat_id, "Settings")
             .reply_parameters(ReplyParameters::new(message_id))
             .reply_markup(InlineKeyboardMarkup::new([[InlineKeyboardButton::new(
-                "Change age restriction (SFW / NSFW)",
+                "Change age restriction",
             )
             .callback_data("user update_age_restriction")]])),
     )
@@ -92,7 +99,7 @@ pub async fn update_age_restriction(
         message: maybe_inaccessible_message,
         ..
     }: CallbackQuery,
-    UserEntity { show_nsfw, .. }: UserEntity,
+    user: UserEntity,
 ) -> HandlerResult {
     let (chat_id, message_id) =
         if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
@@ -112,36 +119,37 @@ pub async fn update_age_restriction(
             return Ok(EventReturn::Finish);
         };
 
-    let show_nsfw = show_nsfw.map_or(false, |show_nsfw| show_nsfw);
+    let max_age_restriction = user.max_age_restriction();
+
+    event!(Level::DEBUG, %max_age_restriction, "Sending age restrictions");
+
+    let buttons = [
+        (AgeRestriction::Sfw, "Show SFW only"),
+        (
+            AgeRestriction::Questionable,
+            "Show up to questionable (18+)",
+        ),
+        (AgeRestriction::Nsfw, "Show up to NSFW (18+)"),
+    ]
+    .into_iter()
+    .filter(|(age_restriction, _)| *age_restriction != max_age_restriction)
+    .map(|(age_restriction, text)| {
+        [InlineKeyboardButton::new(text)
+            .callback_data(format!("user max_age_restriction {age_restriction}"))]
+    });
 
-    if show_nsfw {
-        event!(Level::DEBUG, "Show NSFW is enabled");
-
-        bot.send(
-            SendMessage::new(chat_id, "Change age restriction")
-                .reply_parameters(ReplyParameters::new(message_id))
-                .reply_markup(InlineKeyboardMarkup::new([[InlineKeyboardButton::new(
-                    "Disable show NSFW",
-                )
-                .callback_data("user disable_show_nsfw")]])),
-        )
-        .await?;
-    } else {
-        event!(Level::DEBUG, "Show NSFW is disabled");
-
-        bot.send(
-            SendMessage::new(
-                chat_id,
-                "Change age restriction\n\nBy clicking on the button, you confirm that you're 18 years old",
-            )
-                .reply_parameters(ReplyParameters::new(message_id))
-                .reply_markup(InlineKeyboardMarkup::new([[InlineKeyboardButton::new(
-                    "Enable show NSFW (18+)",
-                )
-                .callback_data("user enable_show_nsfw")]])),
+    bot.send(
+        SendMessage::new(
+            chat_id,
+            format!(
+                "Change age restriction\n\nCurrent: {max_age_restriction}\n\n\
+                By choosing questionable or NSFW, you confirm that you're 18 years old",
+            ),
         )
-        .await?;
-    }
+        .reply_parameters(ReplyParameters::new(message_id))
+        .reply_markup(InlineKeyboardMarkup::new(buttons)),
+    )
+    .await?;
 
     bot.send(AnswerCallbackQuery::new(callback_query_id))
         .await?;
@@ -168,9 +176,11 @@ where
     // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
     let callback_data = data.as_deref().unwrap();
 
-    let show_nsfw = match callback_data {
-        "user enable_show_nsfw" => true,
-        "user disable_show_nsfw" => false,
+    let max_age_restriction = match callback_data
+        .strip_prefix("user max_age_restriction ")
+        .map(AgeRestriction::try_from)
+    {
+        Some(Ok(age_restriction)) if !age_restriction.is_unknown() => age_restriction,
         _ => {
             return Err(HandlerError::new(anyhow!(
                 "Unknown callback data. Callback data: {callback_data}",
@@ -178,14 +188,17 @@ where
         }
     };
 
-    event!(Level::DEBUG, show_nsfw, "Updating show NSFW");
+    event!(Level::DEBUG, %max_age_restriction, "Updating max age restriction");
 
     let mut uow = uow_factory.new_unit_of_work();
 
     uow.user_repo()
         .await
         .map_err(HandlerError::new)?
-        .update_show_nsfw(UpdateUserShowNsfw::new(&db_user_id, show_nsfw))
+        .update_max_age_restriction(UpdateUserMaxAgeRestriction::new(
+            &db_user_id,
+            max_age_restriction.as_str(),
+        ))
         .await
         .map_err(HandlerError::new)?;
 
@@ -193,12 +206,215 @@ where
 
     drop(uow);
 
-    event!(Level::DEBUG, show_nsfw, "Show NSFW updated");
+    event!(Level::DEBUG, %max_age_restriction, "Max age restriction updated");
 
-    let text = if show_nsfw {
-        "You have enabled show NSFW!"
+    bot.send(
+        AnswerCallbackQuery::new(callback_query_id)
+            .text(format!("Age restriction changed to {max_age_restriction}!"))
+            .cache_time(5),
+    )
+    .await?;
+
+    if let Some(MaybeInaccessibleMessage::Message(message)) = maybe_inaccessible_message {
+        bot.send(DeleteMessage::new(message.chat().id(), message.id()))
+            .await?;
     } else {
-        "You have disabled show NSFW!"
+        event!(
+            Level::WARN,
+            "Callback query doesn't have message. Message is too old"
+        );
+    };
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%message_id, user_id))]
+pub async fn my_data<UoWFactory>(
+    bot: Bot,
+    MessageText {
+        id: message_id,
+        text,
+        from,
+        chat,
+        ..
+    }: MessageText,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    user: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    Span::current().record("user_id", from.map(|user| user.id));
+
+    let format = match CommandObject::extract(&text).and_then(|command| {
+        command
+            .args
+            .first()
+            .map(|arg| ExportFormat::try_from(arg.as_ref()))
+    }) {
+        Some(Ok(format)) => format,
+        Some(Err(err)) => {
+            event!(Level::DEBUG, %err, "Failed to parse export format");
+
+            bot.send(
+                SendMessage::new(chat.id(), err.to_string())
+                    .reply_parameters(ReplyParameters::new(message_id)),
+            )
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+        None => ExportFormat::default(),
+    };
+
+    event!(Level::DEBUG, ?format, "Exporting user data");
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let content = export_data(&mut uow, &user, format)
+        .await
+        .map_err(HandlerError::new)?;
+
+    drop(uow);
+
+    event!(Level::DEBUG, size = content.len(), "Sending user data");
+
+    bot.send(
+        SendDocument::new(
+            chat.id(),
+            InputFile::buffered_with_name(
+                content,
+                format!("my_data.{extension}", extension = format.extension()),
+            ),
+        )
+        .caption("Your profile, settings and view history")
+        .reply_parameters(ReplyParameters::new(message_id)),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_me(bot: Bot, message: Message) -> HandlerResult {
+    Span::current().record("user_id", message.from_id());
+
+    // The command is handled only in private chats, but we pass the user id to be sure that only the user can confirm the deletion
+    let Some(user_id) = message.from_id() else {
+        event!(Level::WARN, "Message doesn't have sender");
+
+        return Ok(EventReturn::Finish);
+    };
+
+    event!(Level::DEBUG, "Sending delete confirmation");
+
+    bot.send(
+        SendMessage::new(
+            message.chat().id(),
+            "Are you sure you want to delete your data?\n\n\
+            Your profile, settings and view history will be deleted permanently. \
+            You can get a copy of your data before deleting it with /mydata",
+        )
+        .reply_parameters(ReplyParameters::new(message.id()))
+        .reply_markup(InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::new("Yes, delete my data")
+                .callback_data(format!("user delete_me_confirm {user_id}")),
+            InlineKeyboardButton::new("Cancel")
+                .callback_data(format!("user delete_me_cancel {user_id}")),
+        ]])),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[instrument(skip_all, fields(%callback_query_id, %user_id))]
+pub async fn delete_me_callback<UoWFactory>(
+    bot: Bot,
+    CallbackQuery {
+        id: callback_query_id,
+        from: User { id: user_id, .. },
+        data,
+        message: maybe_inaccessible_message,
+        ..
+    }: CallbackQuery,
+    UoWFactoryWrapper(uow_factory): UoWFactoryWrapper<UoWFactory>,
+    UserEntity { id: db_user_id, .. }: UserEntity,
+) -> HandlerResult
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    // `unwrap` is safe here, because we use `Text` filter for this handler, so we can be sure that `data` is `Some`
+    let callback_data = data.as_deref().unwrap();
+
+    let (confirmed, owner_id) = match callback_data
+        .strip_prefix("user delete_me_")
+        .and_then(|action| action.split_once(' '))
+        .and_then(|(action, owner_id)| Some((action, owner_id.parse::<i64>().ok()?)))
+    {
+        Some(("confirm", owner_id)) => (true, owner_id),
+        Some(("cancel", owner_id)) => (false, owner_id),
+        _ => {
+            return Err(HandlerError::new(anyhow!(
+                "Unknown callback data. Callback data: {callback_data}",
+            )));
+        }
+    };
+
+    if owner_id != user_id {
+        event!(
+            Level::DEBUG,
+            owner_id,
+            "Deletion isn't requested by the user"
+        );
+
+        bot.send(
+            AnswerCallbackQuery::new(callback_query_id)
+                .text("This button isn't for you")
+                .cache_time(5),
+        )
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    let text = if confirmed {
+        event!(Level::DEBUG, "Deleting user");
+
+        let mut uow = uow_factory.new_unit_of_work();
+
+        // User media views are deleted by the `ON DELETE CASCADE` constraint
+        match uow
+            .user_repo()
+            .await
+            .map_err(HandlerError::new)?
+            .delete(DeleteUser::new(&db_user_id))
+            .await
+        {
+            Ok(()) => {
+                uow.commit().await.map_err(HandlerError::new)?;
+
+                event!(Level::DEBUG, "User deleted");
+            }
+            Err(RepoKind::Exception(err)) => {
+                uow.rollback().await.map_err(HandlerError::new)?;
+
+                event!(Level::DEBUG, %err, "User already deleted");
+            }
+            Err(RepoKind::Unexpected(err)) => {
+                uow.rollback().await.map_err(HandlerError::new)?;
+
+                event!(Level::ERROR, %err, "Failed to delete user");
+
+                return Err(HandlerError::new(err));
+            }
+        }
+
+        "Your data has been deleted!"
+    } else {
+        event!(Level::DEBUG, "User deletion cancelled");
+
+        "Deletion cancelled"
     };
 
     bot.send(
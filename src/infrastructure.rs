@@ -1,2 +1,8 @@
 pub mod database;
+pub mod health;
+pub mod media_deduplicator;
 pub mod media_parser;
+pub mod media_validator;
+pub mod media_verifier;
+pub mod metrics;
+pub mod shutdown;
@@ -0,0 +1,86 @@
+use sqlx::{
+    migrate::{Migrate as _, MigrateError, Migrator},
+    PgPool,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// Migrations from `./src/infrastructure/database/migrations`, which are embedded in the binary
+pub static MIGRATOR: Migrator = sqlx::migrate!("./src/infrastructure/database/migrations");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    /// Applied migration differs from the embedded one
+    pub checksum_mismatch: bool,
+}
+
+impl Display for MigrationInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{version}/{status} {description}",
+            version = self.version,
+            status = match (self.applied, self.checksum_mismatch) {
+                (true, false) => "installed",
+                (true, true) => "installed (different checksum)",
+                (false, _) => "pending",
+            },
+            description = self.description,
+        )
+    }
+}
+
+/// Applies pending migrations
+/// # Errors
+/// Returns error if a migration can't be applied or applied migrations differ from the embedded ones
+pub async fn run(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await
+}
+
+/// Returns embedded migrations with their status in the database
+/// # Errors
+/// Returns error if applied migrations can't be listed
+pub async fn info(pool: &PgPool) -> Result<Vec<MigrationInfo>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+
+    conn.ensure_migrations_table().await?;
+
+    let applied_migrations = conn.list_applied_migrations().await?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| {
+            let applied_migration = applied_migrations
+                .iter()
+                .find(|applied_migration| applied_migration.version == migration.version);
+
+            MigrationInfo {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied: applied_migration.is_some(),
+                checksum_mismatch: applied_migration.map_or(false, |applied_migration| {
+                    applied_migration.checksum != migration.checksum
+                }),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MIGRATOR;
+
+    #[test]
+    fn test_migrations_are_sorted() {
+        let versions = MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .collect::<Vec<_>>();
+
+        assert!(!versions.is_empty());
+        assert!(versions.windows(2).all(|window| window[0] < window[1]));
+    }
+}
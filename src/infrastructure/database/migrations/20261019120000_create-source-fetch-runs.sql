@@ -0,0 +1,29 @@
+BEGIN;
+
+/*
+    Create source_fetch_runs table. Check `src/infrastructure/database/models/source_fetch_stats.rs`.
+    The table contains rolling counters of fetch runs for each source and genre, so it doesn't grow over time
+*/
+CREATE TABLE source_fetch_runs (
+    id UUID NOT NULL DEFAULT uuid_generate_v4(),
+    source_id UUID NOT NULL,
+    genre VARCHAR NOT NULL,
+    media_type VARCHAR NOT NULL,
+    is_sfw BOOLEAN NOT NULL,
+    success_count BIGINT NOT NULL DEFAULT 0,
+    failure_count BIGINT NOT NULL DEFAULT 0,
+    new_media_count BIGINT NOT NULL DEFAULT 0,
+    duplicate_media_count BIGINT NOT NULL DEFAULT 0,
+    total_latency_ms BIGINT NOT NULL DEFAULT 0,
+    last_latency_ms BIGINT NOT NULL DEFAULT 0,
+    last_error VARCHAR,
+    last_error_at TIMESTAMPTZ,
+    last_success_at TIMESTAMPTZ,
+    created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (source_id) REFERENCES sources (id) ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE (source_id, genre, media_type, is_sfw)
+);
+
+COMMIT;
@@ -0,0 +1,16 @@
+BEGIN;
+
+/*
+    Random key is assigned to media once, so random media are selected with an index seek
+    from a random pivot instead of sorting all media of the genre with `ORDER BY random()`.
+    The default is volatile, so existing media get different keys.
+*/
+ALTER TABLE media ADD COLUMN random_key DOUBLE PRECISION NOT NULL DEFAULT random();
+
+CREATE INDEX media_genre_media_type_is_sfw_random_key_idx
+    ON media (genre, media_type, is_sfw, random_key);
+
+/* Views are checked by user and media with the unique index, this one is for deletes and updates of media */
+CREATE INDEX user_media_views_media_id_idx ON user_media_views (media_id);
+
+COMMIT;
@@ -0,0 +1,19 @@
+BEGIN;
+
+/*
+    Status of media link, check `src/domain/media/value_objects/media_status.rs`.
+    Broken media aren't sent to users, `checked_at` is time of the last check by the media verifier.
+*/
+ALTER TABLE media
+    ADD COLUMN status VARCHAR NOT NULL DEFAULT 'active',
+    ADD COLUMN checked_at TIMESTAMPTZ;
+
+/* Random media are selected only from active ones */
+DROP INDEX media_genre_media_type_is_sfw_random_key_idx;
+CREATE INDEX media_genre_media_type_is_sfw_status_random_key_idx
+    ON media (genre, media_type, is_sfw, status, random_key);
+
+/* Media verifier checks media, which weren't checked for the longest time, first */
+CREATE INDEX media_checked_at_idx ON media (checked_at NULLS FIRST);
+
+COMMIT;
@@ -0,0 +1,20 @@
+BEGIN;
+
+/*
+    Hashes of media content, which are computed by the media deduplicator.
+    `content_hash` is SHA-256 of the file in hex, `perceptual_hash` is dHash of the image or the first frame of GIF.
+    Duplicates reference the media of the same genre and type, which they duplicate, by `canonical_id`.
+    Only canonical media are sent to users, and a view of a duplicate is a view of its canonical media.
+*/
+ALTER TABLE media
+    ADD COLUMN content_hash VARCHAR,
+    ADD COLUMN perceptual_hash BIGINT,
+    ADD COLUMN canonical_id UUID REFERENCES media(id) ON UPDATE CASCADE ON DELETE SET NULL,
+    ADD COLUMN hashed_at TIMESTAMPTZ;
+
+CREATE INDEX media_content_hash_idx ON media (content_hash);
+CREATE INDEX media_canonical_id_idx ON media (canonical_id);
+/* Media deduplicator hashes media, which weren't hashed yet, from the oldest ones */
+CREATE INDEX media_created_not_hashed_idx ON media (created) WHERE hashed_at IS NULL;
+
+COMMIT;
@@ -0,0 +1,18 @@
+BEGIN;
+
+/*
+    Info about media content, which is collected by the media validator from magic bytes and headers of the file.
+    `mime` is sniffed by magic bytes, `size` is in bytes, `width` and `height` are in pixels.
+    They are used to choose the Telegram method to send media, check `src/domain/media/value_objects/send_method.rs`.
+*/
+ALTER TABLE media
+    ADD COLUMN mime VARCHAR,
+    ADD COLUMN size BIGINT,
+    ADD COLUMN width INTEGER,
+    ADD COLUMN height INTEGER,
+    ADD COLUMN validated_at TIMESTAMPTZ;
+
+/* Media validator validates media, which weren't validated yet, from the oldest ones */
+CREATE INDEX media_created_not_validated_idx ON media (created) WHERE validated_at IS NULL;
+
+COMMIT;
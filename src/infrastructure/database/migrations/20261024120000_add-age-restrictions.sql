@@ -0,0 +1,34 @@
+BEGIN;
+
+/*
+    Age restriction of media is one of `sfw`, `questionable`, `nsfw` and `unknown`, check `src/domain/media_parser/value_objects/age_restriction.rs`.
+    It replaces `is_sfw`, which couldn't represent questionable media of booru-style sources.
+*/
+ALTER TABLE media ADD COLUMN age_restriction VARCHAR NOT NULL DEFAULT 'unknown';
+
+UPDATE media
+SET age_restriction = CASE is_sfw WHEN true THEN 'sfw' WHEN false THEN 'nsfw' ELSE 'unknown' END;
+
+DROP INDEX media_genre_media_type_is_sfw_status_random_key_idx;
+ALTER TABLE media DROP COLUMN is_sfw;
+CREATE INDEX media_genre_media_type_age_restriction_status_random_key_idx
+    ON media (genre, media_type, age_restriction, status, random_key);
+
+ALTER TABLE source_fetch_runs ADD COLUMN age_restriction VARCHAR NOT NULL DEFAULT 'unknown';
+
+UPDATE source_fetch_runs
+SET age_restriction = CASE is_sfw WHEN true THEN 'sfw' ELSE 'nsfw' END;
+
+/* Unique constraint with `is_sfw` is dropped with the column */
+ALTER TABLE source_fetch_runs DROP COLUMN is_sfw;
+ALTER TABLE source_fetch_runs ALTER COLUMN age_restriction DROP DEFAULT;
+ALTER TABLE source_fetch_runs ADD UNIQUE (source_id, genre, media_type, age_restriction);
+
+/* The most restrictive age restriction of media, which the user allowed to show. It replaces `show_nsfw` toggle */
+ALTER TABLE users ADD COLUMN max_age_restriction VARCHAR NOT NULL DEFAULT 'sfw';
+
+UPDATE users SET max_age_restriction = 'nsfw' WHERE show_nsfw;
+
+ALTER TABLE users DROP COLUMN show_nsfw;
+
+COMMIT;
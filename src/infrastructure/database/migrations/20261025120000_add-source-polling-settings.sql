@@ -0,0 +1,10 @@
+BEGIN;
+
+/*
+    Polling settings of sources, check `src/infrastructure/media_parser/control.rs`.
+    Disabled sources aren't polled by the worker, and `polling_interval_ms` is the time between fetches of genres of the source
+*/
+ALTER TABLE sources ADD COLUMN enabled BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE sources ADD COLUMN polling_interval_ms BIGINT NOT NULL DEFAULT 3000 CHECK (polling_interval_ms >= 0);
+
+COMMIT;
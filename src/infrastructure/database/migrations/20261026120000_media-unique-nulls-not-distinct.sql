@@ -0,0 +1,39 @@
+BEGIN;
+
+/*
+    `UNIQUE (url, genre)` treats NULL genres as distinct, so upserts by `(url, genre)` duplicated media without a genre.
+    Duplicates are merged into the oldest media with the url before the constraint is recreated with `NULLS NOT DISTINCT`
+*/
+CREATE TEMPORARY TABLE media_duplicates ON COMMIT DROP AS
+SELECT id, kept_id FROM (
+    SELECT
+        id,
+        first_value(id) OVER (PARTITION BY url ORDER BY created, id) AS kept_id
+    FROM media
+    WHERE genre IS NULL
+) AS media_with_kept_id
+WHERE id <> kept_id;
+
+DELETE FROM user_media_views
+USING media_duplicates
+WHERE user_media_views.media_id = media_duplicates.id
+    AND EXISTS (
+        SELECT 1 FROM user_media_views AS kept_views
+        WHERE kept_views.user_id = user_media_views.user_id
+            AND kept_views.media_id = media_duplicates.kept_id
+    );
+
+UPDATE user_media_views SET media_id = media_duplicates.kept_id
+FROM media_duplicates
+WHERE user_media_views.media_id = media_duplicates.id;
+
+UPDATE media SET canonical_id = NULLIF(media_duplicates.kept_id, media.id)
+FROM media_duplicates
+WHERE media.canonical_id = media_duplicates.id;
+
+DELETE FROM media USING media_duplicates WHERE media.id = media_duplicates.id;
+
+ALTER TABLE media DROP CONSTRAINT media_url_genre_key;
+ALTER TABLE media ADD CONSTRAINT media_url_genre_key UNIQUE NULLS NOT DISTINCT (url, genre);
+
+COMMIT;
@@ -0,0 +1,17 @@
+BEGIN;
+
+/*
+    Create source_paused_genres table. Check `src/infrastructure/database/models/source_paused_genre.rs`.
+    Paused genres of sources aren't fetched by workers, and they're kept after restarts of workers
+*/
+CREATE TABLE source_paused_genres (
+    source_id UUID NOT NULL,
+    genre VARCHAR NOT NULL,
+    media_type VARCHAR NOT NULL,
+    age_restriction VARCHAR NOT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (source_id, genre, media_type, age_restriction),
+    FOREIGN KEY (source_id) REFERENCES sources (id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+COMMIT;
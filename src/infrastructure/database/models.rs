@@ -1,13 +1,25 @@
 pub mod genre_stats;
+pub mod genre_view_stats;
 pub mod media;
 pub mod media_stats;
 pub mod source;
+pub mod source_fetch_stats;
+pub mod source_paused_genre;
 pub mod user;
+pub mod user_genre_stats;
 pub mod user_media_view;
+pub mod user_media_view_with_media;
+pub mod user_stats;
 
 pub use genre_stats::GenreStats;
+pub use genre_view_stats::GenreViewStats;
 pub use media::Media;
 pub use media_stats::MediaStats;
 pub use source::Source;
+pub use source_fetch_stats::SourceFetchStats;
+pub use source_paused_genre::SourcePausedGenre;
 pub use user::User;
+pub use user_genre_stats::UserGenreStats;
 pub use user_media_view::UserMediaView;
+pub use user_media_view_with_media::UserMediaViewWithMedia;
+pub use user_stats::UserStats;
@@ -7,7 +7,7 @@ pub struct GenreStats {
     pub total: i64,
     pub genre: String,
     pub media_type: String,
-    pub is_sfw: bool,
+    pub age_restriction: String,
 }
 
 impl From<GenreStats> for GenreStatsEntity {
@@ -16,7 +16,7 @@ impl From<GenreStats> for GenreStatsEntity {
             total: genre.total,
             genre: genre.genre,
             media_type: genre.media_type,
-            is_sfw: genre.is_sfw,
+            age_restriction: genre.age_restriction,
         }
     }
 }
@@ -0,0 +1,24 @@
+use crate::domain::user_media_view::entities::GenreViewStats as GenreViewStatsEntity;
+
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct GenreViewStats {
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    pub max_viewed: i64,
+    pub recent_views: i64,
+}
+
+impl From<GenreViewStats> for GenreViewStatsEntity {
+    fn from(stats: GenreViewStats) -> Self {
+        Self {
+            genre: stats.genre,
+            media_type: stats.media_type,
+            age_restriction: stats.age_restriction,
+            max_viewed: stats.max_viewed,
+            recent_views: stats.recent_views,
+        }
+    }
+}
@@ -12,9 +12,13 @@ pub struct Media {
     pub url: String,
     pub genre: Option<String>,
     pub media_type: String,
-    pub is_sfw: Option<bool>,
+    pub age_restriction: String,
     pub source_id: Uuid,
     pub created: OffsetDateTime,
+    pub mime: Option<String>,
+    pub size: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
 }
 
 impl From<Media> for MediaEntity {
@@ -24,9 +28,13 @@ impl From<Media> for MediaEntity {
             url: media.url,
             genre: media.genre,
             media_type: media.media_type,
-            is_sfw: media.is_sfw,
+            age_restriction: media.age_restriction,
             source_id: media.source_id,
             created: media.created,
+            mime: media.mime,
+            size: media.size,
+            width: media.width,
+            height: media.height,
         }
     }
 }
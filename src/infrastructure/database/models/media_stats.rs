@@ -7,8 +7,10 @@ pub struct MediaStats {
     pub total: i64,
     pub gif: i64,
     pub image: i64,
+    pub video: i64,
     pub unknown: i64,
     pub sfw: i64,
+    pub questionable: i64,
     pub nsfw: i64,
 }
 
@@ -18,8 +20,10 @@ impl From<MediaStats> for MediaStatsEntity {
             total: media.total,
             gif: media.gif,
             image: media.image,
+            video: media.video,
             unknown: media.unknown,
             sfw: media.sfw,
+            questionable: media.questionable,
             nsfw: media.nsfw,
         }
     }
@@ -10,6 +10,8 @@ pub struct Source {
     pub id: Uuid,
     pub name: String,
     pub url: String,
+    pub enabled: bool,
+    pub polling_interval_ms: i64,
     pub created: OffsetDateTime,
 }
 
@@ -19,6 +21,8 @@ impl From<Source> for SourceEntity {
             id: source.id,
             name: source.name,
             url: source.url,
+            enabled: source.enabled,
+            polling_interval_ms: source.polling_interval_ms,
             created: source.created,
         }
     }
@@ -0,0 +1,42 @@
+use sqlx::{types::time::OffsetDateTime, FromRow};
+
+use crate::domain::source::entities::SourceFetchStats as SourceFetchStatsEntity;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct SourceFetchStats {
+    pub source_name: String,
+    pub source_enabled: bool,
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    pub success_count: i64,
+    pub failure_count: i64,
+    pub new_media_count: i64,
+    pub duplicate_media_count: i64,
+    pub total_latency_ms: i64,
+    pub last_latency_ms: i64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<OffsetDateTime>,
+    pub last_success_at: Option<OffsetDateTime>,
+}
+
+impl From<SourceFetchStats> for SourceFetchStatsEntity {
+    fn from(stats: SourceFetchStats) -> Self {
+        Self {
+            source_name: stats.source_name,
+            source_enabled: stats.source_enabled,
+            genre: stats.genre,
+            media_type: stats.media_type,
+            age_restriction: stats.age_restriction,
+            success_count: stats.success_count,
+            failure_count: stats.failure_count,
+            new_media_count: stats.new_media_count,
+            duplicate_media_count: stats.duplicate_media_count,
+            total_latency_ms: stats.total_latency_ms,
+            last_latency_ms: stats.last_latency_ms,
+            last_error: stats.last_error,
+            last_error_at: stats.last_error_at,
+            last_success_at: stats.last_success_at,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use sqlx::FromRow;
+
+use crate::domain::source::entities::SourcePausedGenre as SourcePausedGenreEntity;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct SourcePausedGenre {
+    pub source_name: String,
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+}
+
+impl From<SourcePausedGenre> for SourcePausedGenreEntity {
+    fn from(paused_genre: SourcePausedGenre) -> Self {
+        Self {
+            source_name: paused_genre.source_name,
+            genre: paused_genre.genre,
+            media_type: paused_genre.media_type,
+            age_restriction: paused_genre.age_restriction,
+        }
+    }
+}
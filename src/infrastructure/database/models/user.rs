@@ -10,7 +10,7 @@ pub struct User {
     pub id: Uuid,
     pub tg_id: i64,
     pub language_code: Option<String>,
-    pub show_nsfw: Option<bool>,
+    pub max_age_restriction: String,
     pub created: OffsetDateTime,
 }
 
@@ -20,7 +20,7 @@ impl From<User> for UserEntity {
             id: user.id,
             tg_id: user.tg_id,
             language_code: user.language_code,
-            show_nsfw: user.show_nsfw,
+            max_age_restriction: user.max_age_restriction,
             created: user.created,
         }
     }
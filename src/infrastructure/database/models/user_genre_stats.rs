@@ -0,0 +1,24 @@
+use crate::domain::user_media_view::entities::UserGenreStats as UserGenreStatsEntity;
+
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct UserGenreStats {
+    pub genre: String,
+    pub media_type: String,
+    pub age_restriction: String,
+    pub total: i64,
+    pub viewed: i64,
+}
+
+impl From<UserGenreStats> for UserGenreStatsEntity {
+    fn from(genre: UserGenreStats) -> Self {
+        Self {
+            genre: genre.genre,
+            media_type: genre.media_type,
+            age_restriction: genre.age_restriction,
+            total: genre.total,
+            viewed: genre.viewed,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::domain::{
+    media::entities::Media as MediaEntity,
+    user_media_view::entities::{
+        UserMediaView as UserMediaViewEntity,
+        UserMediaViewWithMedia as UserMediaViewWithMediaEntity,
+    },
+};
+
+use sqlx::{
+    types::{time::OffsetDateTime, Uuid},
+    FromRow,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct UserMediaViewWithMedia {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub media_id: Uuid,
+    pub created: OffsetDateTime,
+    pub media_url: String,
+    pub media_genre: Option<String>,
+    pub media_type: String,
+    pub media_age_restriction: String,
+    pub media_source_id: Uuid,
+    pub media_created: OffsetDateTime,
+    pub media_mime: Option<String>,
+    pub media_size: Option<i64>,
+    pub media_width: Option<i32>,
+    pub media_height: Option<i32>,
+}
+
+impl From<UserMediaViewWithMedia> for UserMediaViewWithMediaEntity {
+    fn from(user_media_view: UserMediaViewWithMedia) -> Self {
+        Self {
+            view: UserMediaViewEntity {
+                id: user_media_view.id,
+                user_id: user_media_view.user_id,
+                media_id: user_media_view.media_id,
+                created: user_media_view.created,
+            },
+            media: MediaEntity {
+                id: user_media_view.media_id,
+                url: user_media_view.media_url,
+                genre: user_media_view.media_genre,
+                media_type: user_media_view.media_type,
+                age_restriction: user_media_view.media_age_restriction,
+                source_id: user_media_view.media_source_id,
+                created: user_media_view.media_created,
+                mime: user_media_view.media_mime,
+                size: user_media_view.media_size,
+                width: user_media_view.media_width,
+                height: user_media_view.media_height,
+            },
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::domain::user::entities::UserStats as UserStatsEntity;
+
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct UserStats {
+    pub total: i64,
+    pub show_questionable: i64,
+    pub show_nsfw: i64,
+    pub created_last_day: i64,
+    pub created_last_week: i64,
+    pub active_last_week: i64,
+}
+
+impl From<UserStats> for UserStatsEntity {
+    fn from(stats: UserStats) -> Self {
+        Self {
+            total: stats.total,
+            show_questionable: stats.show_questionable,
+            show_nsfw: stats.show_nsfw,
+            created_last_day: stats.created_last_day,
+            created_last_week: stats.created_last_week,
+            active_last_week: stats.active_last_week,
+        }
+    }
+}
@@ -3,23 +3,36 @@ use crate::{
         common::exceptions::{RepoError, RepoKind},
         media::{
             dto::{
-                CreateMedia, GetMediaById, GetMediaByInfo, GetMediaByInfoUnviewedByUser,
-                GetMediaByUrl,
+                CreateMedia, DeleteMediaByInfo, GetCanonicalMedia, GetMediaById, GetMediaByInfo,
+                GetMediaByInfoUnviewedByUser, GetMediaByUrl, GetMediaPage, GetMediaToCheck,
+                GetMediaToHash, GetMediaToValidate, MarkMediaChecked, SetMediaFileInfo,
+                SetMediaHashes,
             },
             exceptions::{MediaIdNotExist, MediaUrlAndGenreAlreadyExists},
             traits::{MediaReader, MediaRepo},
         },
     },
-    domain::media::entities::{GenresStats, Media, MediaStats},
+    domain::{
+        media::{
+            entities::{GenresStats, Media, MediaStats},
+            value_objects::MediaStatus,
+        },
+        media_parser::value_objects::{AgeRestriction, MediaType},
+    },
     infrastructure::database::models::{
         GenreStats as GenreStatsModel, Media as MediaModel, MediaStats as MediaStatsModel,
     },
 };
 
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, Func, JoinType, Order, PostgresQueryBuilder, Query};
+use sea_query::{
+    Alias, Asterisk, Cond, Expr, Func, JoinType, NullOrdering, OnConflict, Order,
+    PostgresQueryBuilder, Query, SimpleExpr, UnionType,
+};
 use sea_query_binder::SqlxBinder as _;
 use sqlx::PgConnection;
+use tracing::instrument;
+use uuid::Uuid;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct MediaRepoImpl<Conn> {
@@ -34,6 +47,7 @@ impl<Conn> MediaRepoImpl<Conn> {
 
 #[async_trait]
 impl<'a> MediaRepo for MediaRepoImpl<&'a mut PgConnection> {
+    #[instrument(skip_all)]
     async fn create<'s>(
         &mut self,
         media: CreateMedia<'s>,
@@ -45,7 +59,7 @@ impl<'a> MediaRepo for MediaRepoImpl<&'a mut PgConnection> {
                 Alias::new("url"),
                 Alias::new("genre"),
                 Alias::new("media_type"),
-                Alias::new("is_sfw"),
+                Alias::new("age_restriction"),
                 Alias::new("source_id"),
             ])
             .values_panic([
@@ -53,7 +67,7 @@ impl<'a> MediaRepo for MediaRepoImpl<&'a mut PgConnection> {
                 media.url().into(),
                 media.genre().into(),
                 media.media_type().into(),
-                media.is_sfw().into(),
+                media.age_restriction().into(),
                 (*media.source_id()).into(),
             ])
             .build_sqlx(PostgresQueryBuilder);
@@ -77,6 +91,230 @@ impl<'a> MediaRepo for MediaRepoImpl<&'a mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    #[instrument(skip_all, fields(count = media.len()))]
+    async fn create_many<'s>(&mut self, media: &[CreateMedia<'s>]) -> Result<Vec<Uuid>, RepoError> {
+        if media.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut query = Query::insert();
+
+        query.into_table(Alias::new("media")).columns([
+            Alias::new("id"),
+            Alias::new("url"),
+            Alias::new("genre"),
+            Alias::new("media_type"),
+            Alias::new("age_restriction"),
+            Alias::new("source_id"),
+        ]);
+
+        for media in media {
+            query.values_panic([
+                (*media.id()).into(),
+                media.url().into(),
+                media.genre().into(),
+                media.media_type().into(),
+                media.age_restriction().into(),
+                (*media.source_id()).into(),
+            ]);
+        }
+
+        let (sql, values) = query
+            .on_conflict(
+                OnConflict::columns([Alias::new("url"), Alias::new("genre")])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .returning_col(Alias::new("id"))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|ids: Vec<(Uuid,)>| ids.into_iter().map(|(id,)| id).collect())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all, fields(count = media.len()))]
+    async fn upsert_many<'s>(&mut self, media: &[CreateMedia<'s>]) -> Result<u64, RepoError> {
+        if media.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query = Query::insert();
+
+        query.into_table(Alias::new("media")).columns([
+            Alias::new("id"),
+            Alias::new("url"),
+            Alias::new("genre"),
+            Alias::new("media_type"),
+            Alias::new("age_restriction"),
+            Alias::new("source_id"),
+        ]);
+
+        for media in media {
+            query.values_panic([
+                (*media.id()).into(),
+                media.url().into(),
+                media.genre().into(),
+                media.media_type().into(),
+                media.age_restriction().into(),
+                (*media.source_id()).into(),
+            ]);
+        }
+
+        // `xmax` is zero only for inserted rows
+        let (sql, values) = query
+            .on_conflict(
+                OnConflict::columns([Alias::new("url"), Alias::new("genre")])
+                    .update_columns([
+                        Alias::new("media_type"),
+                        Alias::new("age_restriction"),
+                        Alias::new("source_id"),
+                    ])
+                    .to_owned(),
+            )
+            .returning(Query::returning().expr(Expr::cust("xmax = 0")))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|inserted: Vec<(bool,)>| {
+                inserted.into_iter().filter(|(inserted,)| *inserted).count() as u64
+            })
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn mark_checked<'s>(&mut self, media: MarkMediaChecked<'s>) -> Result<(), RepoError> {
+        let mut query = Query::update();
+
+        query
+            .table(Alias::new("media"))
+            .value(Alias::new("checked_at"), Expr::current_timestamp())
+            .and_where(Expr::col(Alias::new("id")).eq(*media.id()));
+
+        // Invalid media are unsupported by Telegram even if they are available, so only the validator changes their status
+        if let Some(status) = media.status() {
+            query.value(
+                Alias::new("status"),
+                Expr::case(
+                    Expr::col(Alias::new("status")).eq(MediaStatus::Invalid.as_str()),
+                    Expr::col(Alias::new("status")),
+                )
+                .finally(status),
+            );
+        }
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn set_hashes<'s>(&mut self, media: SetMediaHashes<'s>) -> Result<(), RepoError> {
+        let (sql, values) = Query::update()
+            .table(Alias::new("media"))
+            .values([
+                (Alias::new("content_hash"), media.content_hash().into()),
+                (
+                    Alias::new("perceptual_hash"),
+                    media.perceptual_hash().into(),
+                ),
+                (
+                    Alias::new("canonical_id"),
+                    media.canonical_id().copied().into(),
+                ),
+                (Alias::new("hashed_at"), Expr::current_timestamp().into()),
+            ])
+            .and_where(Expr::col(Alias::new("id")).eq(*media.id()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn set_file_info<'s>(&mut self, media: SetMediaFileInfo<'s>) -> Result<(), RepoError> {
+        let mut query = Query::update();
+
+        query
+            .table(Alias::new("media"))
+            .values([
+                (Alias::new("mime"), media.mime().into()),
+                (Alias::new("size"), media.size().into()),
+                (Alias::new("width"), media.width().into()),
+                (Alias::new("height"), media.height().into()),
+                (Alias::new("validated_at"), Expr::current_timestamp().into()),
+            ])
+            .and_where(Expr::col(Alias::new("id")).eq(*media.id()));
+
+        if let Some(media_type) = media.media_type() {
+            query.value(Alias::new("media_type"), media_type);
+        }
+        // Invalid media are unsupported by Telegram even if they are available, so only the validator changes their status
+        if let Some(status) = media.status() {
+            query.value(
+                Alias::new("status"),
+                Expr::case(
+                    Expr::col(Alias::new("status")).eq(MediaStatus::Invalid.as_str()),
+                    Expr::col(Alias::new("status")),
+                )
+                .finally(status),
+            );
+        }
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn delete_by_info<'s>(&mut self, media: DeleteMediaByInfo<'s>) -> Result<u64, RepoError> {
+        let media_ids_query = Query::select()
+            .column(Alias::new("id"))
+            .from(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("genre")).eq(media.genre()))
+            .and_where(Expr::col(Alias::new("media_type")).eq(media.media_type()))
+            .and_where(Expr::col(Alias::new("age_restriction")).eq(media.age_restriction()))
+            .to_owned();
+
+        // Views reference media with `NOT NULL` column, so they are deleted first
+        let (sql, values) = Query::delete()
+            .from_table(Alias::new("user_media_views"))
+            .and_where(Expr::col(Alias::new("media_id")).in_subquery(media_ids_query))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await?;
+
+        let (sql, values) = Query::delete()
+            .from_table(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("genre")).eq(media.genre()))
+            .and_where(Expr::col(Alias::new("media_type")).eq(media.media_type()))
+            .and_where(Expr::col(Alias::new("age_restriction")).eq(media.age_restriction()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(Into::into)
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -93,6 +331,7 @@ impl<Conn> MediaReaderImpl<Conn> {
 #[async_trait]
 impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_id<'s>(
         &mut self,
         media: GetMediaById<'s>,
@@ -103,9 +342,13 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
                 Alias::new("url"),
                 Alias::new("genre"),
                 Alias::new("media_type"),
-                Alias::new("is_sfw"),
+                Alias::new("age_restriction"),
                 Alias::new("source_id"),
                 Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
             ])
             .from(Alias::new("media"))
             .and_where(Expr::col(Alias::new("id")).eq(*media.id()))
@@ -124,6 +367,7 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             })
     }
 
+    #[instrument(skip_all)]
     async fn get_by_url<'s>(&mut self, media: GetMediaByUrl<'s>) -> Result<Vec<Media>, RepoError> {
         let (sql, values) = Query::select()
             .columns([
@@ -131,9 +375,13 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
                 Alias::new("url"),
                 Alias::new("genre"),
                 Alias::new("media_type"),
-                Alias::new("is_sfw"),
+                Alias::new("age_restriction"),
                 Alias::new("source_id"),
                 Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
             ])
             .from(Alias::new("media"))
             .and_where(Expr::col(Alias::new("url")).eq(media.url()))
@@ -146,6 +394,7 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_info<'s>(
         &mut self,
         media: GetMediaByInfo<'s>,
@@ -158,14 +407,20 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
                 Alias::new("url"),
                 Alias::new("genre"),
                 Alias::new("media_type"),
-                Alias::new("is_sfw"),
+                Alias::new("age_restriction"),
                 Alias::new("source_id"),
                 Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
             ])
             .from(Alias::new("media"))
             .and_where(Expr::col(Alias::new("genre")).eq(media.genre()))
             .and_where(Expr::col(Alias::new("media_type")).eq(media.media_type()))
-            .and_where(Expr::col(Alias::new("is_sfw")).eq(media.is_sfw()));
+            .and_where(Expr::col(Alias::new("age_restriction")).eq(media.age_restriction()))
+            .and_where(Expr::col(Alias::new("status")).eq(MediaStatus::Active.as_str()))
+            .and_where(Expr::col(Alias::new("canonical_id")).is_null());
 
         if let Some(offset) = media.offset() {
             query.offset(offset);
@@ -183,40 +438,104 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_info_unviewed_by_user<'s>(
         &mut self,
         media: GetMediaByInfoUnviewedByUser<'s>,
     ) -> Result<Vec<Media>, RepoError> {
+        // Media are selected in order of their random keys from a random pivot,
+        // and if there are not enough media after the pivot, the rest are selected from the start
+        let pivot = rand::random::<f64>();
+
+        let select_unviewed = |random_key_condition: SimpleExpr| {
+            let mut query = Query::select();
+
+            query
+                .columns([
+                    Alias::new("id"),
+                    Alias::new("url"),
+                    Alias::new("genre"),
+                    Alias::new("media_type"),
+                    Alias::new("age_restriction"),
+                    Alias::new("source_id"),
+                    Alias::new("created"),
+                    Alias::new("mime"),
+                    Alias::new("size"),
+                    Alias::new("width"),
+                    Alias::new("height"),
+                ])
+                .from(Alias::new("media"))
+                .and_where(Expr::col(Alias::new("genre")).eq(media.genre()))
+                .and_where(Expr::col(Alias::new("media_type")).eq(media.media_type()))
+                .and_where(Expr::col(Alias::new("age_restriction")).eq(media.age_restriction()))
+                .and_where(Expr::col(Alias::new("status")).eq(MediaStatus::Active.as_str()))
+                .and_where(Expr::col(Alias::new("canonical_id")).is_null())
+                .and_where(random_key_condition)
+                .and_where(
+                    Expr::exists(
+                        Query::select()
+                            .expr(Expr::val(1))
+                            .from(Alias::new("user_media_views"))
+                            .and_where(Expr::col(Alias::new("user_id")).eq(*media.user_id()))
+                            .and_where(
+                                Expr::col((Alias::new("user_media_views"), Alias::new("media_id")))
+                                    .equals((Alias::new("media"), Alias::new("id"))),
+                            )
+                            .to_owned(),
+                    )
+                    .not(),
+                )
+                // A view of a duplicate is a view of its canonical media
+                .and_where(
+                    Expr::exists(
+                        Query::select()
+                            .expr(Expr::val(1))
+                            .from(Alias::new("user_media_views"))
+                            .join_as(
+                                JoinType::InnerJoin,
+                                Alias::new("media"),
+                                Alias::new("duplicates"),
+                                Expr::col((Alias::new("duplicates"), Alias::new("id"))).equals((
+                                    Alias::new("user_media_views"),
+                                    Alias::new("media_id"),
+                                )),
+                            )
+                            .and_where(Expr::col(Alias::new("user_id")).eq(*media.user_id()))
+                            .and_where(
+                                Expr::col((Alias::new("duplicates"), Alias::new("canonical_id")))
+                                    .equals((Alias::new("media"), Alias::new("id"))),
+                            )
+                            .to_owned(),
+                    )
+                    .not(),
+                )
+                .order_by(Alias::new("random_key"), Order::Asc);
+
+            if let Some(limit) = media.limit() {
+                query.limit(limit + media.offset().unwrap_or_default());
+            }
+
+            query
+        };
+
         let mut query = Query::select();
 
         query
-            .columns([
-                (Alias::new("media"), Alias::new("id")),
-                (Alias::new("media"), Alias::new("url")),
-                (Alias::new("media"), Alias::new("genre")),
-                (Alias::new("media"), Alias::new("media_type")),
-                (Alias::new("media"), Alias::new("is_sfw")),
-                (Alias::new("media"), Alias::new("source_id")),
-                (Alias::new("media"), Alias::new("created")),
-            ])
-            .from(Alias::new("media"))
-            .join(
-                JoinType::LeftJoin,
-                Alias::new("user_media_views"),
-                Expr::col((Alias::new("user_media_views"), Alias::new("user_id")))
-                    .eq(*media.user_id())
-                    .and(
-                        Expr::col((Alias::new("user_media_views"), Alias::new("media_id")))
-                            .equals((Alias::new("media"), Alias::new("id"))),
-                    ),
-            )
-            .and_where(Expr::col((Alias::new("user_media_views"), Alias::new("id"))).is_null())
-            .and_where(Expr::col((Alias::new("media"), Alias::new("genre"))).eq(media.genre()))
-            .and_where(
-                Expr::col((Alias::new("media"), Alias::new("media_type"))).eq(media.media_type()),
+            .column(Asterisk)
+            .from_subquery(
+                select_unviewed(Expr::col(Alias::new("random_key")).gte(pivot)),
+                Alias::new("after_pivot"),
             )
-            .and_where(Expr::col((Alias::new("media"), Alias::new("is_sfw"))).eq(media.is_sfw()))
-            .order_by_expr(Func::random().into(), Order::Asc);
+            .union(
+                UnionType::All,
+                Query::select()
+                    .column(Asterisk)
+                    .from_subquery(
+                        select_unviewed(Expr::col(Alias::new("random_key")).lt(pivot)),
+                        Alias::new("before_pivot"),
+                    )
+                    .to_owned(),
+            );
 
         if let Some(offset) = media.offset() {
             query.offset(offset);
@@ -234,6 +553,190 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
+    async fn get_page<'s>(&mut self, media: GetMediaPage<'s>) -> Result<Vec<Media>, RepoError> {
+        let mut query = Query::select();
+
+        query
+            .columns([
+                Alias::new("id"),
+                Alias::new("url"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("source_id"),
+                Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
+            ])
+            .from(Alias::new("media"))
+            .order_by(Alias::new("id"), Order::Asc)
+            .limit(media.limit());
+
+        if let Some(after_id) = media.after_id() {
+            query.and_where(Expr::col(Alias::new("id")).gt(*after_id));
+        }
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|media_models: Vec<MediaModel>| media_models.into_iter().map(Into::into).collect())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_to_check(&mut self, media: GetMediaToCheck) -> Result<Vec<Media>, RepoError> {
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("url"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("source_id"),
+                Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
+            ])
+            .from(Alias::new("media"))
+            .cond_where(
+                Cond::any()
+                    .add(Expr::col(Alias::new("checked_at")).is_null())
+                    .add(Expr::col(Alias::new("checked_at")).lt(media.checked_before())),
+            )
+            .and_where(Expr::col(Alias::new("status")).ne(MediaStatus::Invalid.as_str()))
+            .order_by_with_nulls(Alias::new("checked_at"), Order::Asc, NullOrdering::First)
+            .limit(media.limit())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|media_models: Vec<MediaModel>| media_models.into_iter().map(Into::into).collect())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_to_hash(&mut self, media: GetMediaToHash) -> Result<Vec<Media>, RepoError> {
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("url"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("source_id"),
+                Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
+            ])
+            .from(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("hashed_at")).is_null())
+            .and_where(Expr::col(Alias::new("status")).eq(MediaStatus::Active.as_str()))
+            .order_by(Alias::new("created"), Order::Asc)
+            .limit(media.limit())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|media_models: Vec<MediaModel>| media_models.into_iter().map(Into::into).collect())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_to_validate(
+        &mut self,
+        media: GetMediaToValidate,
+    ) -> Result<Vec<Media>, RepoError> {
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("url"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("source_id"),
+                Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
+            ])
+            .from(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("validated_at")).is_null())
+            .and_where(Expr::col(Alias::new("status")).eq(MediaStatus::Active.as_str()))
+            .order_by(Alias::new("created"), Order::Asc)
+            .limit(media.limit())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|media_models: Vec<MediaModel>| media_models.into_iter().map(Into::into).collect())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_canonical<'s>(
+        &mut self,
+        media: GetCanonicalMedia<'s>,
+    ) -> Result<Option<Media>, RepoError> {
+        let mut duplicate_condition =
+            Cond::any().add(Expr::col(Alias::new("content_hash")).eq(media.content_hash()));
+
+        if let Some(perceptual_hash) = media.perceptual_hash() {
+            duplicate_condition = duplicate_condition.add(Expr::cust_with_values(
+                "bit_count((perceptual_hash # $1)::bit(64)) <= $2",
+                [perceptual_hash, i64::from(media.max_distance())],
+            ));
+        }
+
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("url"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("source_id"),
+                Alias::new("created"),
+                Alias::new("mime"),
+                Alias::new("size"),
+                Alias::new("width"),
+                Alias::new("height"),
+            ])
+            .from(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("id")).ne(*media.id()))
+            .and_where(Expr::col(Alias::new("genre")).eq(media.genre()))
+            .and_where(Expr::col(Alias::new("media_type")).eq(media.media_type()))
+            .and_where(Expr::col(Alias::new("canonical_id")).is_null())
+            .and_where(Expr::col(Alias::new("content_hash")).is_not_null())
+            .cond_where(duplicate_condition)
+            .order_by_expr(
+                Expr::col(Alias::new("content_hash")).eq(media.content_hash()),
+                Order::Desc,
+            )
+            .order_by(Alias::new("created"), Order::Asc)
+            .limit(1)
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_optional(&mut *self.conn)
+            .await
+            .map(|media_model: Option<MediaModel>| media_model.map(Into::into))
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
     async fn get_media_stats(&mut self) -> Result<MediaStats, RepoError> {
         let mut query = Query::select();
 
@@ -243,26 +746,53 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
                 Alias::new("total"),
             )
             .expr_as(
-                Func::count(Expr::case(Expr::col(Alias::new("media_type")).eq("gif"), 1)),
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("media_type")).eq(MediaType::Gif.as_str()),
+                    1,
+                )),
                 Alias::new("gif"),
             )
             .expr_as(
-                Func::count(Expr::case(Expr::col(Alias::new("media_type")).eq("img"), 1)),
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("media_type")).eq(MediaType::Image.as_str()),
+                    1,
+                )),
                 Alias::new("image"),
             )
             .expr_as(
                 Func::count(Expr::case(
-                    Expr::col(Alias::new("media_type")).eq("unknown"),
+                    Expr::col(Alias::new("media_type")).eq(MediaType::Video.as_str()),
+                    1,
+                )),
+                Alias::new("video"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("media_type")).eq(MediaType::Unknown.as_str()),
                     1,
                 )),
                 Alias::new("unknown"),
             )
             .expr_as(
-                Func::count(Expr::case(Expr::col(Alias::new("is_sfw")).eq(true), 1)),
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("age_restriction")).eq(AgeRestriction::Sfw.as_str()),
+                    1,
+                )),
                 Alias::new("sfw"),
             )
             .expr_as(
-                Func::count(Expr::case(Expr::col(Alias::new("is_sfw")).eq(false), 1)),
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("age_restriction"))
+                        .eq(AgeRestriction::Questionable.as_str()),
+                    1,
+                )),
+                Alias::new("questionable"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("age_restriction")).eq(AgeRestriction::Nsfw.as_str()),
+                    1,
+                )),
                 Alias::new("nsfw"),
             )
             .from(Alias::new("media"));
@@ -276,6 +806,7 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_genre_stats(&mut self) -> Result<GenresStats, RepoError> {
         let mut query = Query::select();
 
@@ -287,13 +818,15 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .columns([
                 Alias::new("genre"),
                 Alias::new("media_type"),
-                Alias::new("is_sfw"),
+                Alias::new("age_restriction"),
             ])
             .from(Alias::new("media"))
+            .and_where(Expr::col(Alias::new("status")).eq(MediaStatus::Active.as_str()))
+            .and_where(Expr::col(Alias::new("canonical_id")).is_null())
             .add_group_by([
                 Expr::col(Alias::new("genre")).into(),
                 Expr::col(Alias::new("media_type")).into(),
-                Expr::col(Alias::new("is_sfw")).into(),
+                Expr::col(Alias::new("age_restriction")).into(),
             ]);
 
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
@@ -311,3 +844,260 @@ impl<'a> MediaReader for MediaReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MediaReaderImpl, MediaRepoImpl};
+
+    use crate::{
+        application::media::{
+            dto::{CreateMedia, GetMediaByInfoUnviewedByUser, GetMediaToCheck, MarkMediaChecked},
+            traits::{MediaReader as _, MediaRepo as _},
+        },
+        domain::media::value_objects::MediaStatus,
+        infrastructure::database::migrations,
+    };
+
+    use sqlx::{Connection as _, PgConnection};
+    use std::{
+        env,
+        time::{Duration, Instant},
+    };
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    const MEDIA_COUNT: i64 = 1_000_000;
+    const GENRE_COUNT: i64 = 10;
+    const USER_COUNT: i64 = 100;
+    /// Every user viewed 1 of `VIEW_RATIO` media, so there are `MEDIA_COUNT * USER_COUNT / VIEW_RATIO` views
+    const VIEW_RATIO: i64 = 10;
+    const QUERY_COUNT: usize = 1000;
+
+    /// Upserts the same media twice, the second upsert must update media with and without genre instead of inserting them.
+    /// Data is created in a transaction, which is rolled back in the end.
+    /// Run it with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored test_upsert_many_reimport`
+    #[tokio::test]
+    #[ignore = "requires `TEST_DATABASE_URL`"]
+    async fn test_upsert_many_reimport() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("`TEST_DATABASE_URL` isn't set");
+
+        let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+        migrations::run(&pool).await.unwrap();
+
+        let mut conn = PgConnection::connect(&database_url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let source_id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO sources (id, name, url) VALUES ($1, 'reimport', 'reimport')")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        for expected_created_count in [2, 0] {
+            let ids = [Uuid::new_v4(), Uuid::new_v4()];
+            let media = [
+                CreateMedia::new(&ids[0], "reimport/1", None, "img", "sfw", &source_id),
+                CreateMedia::new(
+                    &ids[1],
+                    "reimport/2",
+                    Some("genre"),
+                    "img",
+                    "sfw",
+                    &source_id,
+                ),
+            ];
+
+            let created_count = MediaRepoImpl::new(&mut *tx)
+                .upsert_many(&media)
+                .await
+                .unwrap();
+
+            let media_count: i64 =
+                sqlx::query_scalar("SELECT count(*) FROM media WHERE source_id = $1")
+                    .bind(source_id)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .unwrap();
+
+            assert_eq!(created_count, expected_created_count);
+            assert_eq!(media_count, 2);
+        }
+
+        tx.rollback().await.unwrap();
+    }
+
+    /// Invalid media aren't checked by the verifier and their status isn't changed to active by a successful check.
+    /// Data is created in a transaction, which is rolled back in the end.
+    /// Run it with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored test_check_invalid_media`
+    #[tokio::test]
+    #[ignore = "requires `TEST_DATABASE_URL`"]
+    async fn test_check_invalid_media() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("`TEST_DATABASE_URL` isn't set");
+
+        let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+        migrations::run(&pool).await.unwrap();
+
+        let mut conn = PgConnection::connect(&database_url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let source_id = Uuid::new_v4();
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        sqlx::query("INSERT INTO sources (id, name, url) VALUES ($1, 'check', 'check')")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        // Other media are checked recently, so only these ones can be returned
+        sqlx::query("UPDATE media SET checked_at = now()")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO media (id, url, genre, media_type, age_restriction, source_id, status) \
+            VALUES ($1, 'check/1', 'check', 'img', 'sfw', $3, 'invalid'), \
+            ($2, 'check/2', 'check', 'img', 'sfw', $3, 'broken')",
+        )
+        .bind(ids[0])
+        .bind(ids[1])
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let media = MediaReaderImpl::new(&mut *tx)
+            .get_to_check(GetMediaToCheck::new(OffsetDateTime::now_utc(), 10))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            media.iter().map(|media| media.id).collect::<Vec<_>>(),
+            [ids[1]]
+        );
+
+        for id in &ids {
+            MediaRepoImpl::new(&mut *tx)
+                .mark_checked(MarkMediaChecked::new(
+                    id,
+                    Some(MediaStatus::Active.as_str()),
+                ))
+                .await
+                .unwrap();
+        }
+
+        let statuses: Vec<String> =
+            sqlx::query_scalar("SELECT status FROM media WHERE source_id = $1 ORDER BY url")
+                .bind(source_id)
+                .fetch_all(&mut *tx)
+                .await
+                .unwrap();
+
+        assert_eq!(statuses, ["invalid", "active"]);
+
+        tx.rollback().await.unwrap();
+    }
+
+    fn percentile(latencies: &[Duration], percentile: usize) -> Duration {
+        latencies[(latencies.len() - 1) * percentile / 100]
+    }
+
+    /// Measures latency of random media selection with 1M media and 10M views.
+    /// Data is created in a transaction, which is rolled back in the end.
+    /// Run it with `BENCHMARK_DATABASE_URL=postgres://... cargo test --release -- --ignored --nocapture bench_get_by_info_unviewed_by_user`
+    #[tokio::test]
+    #[ignore = "requires `BENCHMARK_DATABASE_URL` and takes about 10 minutes"]
+    async fn bench_get_by_info_unviewed_by_user() {
+        let database_url =
+            env::var("BENCHMARK_DATABASE_URL").expect("`BENCHMARK_DATABASE_URL` isn't set");
+
+        let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+        migrations::run(&pool).await.unwrap();
+
+        let mut conn = PgConnection::connect(&database_url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let source_id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO sources (id, name, url) VALUES ($1, 'benchmark', 'benchmark')")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO media (url, genre, media_type, age_restriction, source_id) \
+            SELECT 'benchmark/' || i, 'benchmark' || (i % $1), 'img', 'sfw', $2 \
+            FROM generate_series(1, $3) AS i",
+        )
+        .bind(GENRE_COUNT)
+        .bind(source_id)
+        .bind(MEDIA_COUNT)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO users (tg_id) SELECT -i FROM generate_series(1, $1) AS i")
+            .bind(USER_COUNT)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO user_media_views (user_id, media_id) \
+            SELECT users.id, media.id FROM users CROSS JOIN media \
+            WHERE users.tg_id < 0 AND media.source_id = $1 \
+            AND abs(hashtext(media.url || users.tg_id)) % $2 = 0",
+        )
+        .bind(source_id)
+        .bind(VIEW_RATIO)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query("ANALYZE media, user_media_views")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let user_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM users WHERE tg_id < 0")
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+
+        let mut latencies = Vec::with_capacity(QUERY_COUNT);
+
+        for (index, genre_index) in (0..QUERY_COUNT).zip((0..GENRE_COUNT).cycle()) {
+            let user_id = &user_ids[index % user_ids.len()];
+            let genre = format!("benchmark{genre_index}");
+
+            let start = Instant::now();
+
+            let media = MediaReaderImpl::new(&mut *tx)
+                .get_by_info_unviewed_by_user(GetMediaByInfoUnviewedByUser::new(
+                    user_id,
+                    Some(&genre),
+                    "img",
+                    "sfw",
+                    None,
+                    Some(1),
+                ))
+                .await
+                .unwrap();
+
+            latencies.push(start.elapsed());
+
+            assert_eq!(media.len(), 1);
+        }
+
+        latencies.sort_unstable();
+
+        println!(
+            "get_by_info_unviewed_by_user with {MEDIA_COUNT} media and {} views: p50 {:?}, p95 {:?}, p99 {:?}, max {:?}",
+            MEDIA_COUNT * USER_COUNT / VIEW_RATIO,
+            percentile(&latencies, 50),
+            percentile(&latencies, 95),
+            percentile(&latencies, 99),
+            latencies[latencies.len() - 1],
+        );
+
+        tx.rollback().await.unwrap();
+    }
+}
@@ -2,21 +2,29 @@ use crate::{
     application::{
         common::exceptions::{RepoError, RepoKind},
         source::{
-            dto::{CreateSource, GetSourceById, GetSourceByName, GetSourceByNameAndUrl},
+            dto::{
+                CreateSource, GetSourceById, GetSourceByName, GetSourceByNameAndUrl,
+                RecordSourceFetchRun, UpdateSourceEnabled, UpdateSourceGenrePaused,
+                UpdateSourcePollingInterval,
+            },
             exceptions::{
                 SourceIdNotExist, SourceNameAndUrlAlreadyExists, SourceNameAndUrlNotExist,
             },
             traits::{SourceReader, SourceRepo},
         },
     },
-    domain::source::entities::Source,
-    infrastructure::database::models::Source as SourceModel,
+    domain::source::entities::{Source, SourceFetchStats, SourcePausedGenre},
+    infrastructure::database::models::{
+        Source as SourceModel, SourceFetchStats as SourceFetchStatsModel,
+        SourcePausedGenre as SourcePausedGenreModel,
+    },
 };
 
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, PostgresQueryBuilder, Query};
+use sea_query::{Alias, Expr, JoinType, OnConflict, Order, PostgresQueryBuilder, Query};
 use sea_query_binder::SqlxBinder as _;
 use sqlx::PgConnection;
+use tracing::instrument;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct SourceRepoImpl<Conn> {
@@ -31,6 +39,7 @@ impl<Conn> SourceRepoImpl<Conn> {
 
 #[async_trait]
 impl<'a> SourceRepo for SourceRepoImpl<&'a mut PgConnection> {
+    #[instrument(skip_all)]
     async fn create<'s>(
         &mut self,
         source: CreateSource<'s>,
@@ -64,6 +73,213 @@ impl<'a> SourceRepo for SourceRepoImpl<&'a mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    #[instrument(skip_all)]
+    async fn record_fetch_run<'s>(
+        &mut self,
+        fetch_run: RecordSourceFetchRun<'s>,
+    ) -> Result<(), RepoError> {
+        let is_success = fetch_run.error().is_none();
+
+        let (sql, values) = Query::insert()
+            .into_table(Alias::new("source_fetch_runs"))
+            .columns([
+                Alias::new("source_id"),
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+                Alias::new("success_count"),
+                Alias::new("failure_count"),
+                Alias::new("new_media_count"),
+                Alias::new("duplicate_media_count"),
+                Alias::new("total_latency_ms"),
+                Alias::new("last_latency_ms"),
+                Alias::new("last_error"),
+                Alias::new("last_error_at"),
+                Alias::new("last_success_at"),
+            ])
+            .values_panic([
+                (*fetch_run.source_id()).into(),
+                fetch_run.genre().into(),
+                fetch_run.media_type().into(),
+                fetch_run.age_restriction().into(),
+                i64::from(is_success).into(),
+                i64::from(!is_success).into(),
+                fetch_run.new_media_count().into(),
+                fetch_run.duplicate_media_count().into(),
+                fetch_run.latency_ms().into(),
+                fetch_run.latency_ms().into(),
+                fetch_run.error().map(ToOwned::to_owned).into(),
+                if is_success {
+                    Expr::cust("NULL")
+                } else {
+                    Expr::current_timestamp().into()
+                },
+                if is_success {
+                    Expr::current_timestamp().into()
+                } else {
+                    Expr::cust("NULL")
+                },
+            ])
+            // Counters are accumulated, so there is only one row for each source and genre
+            .on_conflict(
+                OnConflict::columns([
+                    Alias::new("source_id"),
+                    Alias::new("genre"),
+                    Alias::new("media_type"),
+                    Alias::new("age_restriction"),
+                ])
+                .values([
+                    (
+                        Alias::new("success_count"),
+                        Expr::cust("source_fetch_runs.success_count + EXCLUDED.success_count"),
+                    ),
+                    (
+                        Alias::new("failure_count"),
+                        Expr::cust("source_fetch_runs.failure_count + EXCLUDED.failure_count"),
+                    ),
+                    (
+                        Alias::new("new_media_count"),
+                        Expr::cust("source_fetch_runs.new_media_count + EXCLUDED.new_media_count"),
+                    ),
+                    (
+                        Alias::new("duplicate_media_count"),
+                        Expr::cust(
+                            "source_fetch_runs.duplicate_media_count + EXCLUDED.duplicate_media_count",
+                        ),
+                    ),
+                    (
+                        Alias::new("total_latency_ms"),
+                        Expr::cust("source_fetch_runs.total_latency_ms + EXCLUDED.total_latency_ms"),
+                    ),
+                    (
+                        Alias::new("last_latency_ms"),
+                        Expr::cust("EXCLUDED.last_latency_ms"),
+                    ),
+                    (
+                        Alias::new("last_error"),
+                        Expr::cust("COALESCE(EXCLUDED.last_error, source_fetch_runs.last_error)"),
+                    ),
+                    (
+                        Alias::new("last_error_at"),
+                        Expr::cust(
+                            "COALESCE(EXCLUDED.last_error_at, source_fetch_runs.last_error_at)",
+                        ),
+                    ),
+                    (
+                        Alias::new("last_success_at"),
+                        Expr::cust(
+                            "COALESCE(EXCLUDED.last_success_at, source_fetch_runs.last_success_at)",
+                        ),
+                    ),
+                    (Alias::new("updated"), Expr::current_timestamp().into()),
+                ])
+                .to_owned(),
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn update_enabled<'s>(
+        &mut self,
+        source: UpdateSourceEnabled<'s>,
+    ) -> Result<u64, RepoError> {
+        let (sql, values) = Query::update()
+            .table(Alias::new("sources"))
+            .values([(Alias::new("enabled"), source.enabled().into())])
+            .and_where(Expr::col(Alias::new("name")).eq(source.name()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn update_polling_interval<'s>(
+        &mut self,
+        source: UpdateSourcePollingInterval<'s>,
+    ) -> Result<u64, RepoError> {
+        let (sql, values) = Query::update()
+            .table(Alias::new("sources"))
+            .values([(
+                Alias::new("polling_interval_ms"),
+                source.polling_interval_ms().into(),
+            )])
+            .and_where(Expr::col(Alias::new("name")).eq(source.name()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn update_genre_paused<'s>(
+        &mut self,
+        source: UpdateSourceGenrePaused<'s>,
+    ) -> Result<u64, RepoError> {
+        let source_ids = Query::select()
+            .column(Alias::new("id"))
+            .from(Alias::new("sources"))
+            .and_where(Expr::col(Alias::new("name")).eq(source.name()))
+            .to_owned();
+
+        let (sql, values) = if source.paused() {
+            Query::insert()
+                .into_table(Alias::new("source_paused_genres"))
+                .columns([
+                    Alias::new("source_id"),
+                    Alias::new("genre"),
+                    Alias::new("media_type"),
+                    Alias::new("age_restriction"),
+                ])
+                .select_from(
+                    source_ids
+                        .clone()
+                        .expr(Expr::val(source.genre()))
+                        .expr(Expr::val(source.media_type()))
+                        .expr(Expr::val(source.age_restriction()))
+                        .to_owned(),
+                )
+                .expect("Count of columns and values should be the same")
+                .on_conflict(
+                    OnConflict::columns([
+                        Alias::new("source_id"),
+                        Alias::new("genre"),
+                        Alias::new("media_type"),
+                        Alias::new("age_restriction"),
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .build_sqlx(PostgresQueryBuilder)
+        } else {
+            Query::delete()
+                .from_table(Alias::new("source_paused_genres"))
+                .and_where(Expr::col(Alias::new("source_id")).in_subquery(source_ids))
+                .and_where(Expr::col(Alias::new("genre")).eq(source.genre()))
+                .and_where(Expr::col(Alias::new("media_type")).eq(source.media_type()))
+                .and_where(Expr::col(Alias::new("age_restriction")).eq(source.age_restriction()))
+                .build_sqlx(PostgresQueryBuilder)
+        };
+
+        sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(Into::into)
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -80,6 +296,7 @@ impl<Conn> SourceReaderImpl<Conn> {
 #[async_trait]
 impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_id<'s>(
         &mut self,
         source: GetSourceById<'s>,
@@ -89,6 +306,8 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("name"),
                 Alias::new("url"),
+                Alias::new("enabled"),
+                Alias::new("polling_interval_ms"),
                 Alias::new("created"),
             ])
             .from(Alias::new("sources"))
@@ -108,6 +327,31 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
             })
     }
 
+    #[instrument(skip_all)]
+    async fn get_all(&mut self) -> Result<Vec<Source>, RepoError> {
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("name"),
+                Alias::new("url"),
+                Alias::new("enabled"),
+                Alias::new("polling_interval_ms"),
+                Alias::new("created"),
+            ])
+            .from(Alias::new("sources"))
+            .order_by(Alias::new("created"), Order::Asc)
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|source_models: Vec<SourceModel>| {
+                source_models.into_iter().map(Into::into).collect()
+            })
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
     async fn get_by_name<'s>(
         &mut self,
         source: GetSourceByName<'s>,
@@ -117,6 +361,8 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("name"),
                 Alias::new("url"),
+                Alias::new("enabled"),
+                Alias::new("polling_interval_ms"),
                 Alias::new("created"),
             ])
             .from(Alias::new("sources"))
@@ -133,6 +379,7 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
     }
 
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_name_and_url<'s>(
         &mut self,
         source: GetSourceByNameAndUrl<'s>,
@@ -142,6 +389,8 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("name"),
                 Alias::new("url"),
+                Alias::new("enabled"),
+                Alias::new("polling_interval_ms"),
                 Alias::new("created"),
             ])
             .from(Alias::new("sources"))
@@ -165,4 +414,199 @@ impl<'a> SourceReader for SourceReaderImpl<&'a mut PgConnection> {
                 }
             })
     }
+
+    #[instrument(skip_all)]
+    async fn get_fetch_stats(&mut self) -> Result<Vec<SourceFetchStats>, RepoError> {
+        let (sql, values) = Query::select()
+            .expr_as(
+                Expr::col((Alias::new("sources"), Alias::new("name"))),
+                Alias::new("source_name"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("sources"), Alias::new("enabled"))),
+                Alias::new("source_enabled"),
+            )
+            .columns([
+                (Alias::new("source_fetch_runs"), Alias::new("genre")),
+                (Alias::new("source_fetch_runs"), Alias::new("media_type")),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("age_restriction"),
+                ),
+                (Alias::new("source_fetch_runs"), Alias::new("success_count")),
+                (Alias::new("source_fetch_runs"), Alias::new("failure_count")),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("new_media_count"),
+                ),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("duplicate_media_count"),
+                ),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("total_latency_ms"),
+                ),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("last_latency_ms"),
+                ),
+                (Alias::new("source_fetch_runs"), Alias::new("last_error")),
+                (Alias::new("source_fetch_runs"), Alias::new("last_error_at")),
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("last_success_at"),
+                ),
+            ])
+            .from(Alias::new("source_fetch_runs"))
+            .join(
+                JoinType::InnerJoin,
+                Alias::new("sources"),
+                Expr::col((Alias::new("sources"), Alias::new("id")))
+                    .equals((Alias::new("source_fetch_runs"), Alias::new("source_id"))),
+            )
+            .order_by((Alias::new("sources"), Alias::new("name")), Order::Asc)
+            .order_by(
+                (Alias::new("source_fetch_runs"), Alias::new("media_type")),
+                Order::Asc,
+            )
+            .order_by(
+                (Alias::new("source_fetch_runs"), Alias::new("genre")),
+                Order::Asc,
+            )
+            .order_by(
+                (
+                    Alias::new("source_fetch_runs"),
+                    Alias::new("age_restriction"),
+                ),
+                Order::Desc,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|stats_models: Vec<SourceFetchStatsModel>| {
+                stats_models.into_iter().map(Into::into).collect()
+            })
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_paused_genres(&mut self) -> Result<Vec<SourcePausedGenre>, RepoError> {
+        let (sql, values) = Query::select()
+            .expr_as(
+                Expr::col((Alias::new("sources"), Alias::new("name"))),
+                Alias::new("source_name"),
+            )
+            .columns([
+                (Alias::new("source_paused_genres"), Alias::new("genre")),
+                (Alias::new("source_paused_genres"), Alias::new("media_type")),
+                (
+                    Alias::new("source_paused_genres"),
+                    Alias::new("age_restriction"),
+                ),
+            ])
+            .distinct()
+            .from(Alias::new("source_paused_genres"))
+            .join(
+                JoinType::InnerJoin,
+                Alias::new("sources"),
+                Expr::col((Alias::new("sources"), Alias::new("id")))
+                    .equals((Alias::new("source_paused_genres"), Alias::new("source_id"))),
+            )
+            .order_by(Alias::new("source_name"), Order::Asc)
+            .order_by(Alias::new("media_type"), Order::Asc)
+            .order_by(Alias::new("genre"), Order::Asc)
+            .order_by(Alias::new("age_restriction"), Order::Desc)
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|paused_genre_models: Vec<SourcePausedGenreModel>| {
+                paused_genre_models.into_iter().map(Into::into).collect()
+            })
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceReaderImpl, SourceRepoImpl};
+
+    use crate::{
+        application::source::{
+            dto::UpdateSourceGenrePaused,
+            traits::{SourceReader as _, SourceRepo as _},
+        },
+        infrastructure::database::migrations,
+    };
+
+    use sqlx::{Connection as _, PgConnection};
+    use std::env;
+    use uuid::Uuid;
+
+    /// Pauses a genre of sources with the same name twice and resumes it.
+    /// Data is created in a transaction, which is rolled back in the end.
+    /// Run it with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored test_update_genre_paused`
+    #[tokio::test]
+    #[ignore = "requires `TEST_DATABASE_URL`"]
+    async fn test_update_genre_paused() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("`TEST_DATABASE_URL` isn't set");
+
+        let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+        migrations::run(&pool).await.unwrap();
+
+        let mut conn = PgConnection::connect(&database_url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        sqlx::query("DELETE FROM source_paused_genres")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO sources (id, name, url) VALUES ($1, 'paused', 'paused/1'), ($2, 'paused', 'paused/2')",
+        )
+        .bind(Uuid::new_v4())
+        .bind(Uuid::new_v4())
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        for _ in 0..2 {
+            SourceRepoImpl::new(&mut *tx)
+                .update_genre_paused(UpdateSourceGenrePaused::new(
+                    "paused", "neko", "img", "sfw", true,
+                ))
+                .await
+                .unwrap();
+        }
+
+        let paused_genres = SourceReaderImpl::new(&mut *tx)
+            .get_paused_genres()
+            .await
+            .unwrap();
+
+        // Genres of sources with the same name are returned once
+        assert_eq!(paused_genres.len(), 1);
+        assert_eq!(paused_genres[0].source_name, "paused");
+        assert_eq!(paused_genres[0].genre, "neko");
+
+        let resumed_count = SourceRepoImpl::new(&mut *tx)
+            .update_genre_paused(UpdateSourceGenrePaused::new(
+                "paused", "neko", "img", "sfw", false,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(resumed_count, 2);
+        assert!(SourceReaderImpl::new(&mut *tx)
+            .get_paused_genres()
+            .await
+            .unwrap()
+            .is_empty());
+
+        tx.rollback().await.unwrap();
+    }
 }
@@ -3,20 +3,25 @@ use crate::{
         common::exceptions::{RepoError, RepoKind},
         user::{
             dto::{
-                CreateUser, GetUserById, GetUserByTgId, UpdateUserLanguageCode, UpdateUserShowNsfw,
+                CreateUser, DeleteUser, GetUserById, GetUserByTgId, UpdateUserLanguageCode,
+                UpdateUserMaxAgeRestriction,
             },
             exceptions::{UserIdNotExist, UserTgIdAlreadyExists, UserTgIdNotExist},
             traits::{UserReader, UserRepo},
         },
     },
-    domain::user::entities::User,
-    infrastructure::database::models::User as UserModel,
+    domain::{
+        media_parser::value_objects::AgeRestriction,
+        user::entities::{User, UserStats},
+    },
+    infrastructure::database::models::{User as UserModel, UserStats as UserStatsModel},
 };
 
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, PostgresQueryBuilder, Query};
+use sea_query::{Alias, Expr, Func, PostgresQueryBuilder, Query};
 use sea_query_binder::SqlxBinder as _;
 use sqlx::PgConnection;
+use tracing::instrument;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct UserRepoImpl<Conn> {
@@ -31,6 +36,7 @@ impl<Conn> UserRepoImpl<Conn> {
 
 #[async_trait]
 impl<'a> UserRepo for UserRepoImpl<&'a mut PgConnection> {
+    #[instrument(skip_all)]
     async fn create<'s>(
         &mut self,
         user: CreateUser<'s>,
@@ -41,13 +47,13 @@ impl<'a> UserRepo for UserRepoImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("tg_id"),
                 Alias::new("language_code"),
-                Alias::new("show_nsfw"),
+                Alias::new("max_age_restriction"),
             ])
             .values_panic([
                 (*user.id()).into(),
                 user.tg_id().into(),
                 user.language_code().into(),
-                user.show_nsfw().into(),
+                user.max_age_restriction().into(),
             ])
             .build_sqlx(PostgresQueryBuilder);
 
@@ -70,6 +76,7 @@ impl<'a> UserRepo for UserRepoImpl<&'a mut PgConnection> {
             })
     }
 
+    #[instrument(skip_all)]
     async fn update_language_code<'s>(
         &mut self,
         user: UpdateUserLanguageCode<'s>,
@@ -87,13 +94,17 @@ impl<'a> UserRepo for UserRepoImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
-    async fn update_show_nsfw<'s>(
+    #[instrument(skip_all)]
+    async fn update_max_age_restriction<'s>(
         &mut self,
-        user: UpdateUserShowNsfw<'s>,
+        user: UpdateUserMaxAgeRestriction<'s>,
     ) -> Result<(), RepoError> {
         let (sql, values) = Query::update()
             .table(Alias::new("users"))
-            .values([(Alias::new("show_nsfw"), user.show_nsfw().into())])
+            .values([(
+                Alias::new("max_age_restriction"),
+                user.max_age_restriction().into(),
+            )])
             .and_where(Expr::col(Alias::new("id")).eq(*user.id()))
             .build_sqlx(PostgresQueryBuilder);
 
@@ -103,6 +114,27 @@ impl<'a> UserRepo for UserRepoImpl<&'a mut PgConnection> {
             .map(|_| ())
             .map_err(Into::into)
     }
+
+    #[instrument(skip_all)]
+    async fn delete<'s>(&mut self, user: DeleteUser<'s>) -> Result<(), RepoKind<UserIdNotExist>> {
+        let (sql, values) = Query::delete()
+            .from_table(Alias::new("users"))
+            .and_where(Expr::col(Alias::new("id")).eq(*user.id()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserIdNotExist::new(
+                *user.id(),
+                "No user found to delete",
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -119,6 +151,7 @@ impl<Conn> UserReaderImpl<Conn> {
 #[async_trait]
 impl<'a> UserReader for UserReaderImpl<&'a mut PgConnection> {
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_id<'s>(
         &mut self,
         user: GetUserById<'s>,
@@ -128,7 +161,7 @@ impl<'a> UserReader for UserReaderImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("tg_id"),
                 Alias::new("language_code"),
-                Alias::new("show_nsfw"),
+                Alias::new("max_age_restriction"),
                 Alias::new("created"),
             ])
             .from(Alias::new("users"))
@@ -148,6 +181,7 @@ impl<'a> UserReader for UserReaderImpl<&'a mut PgConnection> {
     }
 
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_tg_id(
         &mut self,
         user: GetUserByTgId,
@@ -157,7 +191,7 @@ impl<'a> UserReader for UserReaderImpl<&'a mut PgConnection> {
                 Alias::new("id"),
                 Alias::new("tg_id"),
                 Alias::new("language_code"),
-                Alias::new("show_nsfw"),
+                Alias::new("max_age_restriction"),
                 Alias::new("created"),
             ])
             .from(Alias::new("users"))
@@ -178,4 +212,59 @@ impl<'a> UserReader for UserReaderImpl<&'a mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    #[instrument(skip_all)]
+    async fn get_stats(&mut self) -> Result<UserStats, RepoError> {
+        let (sql, values) = Query::select()
+            .expr_as(
+                Func::count(Expr::col(Alias::new("id"))),
+                Alias::new("total"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("max_age_restriction"))
+                        .eq(AgeRestriction::Questionable.as_str()),
+                    1,
+                )),
+                Alias::new("show_questionable"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("max_age_restriction")).eq(AgeRestriction::Nsfw.as_str()),
+                    1,
+                )),
+                Alias::new("show_nsfw"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("created"))
+                        .gte(Expr::cust("CURRENT_TIMESTAMP - INTERVAL '1 day'")),
+                    1,
+                )),
+                Alias::new("created_last_day"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col(Alias::new("created"))
+                        .gte(Expr::cust("CURRENT_TIMESTAMP - INTERVAL '7 days'")),
+                    1,
+                )),
+                Alias::new("created_last_week"),
+            )
+            .expr_as(
+                Expr::cust(
+                    "(SELECT COUNT(DISTINCT user_id) FROM user_media_views \
+                    WHERE created >= CURRENT_TIMESTAMP - INTERVAL '7 days')",
+                ),
+                Alias::new("active_last_week"),
+            )
+            .from(Alias::new("users"))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_one(&mut *self.conn)
+            .await
+            .map(|stats_model: UserStatsModel| stats_model.into())
+            .map_err(Into::into)
+    }
 }
@@ -3,23 +3,35 @@ use crate::{
         common::exceptions::{RepoError, RepoKind},
         user_media_view::{
             dto::{
-                CreateUserMediaView, GetUserMediaViewById, GetUserMediaViewByMediaAgeRestriction,
-                GetUserMediaViewByMediaGenre, GetUserMediaViewByMediaId,
-                GetUserMediaViewByMediaSourceId, GetUserMediaViewByMediaType,
-                GetUserMediaViewByUserId, GetUserMediaViewByUserTgId,
+                CreateUserMediaView, DeleteUserMediaView, GetUserMediaViewById,
+                GetUserMediaViewByMediaAgeRestriction, GetUserMediaViewByMediaGenre,
+                GetUserMediaViewByMediaId, GetUserMediaViewByMediaSourceId,
+                GetUserMediaViewByMediaType, GetUserMediaViewByUserId, GetUserMediaViewByUserTgId,
+                GetUserMediaViewGenreStatsByUserId, GetUserMediaViewGenreViewStats,
+                GetUserMediaViewHistoryByUserId,
             },
             exceptions::{UserMediaViewIdNotExist, UserMediaViewUserIdAndMediaIdAlreadyExists},
             traits::{UserMediaViewReader, UserMediaViewRepo},
         },
     },
-    domain::user_media_view::entities::UserMediaView,
-    infrastructure::database::models::UserMediaView as UserMediaViewModel,
+    domain::{
+        media::value_objects::MediaStatus,
+        media_parser::value_objects::AgeRestriction,
+        user_media_view::entities::{
+            GenreViewStats, UserGenresStats, UserMediaView, UserMediaViewWithMedia,
+        },
+    },
+    infrastructure::database::models::{
+        GenreViewStats as GenreViewStatsModel, UserGenreStats as UserGenreStatsModel,
+        UserMediaView as UserMediaViewModel, UserMediaViewWithMedia as UserMediaViewWithMediaModel,
+    },
 };
 
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, JoinType, PostgresQueryBuilder, Query};
+use sea_query::{Alias, Expr, Func, JoinType, Order, PostgresQueryBuilder, Query};
 use sea_query_binder::SqlxBinder as _;
 use sqlx::PgConnection;
+use tracing::instrument;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct UserMediaViewRepoImpl<Conn> {
@@ -34,6 +46,7 @@ impl<Conn> UserMediaViewRepoImpl<Conn> {
 
 #[async_trait]
 impl<'a> UserMediaViewRepo for UserMediaViewRepoImpl<&'a mut PgConnection> {
+    #[instrument(skip_all)]
     async fn create<'s>(
         &mut self,
         user_media_view: CreateUserMediaView<'s>,
@@ -73,6 +86,31 @@ impl<'a> UserMediaViewRepo for UserMediaViewRepoImpl<&'a mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    #[instrument(skip_all)]
+    async fn delete<'s>(
+        &mut self,
+        user_media_view: DeleteUserMediaView<'s>,
+    ) -> Result<(), RepoKind<UserMediaViewIdNotExist>> {
+        let (sql, values) = Query::delete()
+            .from_table(Alias::new("user_media_views"))
+            .and_where(Expr::col(Alias::new("id")).eq(*user_media_view.id()))
+            .and_where(Expr::col(Alias::new("user_id")).eq(*user_media_view.user_id()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserMediaViewIdNotExist::new(
+                *user_media_view.id(),
+                "No user media view found to delete",
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -89,6 +127,7 @@ impl<Conn> UserMediaViewReaderImpl<Conn> {
 #[async_trait]
 impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
     #[allow(clippy::redundant_closure_for_method_calls)]
+    #[instrument(skip_all)]
     async fn get_by_id<'s>(
         &mut self,
         user_media_view: GetUserMediaViewById<'s>,
@@ -120,6 +159,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             })
     }
 
+    #[instrument(skip_all)]
     async fn get_by_user_id<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByUserId<'s>,
@@ -144,6 +184,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_media_id<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByMediaId<'s>,
@@ -168,6 +209,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_user_tg_id(
         &mut self,
         user_media_view: GetUserMediaViewByUserTgId,
@@ -201,6 +243,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_media_genre<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByMediaGenre<'s>,
@@ -234,6 +277,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_media_type<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByMediaType<'s>,
@@ -267,9 +311,10 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
-    async fn get_by_media_age_restriction(
+    #[instrument(skip_all)]
+    async fn get_by_media_age_restriction<'s>(
         &mut self,
-        user_media_view: GetUserMediaViewByMediaAgeRestriction,
+        user_media_view: GetUserMediaViewByMediaAgeRestriction<'s>,
     ) -> Result<Vec<UserMediaView>, RepoError> {
         let (sql, values) = Query::select()
             .columns([
@@ -282,8 +327,8 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .join(
                 JoinType::InnerJoin,
                 Alias::new("media"),
-                Expr::col((Alias::new("media"), Alias::new("is_sfw")))
-                    .eq(user_media_view.is_sfw())
+                Expr::col((Alias::new("media"), Alias::new("age_restriction")))
+                    .eq(user_media_view.age_restriction())
                     .and(
                         Expr::col((Alias::new("media"), Alias::new("id")))
                             .equals((Alias::new("user_media_views"), Alias::new("media_id"))),
@@ -300,6 +345,7 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     async fn get_by_media_source_id<'s>(
         &mut self,
         user_media_view: GetUserMediaViewByMediaSourceId<'s>,
@@ -332,4 +378,371 @@ impl<'a> UserMediaViewReader for UserMediaViewReaderImpl<&'a mut PgConnection> {
             })
             .map_err(Into::into)
     }
+
+    #[instrument(skip_all)]
+    async fn get_history_by_user_id<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewHistoryByUserId<'s>,
+    ) -> Result<Vec<UserMediaViewWithMedia>, RepoError> {
+        let mut query = Query::select();
+
+        query
+            .columns([
+                (Alias::new("user_media_views"), Alias::new("id")),
+                (Alias::new("user_media_views"), Alias::new("user_id")),
+                (Alias::new("user_media_views"), Alias::new("media_id")),
+                (Alias::new("user_media_views"), Alias::new("created")),
+            ])
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("url"))),
+                Alias::new("media_url"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("genre"))),
+                Alias::new("media_genre"),
+            )
+            .column((Alias::new("media"), Alias::new("media_type")))
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("age_restriction"))),
+                Alias::new("media_age_restriction"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("source_id"))),
+                Alias::new("media_source_id"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("created"))),
+                Alias::new("media_created"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("mime"))),
+                Alias::new("media_mime"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("size"))),
+                Alias::new("media_size"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("width"))),
+                Alias::new("media_width"),
+            )
+            .expr_as(
+                Expr::col((Alias::new("media"), Alias::new("height"))),
+                Alias::new("media_height"),
+            )
+            .from(Alias::new("user_media_views"))
+            .join(
+                JoinType::InnerJoin,
+                Alias::new("media"),
+                Expr::col((Alias::new("media"), Alias::new("id")))
+                    .equals((Alias::new("user_media_views"), Alias::new("media_id"))),
+            )
+            .and_where(
+                Expr::col((Alias::new("user_media_views"), Alias::new("user_id")))
+                    .eq(*user_media_view.user_id()),
+            );
+
+        if let Some(genre) = user_media_view.genre() {
+            query.and_where(Expr::col((Alias::new("media"), Alias::new("genre"))).eq(genre));
+        }
+        if let Some(media_type) = user_media_view.media_type() {
+            query.and_where(
+                Expr::col((Alias::new("media"), Alias::new("media_type"))).eq(media_type),
+            );
+        }
+        // Keyset pagination: take only views which are older than the view with `after_id`.
+        // If the view is forgotten, the first page is taken, because its position is unknown.
+        if let Some(after_id) = user_media_view.after_id() {
+            query.and_where(Expr::cust_with_values(
+                "(NOT EXISTS (SELECT 1 FROM user_media_views WHERE id = $1) \
+                 OR (user_media_views.created, user_media_views.id) < \
+                 (SELECT created, id FROM user_media_views WHERE id = $2))",
+                [*after_id, *after_id],
+            ));
+        }
+
+        query
+            .order_by(
+                (Alias::new("user_media_views"), Alias::new("created")),
+                Order::Desc,
+            )
+            .order_by(
+                (Alias::new("user_media_views"), Alias::new("id")),
+                Order::Desc,
+            );
+
+        if let Some(limit) = user_media_view.limit() {
+            query.limit(limit);
+        }
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|models: Vec<UserMediaViewWithMediaModel>| {
+                models.into_iter().map(Into::into).collect()
+            })
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_genre_stats_by_user_id<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewGenreStatsByUserId<'s>,
+    ) -> Result<UserGenresStats, RepoError> {
+        let (sql, values) = Query::select()
+            .columns([
+                (Alias::new("media"), Alias::new("genre")),
+                (Alias::new("media"), Alias::new("media_type")),
+                (Alias::new("media"), Alias::new("age_restriction")),
+            ])
+            .expr_as(
+                Func::count(Expr::col((Alias::new("media"), Alias::new("id")))),
+                Alias::new("total"),
+            )
+            .expr_as(
+                Func::count(Expr::col((
+                    Alias::new("user_media_views"),
+                    Alias::new("id"),
+                ))),
+                Alias::new("viewed"),
+            )
+            .from(Alias::new("media"))
+            .join(
+                JoinType::LeftJoin,
+                Alias::new("user_media_views"),
+                Expr::col((Alias::new("user_media_views"), Alias::new("media_id")))
+                    .equals((Alias::new("media"), Alias::new("id")))
+                    .and(
+                        Expr::col((Alias::new("user_media_views"), Alias::new("user_id")))
+                            .eq(*user_media_view.user_id()),
+                    ),
+            )
+            .and_where(Expr::col((Alias::new("media"), Alias::new("genre"))).is_not_null())
+            .and_where(
+                Expr::col((Alias::new("media"), Alias::new("age_restriction")))
+                    .ne(AgeRestriction::Unknown.as_str()),
+            )
+            .add_group_by([
+                Expr::col((Alias::new("media"), Alias::new("genre"))).into(),
+                Expr::col((Alias::new("media"), Alias::new("media_type"))).into(),
+                Expr::col((Alias::new("media"), Alias::new("age_restriction"))).into(),
+            ])
+            .order_by((Alias::new("media"), Alias::new("media_type")), Order::Asc)
+            .order_by((Alias::new("media"), Alias::new("genre")), Order::Asc)
+            .order_by(
+                (Alias::new("media"), Alias::new("age_restriction")),
+                Order::Desc,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|genre_stats_models: Vec<UserGenreStatsModel>| {
+                genre_stats_models
+                    .into_iter()
+                    .map(Into::into)
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .map_err(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_genre_view_stats<'s>(
+        &mut self,
+        user_media_view: GetUserMediaViewGenreViewStats<'s>,
+    ) -> Result<Vec<GenreViewStats>, RepoError> {
+        let active_since = *user_media_view.active_since();
+
+        // Views of each active user by genres
+        let user_genre_views = Query::select()
+            .columns([
+                (Alias::new("media"), Alias::new("genre")),
+                (Alias::new("media"), Alias::new("media_type")),
+                (Alias::new("media"), Alias::new("age_restriction")),
+            ])
+            .expr_as(
+                Func::count(Expr::col((
+                    Alias::new("user_media_views"),
+                    Alias::new("id"),
+                ))),
+                Alias::new("viewed"),
+            )
+            .expr_as(
+                Func::count(Expr::case(
+                    Expr::col((Alias::new("user_media_views"), Alias::new("created")))
+                        .gte(active_since),
+                    Expr::col((Alias::new("user_media_views"), Alias::new("id"))),
+                )),
+                Alias::new("recent_views"),
+            )
+            .from(Alias::new("user_media_views"))
+            .join(
+                JoinType::InnerJoin,
+                Alias::new("media"),
+                Expr::col((Alias::new("media"), Alias::new("id")))
+                    .equals((Alias::new("user_media_views"), Alias::new("media_id"))),
+            )
+            .and_where(Expr::col((Alias::new("media"), Alias::new("genre"))).is_not_null())
+            .and_where(
+                Expr::col((Alias::new("media"), Alias::new("status")))
+                    .eq(MediaStatus::Active.as_str()),
+            )
+            .and_where(Expr::col((Alias::new("media"), Alias::new("canonical_id"))).is_null())
+            .and_where(
+                Expr::col((Alias::new("user_media_views"), Alias::new("user_id"))).in_subquery(
+                    Query::select()
+                        .distinct()
+                        .column(Alias::new("user_id"))
+                        .from(Alias::new("user_media_views"))
+                        .and_where(Expr::col(Alias::new("created")).gte(active_since))
+                        .to_owned(),
+                ),
+            )
+            .add_group_by([
+                Expr::col((Alias::new("media"), Alias::new("genre"))).into(),
+                Expr::col((Alias::new("media"), Alias::new("media_type"))).into(),
+                Expr::col((Alias::new("media"), Alias::new("age_restriction"))).into(),
+                Expr::col((Alias::new("user_media_views"), Alias::new("user_id"))).into(),
+            ])
+            .to_owned();
+
+        let (sql, values) = Query::select()
+            .columns([
+                Alias::new("genre"),
+                Alias::new("media_type"),
+                Alias::new("age_restriction"),
+            ])
+            .expr_as(
+                Func::max(Expr::col(Alias::new("viewed"))),
+                Alias::new("max_viewed"),
+            )
+            .expr_as(
+                Func::cast_as(
+                    Func::sum(Expr::col(Alias::new("recent_views"))),
+                    Alias::new("BIGINT"),
+                ),
+                Alias::new("recent_views"),
+            )
+            .from_subquery(user_genre_views, Alias::new("user_genre_views"))
+            .add_group_by([
+                Expr::col(Alias::new("genre")).into(),
+                Expr::col(Alias::new("media_type")).into(),
+                Expr::col(Alias::new("age_restriction")).into(),
+            ])
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_as_with(&sql, values)
+            .fetch_all(&mut *self.conn)
+            .await
+            .map(|stats_models: Vec<GenreViewStatsModel>| {
+                stats_models.into_iter().map(Into::into).collect()
+            })
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserMediaViewReaderImpl;
+
+    use crate::{
+        application::user_media_view::{
+            dto::GetUserMediaViewHistoryByUserId, traits::UserMediaViewReader as _,
+        },
+        infrastructure::database::migrations,
+    };
+
+    use sqlx::{Connection as _, PgConnection};
+    use std::env;
+    use uuid::Uuid;
+
+    /// Gets the next page of the history after its last view is forgotten, the first page must be returned.
+    /// Data is created in a transaction, which is rolled back in the end.
+    /// Run it with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored test_get_history_after_forgotten_view`
+    #[tokio::test]
+    #[ignore = "requires `TEST_DATABASE_URL`"]
+    async fn test_get_history_after_forgotten_view() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("`TEST_DATABASE_URL` isn't set");
+
+        let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+        migrations::run(&pool).await.unwrap();
+
+        let mut conn = PgConnection::connect(&database_url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let user_id = Uuid::new_v4();
+        let source_id = Uuid::new_v4();
+        let media_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let view_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+        sqlx::query("INSERT INTO users (id, tg_id) VALUES ($1, -1)")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO sources (id, name, url) VALUES ($1, 'history', 'history')")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        for (index, (media_id, view_id)) in media_ids.iter().zip(&view_ids).enumerate() {
+            sqlx::query(
+                "INSERT INTO media (id, url, genre, media_type, age_restriction, source_id) \
+                VALUES ($1, $2, 'history', 'img', 'sfw', $3)",
+            )
+            .bind(media_id)
+            .bind(format!("history/{index}"))
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO user_media_views (id, user_id, media_id, created) \
+                VALUES ($1, $2, $3, now() - make_interval(mins => $4))",
+            )
+            .bind(view_id)
+            .bind(user_id)
+            .bind(media_id)
+            .bind(i32::try_from(index).unwrap())
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let get_history =
+            |after_id| GetUserMediaViewHistoryByUserId::new(&user_id, None, None, after_id, None);
+
+        let history = UserMediaViewReaderImpl::new(&mut *tx)
+            .get_history_by_user_id(get_history(Some(&view_ids[0])))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            history.iter().map(|view| view.view.id).collect::<Vec<_>>(),
+            view_ids[1..],
+        );
+
+        sqlx::query("DELETE FROM user_media_views WHERE id = $1")
+            .bind(view_ids[1])
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let history = UserMediaViewReaderImpl::new(&mut *tx)
+            .get_history_by_user_id(get_history(Some(&view_ids[1])))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            history.iter().map(|view| view.view.id).collect::<Vec<_>>(),
+            [view_ids[0], view_ids[2]],
+        );
+
+        tx.rollback().await.unwrap();
+    }
 }
@@ -0,0 +1,7 @@
+pub mod heartbeats;
+pub mod layer;
+pub mod server;
+
+pub use heartbeats::Heartbeats;
+pub use layer::Layer;
+pub use server::{run_server, Readiness};
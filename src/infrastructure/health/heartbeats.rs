@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Heartbeats of the bot components, which are used to check readiness of the bot
+#[derive(Debug)]
+pub struct Heartbeats {
+    started: Instant,
+    last_poll: Mutex<Option<Instant>>,
+    /// Last heartbeats of workers of sources by names of the sources
+    last_worker_beats: Mutex<HashMap<String, Instant>>,
+}
+
+impl Heartbeats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_poll: Mutex::new(None),
+            last_worker_beats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a successful poll of updates
+    pub fn record_poll(&self) {
+        *self.last_poll.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Returns time since the last successful poll of updates.
+    /// If there were no polls yet, returns time since the start.
+    pub fn last_poll_age(&self) -> Duration {
+        self.last_poll
+            .lock()
+            .unwrap()
+            .unwrap_or(self.started)
+            .elapsed()
+    }
+
+    /// Records a heartbeat of the worker of the source
+    pub fn record_worker_beat(&self, source_name: &str) {
+        let mut last_worker_beats = self.last_worker_beats.lock().unwrap();
+
+        match last_worker_beats.get_mut(source_name) {
+            Some(last_beat) => *last_beat = Instant::now(),
+            None => {
+                last_worker_beats.insert(source_name.to_owned(), Instant::now());
+            }
+        }
+    }
+
+    /// Returns time since the last heartbeat of the worker of the source or `None` if there were no heartbeats
+    pub fn last_worker_beat_age(&self, source_name: &str) -> Option<Duration> {
+        self.last_worker_beats
+            .lock()
+            .unwrap()
+            .get(source_name)
+            .map(Instant::elapsed)
+    }
+
+    /// Returns time since the start
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Default for Heartbeats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,140 @@
+use super::Heartbeats;
+
+use crate::infrastructure::media_parser::schedule::HEARTBEAT_TARGET;
+
+use std::{fmt, sync::Arc};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Metadata, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+/// Target prefix of spans and events of the Telegram Bot API client
+const TELEGRAM_CLIENT_TARGET: &str = "telers::client";
+/// Name of the Telegram Bot API method, which is used for polling
+const GET_UPDATES_METHOD_NAME: &str = "getUpdates";
+
+/// Span of a `getUpdates` request
+#[derive(Default)]
+struct PollRequest {
+    is_get_updates: bool,
+    failed: bool,
+}
+
+impl Visit for PollRequest {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "method_name" {
+            self.is_get_updates = value == GET_UPDATES_METHOD_NAME;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+/// Heartbeat event of a schedule of a media parser source
+#[derive(Default)]
+struct WorkerBeat {
+    source_name: Option<String>,
+}
+
+impl Visit for WorkerBeat {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "source" {
+            self.source_name = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+/// Tracing layer, which records heartbeats of successful `getUpdates` requests and of media parser workers.
+/// Use it with [`Layer::is_tracked`] as a per-layer filter,
+/// so the heartbeats don't depend on the logging level.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    heartbeats: Arc<Heartbeats>,
+}
+
+impl Layer {
+    pub fn new(heartbeats: Arc<Heartbeats>) -> Self {
+        Self { heartbeats }
+    }
+
+    /// Checks if spans and events with the metadata are used for heartbeats
+    pub fn is_tracked(metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with(TELEGRAM_CLIENT_TARGET)
+            || metadata.target() == HEARTBEAT_TARGET
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for Layer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "send" {
+            return;
+        }
+
+        let mut poll_request = PollRequest::default();
+        attrs.record(&mut poll_request);
+
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(poll_request);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut extensions = span.extensions_mut();
+
+        if let Some(poll_request) = extensions.get_mut::<PollRequest>() {
+            values.record(poll_request);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() == HEARTBEAT_TARGET {
+            let mut worker_beat = WorkerBeat::default();
+            event.record(&mut worker_beat);
+
+            if let Some(source_name) = worker_beat.source_name {
+                self.heartbeats.record_worker_beat(&source_name);
+            }
+
+            return;
+        }
+
+        // Errors and warnings inside of a request span mean that the request wasn't sent or the response wasn't received
+        if *event.metadata().level() > Level::WARN {
+            return;
+        }
+
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+
+        let mut extensions = span.extensions_mut();
+
+        if let Some(poll_request) = extensions.get_mut::<PollRequest>() {
+            poll_request.failed = true;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(poll_request) = span.extensions_mut().remove::<PollRequest>() else {
+            return;
+        };
+
+        if poll_request.is_get_updates && !poll_request.failed {
+            self.heartbeats.record_poll();
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use super::Heartbeats;
+
+use crate::{
+    application::common::traits::{UnitOfWork as _, UnitOfWorkFactory},
+    domain::source::entities::SourceFetchStats,
+};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse as _, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use std::{collections::HashMap, io, net::SocketAddr, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use tokio::{net::TcpListener, time as tokio_time};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+/// Timeout of database checks
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Settings and dependencies of readiness checks
+#[derive(Debug, Clone)]
+pub struct Readiness<UoWFactory> {
+    pub heartbeats: Arc<Heartbeats>,
+    pub uow_factory: UoWFactory,
+    pub max_poll_age: Duration,
+    pub max_worker_inactivity: Duration,
+    /// Check polling of updates. It should be disabled if updates are received with webhook.
+    pub check_polling: bool,
+    /// Check media parser worker. It should be disabled if the worker isn't started.
+    pub check_worker: bool,
+}
+
+/// Returns names of enabled sources, which didn't fetch media longer than `max_inactivity`
+/// and whose workers didn't send heartbeats during this time.
+/// Workers don't fetch genres, which are stocked up or backed off, but send heartbeats while they wait.
+fn inactive_sources<'a>(
+    fetch_stats: &'a [SourceFetchStats],
+    now: OffsetDateTime,
+    max_inactivity: Duration,
+    heartbeats: &Heartbeats,
+) -> Vec<&'a str> {
+    let mut last_fetches: HashMap<&str, Option<OffsetDateTime>> = HashMap::new();
+
+    for stats in fetch_stats.iter().filter(|stats| stats.source_enabled) {
+        let last_fetch_at = last_fetches.entry(&stats.source_name).or_default();
+        *last_fetch_at = (*last_fetch_at).max(stats.last_fetch_at());
+    }
+
+    let mut inactive_sources = last_fetches
+        .into_iter()
+        .filter(|(source_name, last_fetch_at)| {
+            last_fetch_at.map_or(true, |last_fetch_at| {
+                (now - last_fetch_at).unsigned_abs() > max_inactivity
+            }) && heartbeats
+                .last_worker_beat_age(source_name)
+                .map_or(true, |age| age > max_inactivity)
+        })
+        .map(|(source_name, _)| source_name)
+        .collect::<Vec<_>>();
+    inactive_sources.sort_unstable();
+    inactive_sources
+}
+
+/// Fetch stats of a genre of a source, which are served by `/sources`
+#[derive(Debug, Serialize)]
+struct SourceFetchStatsResponse<'a> {
+    source_name: &'a str,
+    source_enabled: bool,
+    genre: &'a str,
+    media_type: &'a str,
+    age_restriction: &'a str,
+    success_count: i64,
+    failure_count: i64,
+    new_media_count: i64,
+    duplicate_media_count: i64,
+    average_latency_ms: i64,
+    last_latency_ms: i64,
+    failing: bool,
+    last_error: Option<&'a str>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    last_error_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    last_success_at: Option<OffsetDateTime>,
+}
+
+impl<'a> From<&'a SourceFetchStats> for SourceFetchStatsResponse<'a> {
+    fn from(stats: &'a SourceFetchStats) -> Self {
+        Self {
+            source_name: &stats.source_name,
+            source_enabled: stats.source_enabled,
+            genre: &stats.genre,
+            media_type: &stats.media_type,
+            age_restriction: &stats.age_restriction,
+            success_count: stats.success_count,
+            failure_count: stats.failure_count,
+            new_media_count: stats.new_media_count,
+            duplicate_media_count: stats.duplicate_media_count,
+            average_latency_ms: stats.average_latency_ms(),
+            last_latency_ms: stats.last_latency_ms,
+            failing: stats.is_failing(),
+            last_error: stats.last_error.as_deref(),
+            last_error_at: stats.last_error_at,
+            last_success_at: stats.last_success_at,
+        }
+    }
+}
+
+/// Returns fetch stats of genres of sources, the same as the `/sources` admin command
+async fn get_fetch_stats<UoWFactory>(
+    uow_factory: &UoWFactory,
+) -> Result<Vec<SourceFetchStats>, String>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let fetch_stats_result = tokio_time::timeout(DATABASE_TIMEOUT, async {
+        match uow.source_reader().await {
+            Ok(mut source_reader) => source_reader
+                .get_fetch_stats()
+                .await
+                .map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        }
+    })
+    .await;
+
+    match fetch_stats_result {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {DATABASE_TIMEOUT:?}")),
+    }
+}
+
+async fn healthz() -> &'static str {
+    "OK"
+}
+
+#[instrument(skip_all)]
+async fn readyz<UoWFactory>(State(readiness): State<Readiness<UoWFactory>>) -> (StatusCode, String)
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut failures = vec![];
+
+    let poll_age = readiness.heartbeats.last_poll_age();
+    if readiness.check_polling && poll_age > readiness.max_poll_age {
+        failures.push(format!("polling: last successful poll {poll_age:.0?} ago"));
+    }
+
+    match get_fetch_stats(&readiness.uow_factory).await {
+        Ok(fetch_stats) => {
+            // Sources have time to fetch media after the start
+            if readiness.check_worker
+                && readiness.heartbeats.uptime() > readiness.max_worker_inactivity
+            {
+                for source_name in inactive_sources(
+                    &fetch_stats,
+                    OffsetDateTime::now_utc(),
+                    readiness.max_worker_inactivity,
+                    &readiness.heartbeats,
+                ) {
+                    failures.push(format!("worker: source {source_name} is inactive"));
+                }
+            }
+        }
+        Err(err) => failures.push(format!("database: {err}")),
+    }
+
+    if failures.is_empty() {
+        (StatusCode::OK, "OK".to_owned())
+    } else {
+        event!(Level::WARN, ?failures, "Bot isn't ready");
+
+        (StatusCode::SERVICE_UNAVAILABLE, failures.join("\n"))
+    }
+}
+
+#[instrument(skip_all)]
+async fn sources<UoWFactory>(State(readiness): State<Readiness<UoWFactory>>) -> Response
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    match get_fetch_stats(&readiness.uow_factory).await {
+        Ok(fetch_stats) => Json(
+            fetch_stats
+                .iter()
+                .map(SourceFetchStatsResponse::from)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(err) => {
+            event!(Level::ERROR, %err, "Failed to get fetch stats");
+
+            (StatusCode::SERVICE_UNAVAILABLE, format!("database: {err}")).into_response()
+        }
+    }
+}
+
+/// Run HTTP listener, which exposes `/healthz`, `/readyz` and `/sources` with fetch stats of sources,
+/// until the shutdown token is cancelled
+/// # Errors
+/// Returns error if the listener can't be bound
+#[instrument(skip_all, fields(%address))]
+pub async fn run_server<UoWFactory>(
+    address: SocketAddr,
+    readiness: Readiness<UoWFactory>,
+    shutdown: CancellationToken,
+) -> Result<(), io::Error>
+where
+    UoWFactory: UnitOfWorkFactory + Clone + Send + Sync + 'static,
+    UoWFactory::UnitOfWork: Send,
+{
+    let listener = TcpListener::bind(address).await?;
+
+    event!(Level::INFO, "Health server started");
+
+    let router = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz::<UoWFactory>))
+        .route("/sources", get(sources::<UoWFactory>))
+        .with_state(readiness);
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inactive_sources, SourceFetchStatsResponse};
+
+    use crate::{
+        domain::{
+            media::entities::{GenreStats, GenresStats},
+            media_parser::entities::{Genre, Genres},
+            source::entities::SourceFetchStats,
+        },
+        infrastructure::{
+            health::{Heartbeats, Layer},
+            media_parser::{Schedule, Stock},
+        },
+    };
+
+    use serde_json::json;
+    use std::{sync::Arc, time::Duration};
+    use time::{Duration as TimeDuration, OffsetDateTime};
+    use tokio::{sync::watch, time as tokio_time};
+    use tracing_subscriber::{filter::filter_fn, layer::SubscriberExt as _, Layer as _, Registry};
+
+    fn fetch_stats(
+        source_name: &str,
+        last_success_at: Option<OffsetDateTime>,
+        last_error_at: Option<OffsetDateTime>,
+    ) -> SourceFetchStats {
+        SourceFetchStats {
+            source_name: source_name.to_owned(),
+            source_enabled: true,
+            genre: "neko".to_owned(),
+            media_type: "gif".to_owned(),
+            age_restriction: "sfw".to_owned(),
+            success_count: 0,
+            failure_count: 0,
+            new_media_count: 0,
+            duplicate_media_count: 0,
+            total_latency_ms: 0,
+            last_latency_ms: 0,
+            last_error: None,
+            last_error_at,
+            last_success_at,
+        }
+    }
+
+    #[test]
+    fn test_inactive_sources() {
+        let now = OffsetDateTime::now_utc();
+        let hour_ago = now - TimeDuration::hours(1);
+
+        let stats = [
+            fetch_stats("nekos.best", Some(hour_ago), None),
+            fetch_stats("nekos.best", None, Some(now)),
+            fetch_stats("waifu.pics", Some(hour_ago), Some(hour_ago)),
+            fetch_stats("nekos.fun", None, None),
+            SourceFetchStats {
+                source_enabled: false,
+                ..fetch_stats("nekos.life", None, None)
+            },
+        ];
+
+        let heartbeats = Heartbeats::new();
+
+        assert_eq!(
+            inactive_sources(&stats, now, Duration::from_secs(600), &heartbeats),
+            ["nekos.fun", "waifu.pics"]
+        );
+        assert_eq!(
+            inactive_sources(&stats, now, Duration::from_secs(7200), &heartbeats),
+            ["nekos.fun"]
+        );
+    }
+
+    #[test]
+    fn test_source_fetch_stats_response() {
+        let stats = SourceFetchStats {
+            success_count: 3,
+            failure_count: 1,
+            new_media_count: 20,
+            duplicate_media_count: 10,
+            total_latency_ms: 400,
+            last_latency_ms: 50,
+            last_error: Some("Timeout".to_owned()),
+            ..fetch_stats(
+                "nekos.best",
+                Some(OffsetDateTime::from_unix_timestamp(1_704_110_400).unwrap()),
+                Some(OffsetDateTime::from_unix_timestamp(1_704_196_800).unwrap()),
+            )
+        };
+
+        assert_eq!(
+            serde_json::to_value(SourceFetchStatsResponse::from(&stats)).unwrap(),
+            json!({
+                "source_name": "nekos.best",
+                "source_enabled": true,
+                "genre": "neko",
+                "media_type": "gif",
+                "age_restriction": "sfw",
+                "success_count": 3,
+                "failure_count": 1,
+                "new_media_count": 20,
+                "duplicate_media_count": 10,
+                "average_latency_ms": 100,
+                "last_latency_ms": 50,
+                "failing": true,
+                "last_error": "Timeout",
+                "last_error_at": "2024-01-02T12:00:00Z",
+                "last_success_at": "2024-01-01T12:00:00Z",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inactive_sources_with_stocked_up_worker() {
+        let heartbeats = Arc::new(Heartbeats::new());
+        let _guard = tracing::subscriber::set_default(
+            Registry::default()
+                .with(Layer::new(heartbeats.clone()).with_filter(filter_fn(Layer::is_tracked))),
+        );
+
+        let genres = Genres::new(vec![Genre::new_sfw_image("neko")]);
+        let (_stock_sender, stock) = watch::channel(Stock::new(
+            GenresStats(vec![GenreStats {
+                total: 100,
+                genre: "neko".to_owned(),
+                media_type: "img".to_owned(),
+                age_restriction: "sfw".to_owned(),
+            }]),
+            vec![],
+        ));
+        let mut schedule =
+            Schedule::new("nekos.best", true, Duration::ZERO).with_stock(stock, Some(100));
+
+        // The genre is stocked up, so the worker waits without fetches
+        assert!(
+            tokio_time::timeout(Duration::from_millis(10), schedule.next_genre(&genres))
+                .await
+                .is_err()
+        );
+
+        let now = OffsetDateTime::now_utc();
+        let hour_ago = now - TimeDuration::hours(1);
+
+        let stats = [
+            fetch_stats("nekos.best", Some(hour_ago), None),
+            fetch_stats("waifu.pics", Some(hour_ago), None),
+        ];
+
+        assert_eq!(
+            inactive_sources(&stats, now, Duration::from_secs(600), &heartbeats),
+            ["waifu.pics"]
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use crate::application::{
+    common::{
+        exceptions::{BeginError, CommitError, RepoError, RollbackError},
+        traits::{UnitOfWork as _, UnitOfWorkFactory},
+    },
+    media::dto::{GetCanonicalMedia, GetMediaToHash, SetMediaHashes},
+};
+
+use image::{imageops::FilterType, DynamicImage};
+use metrics::counter;
+use sha2::{Digest as _, Sha256};
+use std::{fmt::Write as _, num::NonZeroU32, time::Duration};
+use tokio::{
+    task,
+    time::{self as tokio_time, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+/// Count of media, which are read from the database per request
+const BATCH_SIZE: u64 = 100;
+/// Timeout of a download request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Max size of a file to download. Telegram doesn't send bigger files by url anyway.
+const MAX_FILE_SIZE: u64 = 20 * 1024 * 1024;
+/// Time to wait before the next try if there are no media to hash
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Hashes of media content
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MediaHashes {
+    content_hash: String,
+    perceptual_hash: Option<u64>,
+}
+
+impl MediaHashes {
+    fn from_content(content: &[u8]) -> Self {
+        Self {
+            content_hash: content_hash(content),
+            perceptual_hash: image::load_from_memory(content)
+                .ok()
+                .as_ref()
+                .map(perceptual_hash),
+        }
+    }
+}
+
+/// Downloads media and links duplicates to the media, which they duplicate
+#[derive(Debug, Clone)]
+pub struct MediaDeduplicator<Client = reqwest::Client> {
+    client: Client,
+    requests_per_second: NonZeroU32,
+    max_distance: u32,
+}
+
+impl MediaDeduplicator {
+    /// # Arguments
+    /// * `requests_per_second` - Max count of download requests per second
+    /// * `max_distance` - Max count of different bits of perceptual hashes of near-duplicates
+    pub fn new(requests_per_second: NonZeroU32, max_distance: u32) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            requests_per_second,
+            max_distance,
+        }
+    }
+
+    /// Downloads the media and returns its hashes.
+    /// If the media can't be downloaded or it's too big, returns `None`.
+    /// Images are decoded in a blocking thread, so they don't block other tasks of the runtime.
+    #[instrument(skip(self))]
+    async fn hash(&self, url: &str) -> Option<MediaHashes> {
+        let mut response = match self
+            .client
+            .get(url)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+        {
+            Ok(response) => response,
+            Err(err) => {
+                event!(Level::DEBUG, %err, "Error downloading media");
+
+                return None;
+            }
+        };
+
+        if response
+            .content_length()
+            .is_some_and(|content_length| content_length > MAX_FILE_SIZE)
+        {
+            event!(Level::DEBUG, "Media is too big to download");
+
+            return None;
+        }
+
+        let content = match read_content(&mut response, MAX_FILE_SIZE).await {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                event!(Level::DEBUG, "Media is too big to download");
+
+                return None;
+            }
+            Err(err) => {
+                event!(Level::DEBUG, %err, "Error downloading media");
+
+                return None;
+            }
+        };
+
+        match task::spawn_blocking(move || MediaHashes::from_content(&content)).await {
+            Ok(hashes) => Some(hashes),
+            Err(err) => {
+                event!(Level::ERROR, %err, "Error hashing media");
+
+                None
+            }
+        }
+    }
+}
+
+/// Reads body of the response. If the body is bigger than `max_size`, returns `None` without reading the rest.
+/// Content length can be unknown, for example, for chunked responses, so the size is checked while reading.
+async fn read_content(
+    response: &mut reqwest::Response,
+    max_size: u64,
+) -> Result<Option<Vec<u8>>, reqwest::Error> {
+    let mut content = vec![];
+
+    while let Some(chunk) = response.chunk().await? {
+        if (content.len() + chunk.len()) as u64 > max_size {
+            return Ok(None);
+        }
+
+        content.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(content))
+}
+
+/// Returns SHA-256 of the content in hex
+fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        })
+}
+
+/// Returns dHash of the image. Images, which differ in size, compression or small details, have close hashes.
+/// Animated images are decoded by their first frame.
+fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let image = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    image.rows().fold(0, |hash, row| {
+        let row = row.collect::<Vec<_>>();
+
+        row.windows(2).fold(hash, |hash, pixels| {
+            (hash << 1) | u64::from(pixels[0].0[0] < pixels[1].0[0])
+        })
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error(transparent)]
+    Begin(#[from] BeginError),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+    #[error(transparent)]
+    Rollback(#[from] RollbackError),
+    #[error(transparent)]
+    Unexpected(#[from] RepoError),
+}
+
+/// Hash media, which weren't hashed yet, and link duplicates to their canonical media.
+/// Media, which can't be downloaded, are marked as hashed without hashes and aren't deduplicated.
+/// Deduplication stops between media, when the shutdown token is cancelled.
+/// # Errors
+/// Returns error if media can't be read from the database
+#[instrument(skip_all)]
+pub async fn run_deduplication<UoWFactory>(
+    deduplicator: MediaDeduplicator,
+    uow_factory: UoWFactory,
+    shutdown: CancellationToken,
+) -> Result<(), ErrorKind>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut rate_limit =
+        tokio_time::interval(Duration::from_secs(1) / deduplicator.requests_per_second.get());
+    rate_limit.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    loop {
+        let media_list = uow
+            .media_reader()
+            .await?
+            .get_to_hash(GetMediaToHash::new(BATCH_SIZE))
+            .await?;
+
+        // Don't keep the transaction open while media are downloaded
+        uow.commit().await?;
+
+        if media_list.is_empty() {
+            event!(Level::DEBUG, "No media to hash");
+
+            if shutdown
+                .run_until_cancelled(tokio_time::sleep(IDLE_INTERVAL))
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media deduplicator stopped by shutdown");
+
+                return Ok(());
+            }
+
+            continue;
+        }
+
+        event!(Level::DEBUG, count = media_list.len(), "Hashing media");
+
+        for media in media_list {
+            if shutdown
+                .run_until_cancelled(rate_limit.tick())
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media deduplicator stopped by shutdown");
+
+                return Ok(());
+            }
+
+            let Some(hashes) = shutdown
+                .run_until_cancelled(deduplicator.hash(&media.url))
+                .await
+            else {
+                event!(Level::DEBUG, "Media deduplicator stopped by shutdown");
+
+                return Ok(());
+            };
+            // Postgres doesn't have unsigned integers, so the hash is stored with the same bits
+            let perceptual_hash = hashes
+                .as_ref()
+                .and_then(|hashes| hashes.perceptual_hash)
+                .map(|hash| i64::from_ne_bytes(hash.to_ne_bytes()));
+
+            let canonical = match &hashes {
+                Some(hashes) => {
+                    uow.media_reader()
+                        .await?
+                        .get_canonical(GetCanonicalMedia::new(
+                            &media.id,
+                            media.genre.as_deref(),
+                            &media.media_type,
+                            &hashes.content_hash,
+                            perceptual_hash,
+                            deduplicator.max_distance,
+                        ))
+                        .await?
+                }
+                None => None,
+            };
+
+            counter!(
+                "media_deduplicator_hashes_total",
+                "result" => match (&hashes, &canonical) {
+                    (None, _) => "failed",
+                    (Some(_), None) => "unique",
+                    (Some(_), Some(_)) => "duplicate",
+                },
+            )
+            .increment(1);
+
+            if let Some(canonical) = &canonical {
+                event!(
+                    Level::INFO,
+                    media_id = %media.id,
+                    canonical_id = %canonical.id,
+                    url = media.url,
+                    canonical_url = canonical.url,
+                    "Media is a duplicate",
+                );
+            }
+
+            let set_hashes_result = uow
+                .media_repo()
+                .await?
+                .set_hashes(SetMediaHashes::new(
+                    &media.id,
+                    hashes.as_ref().map(|hashes| hashes.content_hash.as_str()),
+                    perceptual_hash,
+                    canonical.as_ref().map(|canonical| &canonical.id),
+                ))
+                .await;
+
+            match set_hashes_result {
+                Ok(()) => uow.commit().await?,
+                Err(err) => {
+                    uow.rollback().await?;
+
+                    event!(Level::ERROR, %err, media_id = %media.id, "Failed to set media hashes");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_hash, perceptual_hash, read_content, MediaHashes};
+
+    use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+    use tokio::{
+        io::{AsyncReadExt as _, AsyncWriteExt as _},
+        net::TcpListener,
+    };
+
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            let value = ((x * 255 / width + y * 64 / height) % 256) as u8;
+
+            Rgb([value, value / 2, 255 - value])
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_read_content() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        // Chunked response without content length
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let _ = stream.read(&mut [0; 1024]).await.unwrap();
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                        4\r\nabcd\r\n4\r\nefgh\r\n0\r\n\r\n",
+                    )
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let client = reqwest::Client::new();
+
+        let mut response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.content_length(), None);
+        assert_eq!(read_content(&mut response, 6).await.unwrap(), None);
+
+        let mut response = client.get(&url).send().await.unwrap();
+        assert_eq!(
+            read_content(&mut response, 8).await.unwrap().as_deref(),
+            Some(&b"abcdefgh"[..])
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_perceptual_hash() {
+        let image = gradient(300, 200);
+
+        // Resized image is a near-duplicate
+        let resized_distance = (perceptual_hash(&image)
+            ^ perceptual_hash(&image.resize_exact(150, 100, image::imageops::FilterType::Nearest)))
+        .count_ones();
+        assert!(resized_distance <= 2, "{resized_distance}");
+
+        // Mirrored image isn't
+        let mirrored_distance =
+            (perceptual_hash(&image) ^ perceptual_hash(&image.fliph())).count_ones();
+        assert!(mirrored_distance > 32, "{mirrored_distance}");
+    }
+
+    #[test]
+    fn test_media_hashes_from_content() {
+        let mut png = Vec::new();
+        gradient(64, 64)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+
+        let hashes = MediaHashes::from_content(&png);
+        assert_eq!(hashes.content_hash, content_hash(&png));
+        assert_eq!(
+            hashes.perceptual_hash,
+            Some(perceptual_hash(&gradient(64, 64)))
+        );
+
+        let hashes = MediaHashes::from_content(b"not an image");
+        assert_eq!(hashes.perceptual_hash, None);
+    }
+}
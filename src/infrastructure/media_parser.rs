@@ -1,6 +1,16 @@
+pub mod control;
 pub mod nekos_best;
+pub mod schedule;
+pub mod settings;
+pub mod sources;
+pub mod stock;
 pub mod waifu_pics;
 pub mod worker;
 
+pub use control::{Command as ControlCommand, Controller};
 pub use nekos_best::NekosBest;
+pub use schedule::{FetchOutcome, Schedule, Scheduling};
+pub use settings::{SourceSettings, SourcesSettings};
+pub use sources::{Sources, SourcesReloader};
+pub use stock::Stock;
 pub use waifu_pics::WaifuPics;
@@ -0,0 +1,71 @@
+use crate::domain::media_parser::entities::Genre;
+
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Max count of commands, which aren't received by workers yet
+const COMMANDS_BUFFER: usize = 16;
+
+/// Command for workers of sources
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Stop fetches of the source or only of the genre of the source
+    Pause {
+        source_name: String,
+        genre: Option<Genre>,
+    },
+    /// Continue fetches of the source or only of the genre of the source
+    Resume {
+        source_name: String,
+        genre: Option<Genre>,
+    },
+    /// Fetch the genre or all genres of the source without waiting for their turn
+    Refresh {
+        source_name: String,
+        genre: Option<Genre>,
+    },
+    /// Change time between fetches of genres of the source
+    SetPollingInterval {
+        source_name: String,
+        polling_interval: Duration,
+    },
+}
+
+impl Command {
+    /// Returns name of the source, which the command is sent to
+    pub fn source_name(&self) -> &str {
+        match self {
+            Self::Pause { source_name, .. }
+            | Self::Resume { source_name, .. }
+            | Self::Refresh { source_name, .. }
+            | Self::SetPollingInterval { source_name, .. } => source_name,
+        }
+    }
+}
+
+/// Sends commands to workers of sources, which are run in the same process
+#[derive(Debug, Clone)]
+pub struct Controller {
+    sender: broadcast::Sender<Command>,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(COMMANDS_BUFFER);
+
+        Self { sender }
+    }
+}
+
+impl Controller {
+    /// Sends the command to workers.
+    /// Returns `false` if there are no workers to receive it, for example, if the worker is run in another process.
+    pub fn send(&self, command: Command) -> bool {
+        self.sender.send(command).is_ok()
+    }
+
+    /// Returns receiver of commands for a worker
+    pub fn subscribe(&self) -> broadcast::Receiver<Command> {
+        self.sender.subscribe()
+    }
+}
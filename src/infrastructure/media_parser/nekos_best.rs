@@ -12,9 +12,32 @@ use serde::Deserialize;
 use std::{borrow::Cow, collections::HashMap};
 use tracing::{event, instrument, Level};
 
+/// Returns genres, which are provided by the source by default.
+/// They're used if genres of the source aren't set in the sources config.
+pub fn default_genres() -> &'static Genres {
+    lazy_static! {
+        static ref GENRES: Genres = Genres::new(
+            [
+                vec_new_sfw_gif![
+                    "baka", "bite", "blush", "bored", "cry", "cuddle", "dance", "facepalm", "feed",
+                    "handhold", "happy", "highfive", "hug", "kick", "kiss", "laugh", "nod", "nom",
+                    "nope", "pat", "poke", "pout", "punch", "shoot", "shrug", "slap", "sleep",
+                    "smile", "smug", "stare", "think", "thumbsup", "tickle", "wave", "wink",
+                    "yeet",
+                ],
+                vec_new_sfw_image!["husbando", "kitsune", "neko", "waifu"],
+            ]
+            .concat()
+        );
+    }
+
+    &GENRES
+}
+
 #[derive(Debug, Clone)]
 pub struct NekosBest<Client = reqwest::Client> {
     url: Cow<'static, str>,
+    genres: Genres,
     client: Client,
 }
 
@@ -28,9 +51,16 @@ impl<Client> NekosBest<Client> {
     pub fn new(client: Client) -> NekosBest<Client> {
         Self {
             client,
+            genres: default_genres().clone(),
             url: "https://nekos.best/api/v2".into(),
         }
     }
+
+    /// Set the genres to parse instead of the default ones
+    #[must_use]
+    pub fn with_genres(self, genres: Genres) -> Self {
+        Self { genres, ..self }
+    }
 }
 
 impl Default for NekosBest {
@@ -72,23 +102,7 @@ impl Source for NekosBest<reqwest::Client> {
     }
 
     fn genres(&self) -> &Genres {
-        lazy_static! {
-            static ref GENRES: Genres = Genres::new(
-                [
-                    vec_new_sfw_gif![
-                        "baka", "bite", "blush", "bored", "cry", "cuddle", "dance", "facepalm",
-                        "feed", "handhold", "happy", "highfive", "hug", "kick", "kiss", "laugh",
-                        "nod", "nom", "nope", "pat", "poke", "pout", "punch", "shoot", "shrug",
-                        "slap", "sleep", "smile", "smug", "stare", "think", "thumbsup", "tickle",
-                        "wave", "wink", "yeet",
-                    ],
-                    vec_new_sfw_image!["husbando", "kitsune", "neko", "waifu"],
-                ]
-                .concat()
-            );
-        }
-
-        &GENRES
+        &self.genres
     }
 
     #[instrument(skip(self))]
@@ -0,0 +1,711 @@
+use super::{control::Command, Controller, SourceSettings, SourcesSettings, Stock};
+
+use crate::domain::media_parser::entities::{Genre, Genres};
+
+use std::{cmp::Ordering, collections::HashMap, collections::VecDeque, future, time::Duration};
+use tokio::{
+    sync::{
+        broadcast::{
+            self,
+            error::{RecvError, TryRecvError},
+        },
+        mpsc, watch,
+    },
+    time::{self as tokio_time, Instant},
+};
+use tracing::{event, Level};
+
+/// Backoff of a genre after the first fetch, which returned only duplicates
+const DUPLICATES_INITIAL_BACKOFF: Duration = Duration::from_secs(60);
+/// Max backoff of a genre, which returns only duplicates, if it isn't set
+const DEFAULT_DUPLICATES_MAX_BACKOFF: Duration = Duration::from_secs(3600);
+/// Target of heartbeat events of schedules, which show that the worker of the source is alive,
+/// even if it doesn't fetch genres, because they're stocked up or backed off
+pub const HEARTBEAT_TARGET: &str = "media_parser::heartbeat";
+/// Max time between heartbeats of a waiting schedule
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Settings of scheduling of fetches of genres by stock of media
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scheduling {
+    /// Genres with this count of media, which aren't viewed by active users, aren't fetched.
+    /// If it isn't set, genres are fetched regardless of their stock.
+    pub stock_target: Option<i64>,
+    /// Users, who viewed media during this period, are active
+    pub active_users_period: Duration,
+    /// Time between updates of the stock
+    pub stock_polling_interval: Duration,
+    /// Max time a genre, which returns only duplicates, isn't fetched
+    pub duplicates_max_backoff: Duration,
+}
+
+impl Default for Scheduling {
+    fn default() -> Self {
+        Self {
+            stock_target: None,
+            active_users_period: Duration::from_secs(7 * 24 * 60 * 60),
+            stock_polling_interval: Duration::from_secs(300),
+            duplicates_max_backoff: DEFAULT_DUPLICATES_MAX_BACKOFF,
+        }
+    }
+}
+
+/// Outcome of a saved fetch of a genre, which is sent to the schedule of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchOutcome {
+    pub genre: Genre,
+    pub new_media_count: i64,
+}
+
+#[derive(Debug, Default)]
+struct GenreState {
+    /// Number of the last fetch of the genre among fetches of the source
+    last_fetch_number: Option<u64>,
+    /// Count of fetches in a row, which returned only duplicates
+    duplicate_fetches: u32,
+    backoff_until: Option<Instant>,
+}
+
+/// Schedule of fetches of genres of a source.
+/// Genres with the lowest stock of unviewed media relative to their recent views are fetched first,
+/// genres, which return only duplicates, are backed off and genres with enough stock aren't fetched.
+/// Commands of the controller pause, resume and refresh the source and its genres,
+/// and settings of the source in the database apply commands, which are sent to workers in other processes.
+#[derive(Debug)]
+pub struct Schedule {
+    source_name: String,
+    receiver: Option<broadcast::Receiver<Command>>,
+    enabled: bool,
+    polling_interval: Duration,
+    paused_genres: Vec<Genre>,
+    refresh_genres: VecDeque<Genre>,
+    last_fetch_at: Option<Instant>,
+    skip_wait: bool,
+    fetch_count: u64,
+    settings: Option<watch::Receiver<SourcesSettings>>,
+    stock: Option<watch::Receiver<Stock>>,
+    stock_target: Option<i64>,
+    outcomes: Option<mpsc::UnboundedReceiver<FetchOutcome>>,
+    duplicates_max_backoff: Duration,
+    genre_states: HashMap<Genre, GenreState>,
+}
+
+impl Schedule {
+    /// Creates a new schedule
+    /// # Arguments
+    /// * `source_name` - The name of the source
+    /// * `enabled` - If `false`, genres aren't fetched until the source is resumed
+    /// * `polling_interval` - Time between fetches of genres
+    pub fn new(source_name: impl Into<String>, enabled: bool, polling_interval: Duration) -> Self {
+        Self {
+            source_name: source_name.into(),
+            receiver: None,
+            enabled,
+            polling_interval,
+            paused_genres: vec![],
+            refresh_genres: VecDeque::new(),
+            last_fetch_at: None,
+            skip_wait: false,
+            fetch_count: 0,
+            settings: None,
+            stock: None,
+            stock_target: None,
+            outcomes: None,
+            duplicates_max_backoff: DEFAULT_DUPLICATES_MAX_BACKOFF,
+            genre_states: HashMap::new(),
+        }
+    }
+
+    /// Receive commands for the source from the controller
+    #[must_use]
+    pub fn with_controller(self, controller: &Controller) -> Self {
+        Self {
+            receiver: Some(controller.subscribe()),
+            ..self
+        }
+    }
+
+    /// Apply settings of the source, which are updated periodically.
+    /// Current settings are applied immediately, if the source is in them.
+    /// # Arguments
+    /// * `settings` - Receiver of settings of sources
+    #[must_use]
+    pub fn with_settings(mut self, mut settings: watch::Receiver<SourcesSettings>) -> Self {
+        let source_settings = settings.borrow_and_update().get(&self.source_name).cloned();
+
+        if let Some(source_settings) = source_settings {
+            self.apply_settings(source_settings);
+        }
+
+        Self {
+            settings: Some(settings),
+            ..self
+        }
+    }
+
+    /// Prioritize genres by the stock of media
+    /// # Arguments
+    /// * `stock` - Receiver of the stock, which is updated periodically
+    /// * `stock_target` - Genres with this count of unviewed media aren't fetched
+    #[must_use]
+    pub fn with_stock(self, stock: watch::Receiver<Stock>, stock_target: Option<i64>) -> Self {
+        Self {
+            stock: Some(stock),
+            stock_target,
+            ..self
+        }
+    }
+
+    /// Back off genres, which return only duplicates, by outcomes of their fetches
+    /// # Arguments
+    /// * `outcomes` - Receiver of outcomes of saved fetches
+    /// * `duplicates_max_backoff` - Max time a genre isn't fetched
+    #[must_use]
+    pub fn with_outcomes(
+        self,
+        outcomes: mpsc::UnboundedReceiver<FetchOutcome>,
+        duplicates_max_backoff: Duration,
+    ) -> Self {
+        Self {
+            outcomes: Some(outcomes),
+            duplicates_max_backoff,
+            ..self
+        }
+    }
+
+    /// Returns received command for the source without waiting
+    fn try_recv(&mut self) -> Option<Command> {
+        loop {
+            let receiver = self.receiver.as_mut()?;
+
+            match receiver.try_recv() {
+                Ok(command) if command.source_name() == self.source_name => return Some(command),
+                Ok(_) => {}
+                Err(TryRecvError::Lagged(count)) => {
+                    event!(
+                        Level::WARN,
+                        source = self.source_name,
+                        count,
+                        "Commands skipped"
+                    );
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Closed) => {
+                    self.receiver = None;
+
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Returns received outcome of a fetch without waiting
+    fn try_recv_outcome(&mut self) -> Option<FetchOutcome> {
+        let outcomes = self.outcomes.as_mut()?;
+
+        match outcomes.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(mpsc::error::TryRecvError::Empty) => None,
+            Err(mpsc::error::TryRecvError::Disconnected) => {
+                self.outcomes = None;
+
+                None
+            }
+        }
+    }
+
+    /// Returns settings of the source, if they're changed since the last check, without waiting
+    fn try_recv_settings(&mut self) -> Option<SourceSettings> {
+        let settings = self.settings.as_mut()?;
+
+        if !settings.has_changed().unwrap_or_default() {
+            return None;
+        }
+
+        let source_settings = settings.borrow_and_update().get(&self.source_name).cloned();
+        source_settings
+    }
+
+    fn apply_settings(&mut self, settings: SourceSettings) {
+        if settings.enabled != self.enabled
+            || settings.polling_interval != self.polling_interval
+            || settings.paused_genres != self.paused_genres
+        {
+            event!(
+                Level::INFO,
+                source = self.source_name,
+                ?settings,
+                "Applying settings"
+            );
+        }
+
+        self.enabled = settings.enabled;
+        self.polling_interval = settings.polling_interval;
+        self.paused_genres = settings.paused_genres;
+    }
+
+    fn apply(&mut self, command: Command, genres: &Genres) {
+        event!(
+            Level::INFO,
+            source = self.source_name,
+            ?command,
+            "Applying command"
+        );
+
+        match command {
+            Command::Pause { genre: None, .. } => self.enabled = false,
+            Command::Pause {
+                genre: Some(genre), ..
+            } => {
+                if !self.paused_genres.contains(&genre) {
+                    self.paused_genres.push(genre);
+                }
+            }
+            Command::Resume { genre: None, .. } => self.enabled = true,
+            Command::Resume {
+                genre: Some(genre), ..
+            } => self
+                .paused_genres
+                .retain(|paused_genre| *paused_genre != genre),
+            Command::Refresh {
+                genre: Some(genre), ..
+            } => {
+                self.refresh_genres.push_back(genre);
+                self.skip_wait = true;
+            }
+            Command::Refresh { genre: None, .. } => {
+                self.refresh_genres.extend(
+                    genres
+                        .iter()
+                        .filter(|genre| !self.paused_genres.contains(genre))
+                        .cloned(),
+                );
+                self.skip_wait = true;
+            }
+            Command::SetPollingInterval {
+                polling_interval, ..
+            } => self.polling_interval = polling_interval,
+        }
+    }
+
+    /// Backs off the genre if the fetch returned only duplicates, otherwise resets its backoff
+    fn apply_outcome(&mut self, outcome: FetchOutcome) {
+        let state = self.genre_states.entry(outcome.genre).or_default();
+
+        if outcome.new_media_count > 0 {
+            state.duplicate_fetches = 0;
+            state.backoff_until = None;
+
+            return;
+        }
+
+        state.duplicate_fetches = state.duplicate_fetches.saturating_add(1);
+
+        let backoff = DUPLICATES_INITIAL_BACKOFF
+            .saturating_mul(2_u32.saturating_pow(state.duplicate_fetches - 1))
+            .min(self.duplicates_max_backoff);
+
+        state.backoff_until = Some(Instant::now() + backoff);
+    }
+
+    /// Waits for a command, an outcome of a fetch, a change of settings or the stock or the deadline, whichever is first.
+    /// Received command, outcome or settings are applied.
+    /// The wait is interrupted after the heartbeat interval, so the schedule sends heartbeats while it waits.
+    async fn wait(&mut self, deadline: Option<Instant>, genres: &Genres) {
+        enum Event {
+            Command(Command),
+            Outcome(FetchOutcome),
+            Settings(SourceSettings),
+            Other,
+        }
+
+        let heartbeat_deadline = Instant::now() + HEARTBEAT_INTERVAL;
+        let deadline = deadline.map_or(heartbeat_deadline, |deadline| {
+            deadline.min(heartbeat_deadline)
+        });
+
+        let event = tokio::select! {
+            () = tokio_time::sleep_until(deadline) => Event::Other,
+            command = recv(&mut self.receiver, &self.source_name) => Event::Command(command),
+            outcome = recv_outcome(&mut self.outcomes) => Event::Outcome(outcome),
+            settings = recv_settings(&mut self.settings, &self.source_name) => Event::Settings(settings),
+            () = stock_changed(&mut self.stock) => Event::Other,
+        };
+
+        match event {
+            Event::Command(command) => self.apply(command, genres),
+            Event::Outcome(outcome) => self.apply_outcome(outcome),
+            Event::Settings(settings) => self.apply_settings(settings),
+            Event::Other => {}
+        }
+    }
+
+    /// Returns the genre with the lowest coverage of recent views by unviewed media,
+    /// which isn't paused, backed off or stocked up.
+    /// Genres with the same coverage are returned in turn.
+    fn next_scheduled_genre(&self, genres: &Genres, now: Instant) -> Option<Genre> {
+        let stock = self.stock.as_ref().map(|stock| stock.borrow());
+
+        genres
+            .iter()
+            .filter(|genre| !self.paused_genres.contains(genre))
+            .filter(|genre| {
+                self.genre_states
+                    .get(genre)
+                    .and_then(|state| state.backoff_until)
+                    .map_or(true, |backoff_until| backoff_until <= now)
+            })
+            .map(|genre| {
+                let genre_stock = stock
+                    .as_ref()
+                    .and_then(|stock| stock.get(genre))
+                    .copied()
+                    .unwrap_or_default();
+                let last_fetch_number = self
+                    .genre_states
+                    .get(genre)
+                    .and_then(|state| state.last_fetch_number);
+
+                (
+                    genre,
+                    genre_stock.unviewed(),
+                    genre_stock.recent_views,
+                    last_fetch_number,
+                )
+            })
+            .filter(|(_, unviewed, _, _)| {
+                self.stock_target
+                    .map_or(true, |stock_target| *unviewed < stock_target)
+            })
+            .min_by(|a, b| cmp_coverage((a.1, a.2), (b.1, b.2)).then_with(|| a.3.cmp(&b.3)))
+            .map(|(genre, _, _, _)| genre.clone())
+    }
+
+    /// Returns the earliest time a backed off genre can be fetched
+    fn next_backoff_end(&self, genres: &Genres) -> Option<Instant> {
+        genres
+            .iter()
+            .filter_map(|genre| self.genre_states.get(genre)?.backoff_until)
+            .min()
+    }
+
+    /// Waits for the next genre to fetch and returns it.
+    /// Commands are applied while waiting, so genres of paused sources and paused genres aren't returned.
+    /// Refreshed genres are returned before the scheduled ones regardless of their stock and backoff,
+    /// and the first of them without waiting for the polling interval.
+    pub async fn next_genre(&mut self, genres: &Genres) -> Genre {
+        loop {
+            event!(
+                target: HEARTBEAT_TARGET,
+                Level::TRACE,
+                source = self.source_name,
+                "Schedule is alive",
+            );
+
+            // Settings are applied before commands, because commands are newer than saved settings
+            if let Some(settings) = self.try_recv_settings() {
+                self.apply_settings(settings);
+            }
+            while let Some(command) = self.try_recv() {
+                self.apply(command, genres);
+            }
+            while let Some(outcome) = self.try_recv_outcome() {
+                self.apply_outcome(outcome);
+            }
+
+            if !self.enabled {
+                self.wait(None, genres).await;
+
+                continue;
+            }
+
+            let now = Instant::now();
+
+            if let Some(last_fetch_at) = self.last_fetch_at.filter(|_| !self.skip_wait) {
+                let next_fetch_at = last_fetch_at + self.polling_interval;
+
+                if next_fetch_at > now {
+                    self.wait(Some(next_fetch_at), genres).await;
+
+                    continue;
+                }
+            }
+
+            let genre = if let Some(genre) = self.refresh_genres.pop_front() {
+                // Genres of the source can be changed after the refresh command
+                if !genres.contains(&genre) {
+                    continue;
+                }
+
+                genre
+            } else if let Some(genre) = self.next_scheduled_genre(genres, now) {
+                genre
+            } else {
+                // All genres are paused, backed off or stocked up
+                let deadline = self.next_backoff_end(genres);
+
+                self.wait(deadline, genres).await;
+
+                continue;
+            };
+
+            self.last_fetch_at = Some(now);
+            self.skip_wait = false;
+            self.genre_states
+                .entry(genre.clone())
+                .or_default()
+                .last_fetch_number = Some(self.fetch_count);
+            self.fetch_count += 1;
+
+            return genre;
+        }
+    }
+}
+
+/// Compares coverage of recent views by unviewed media, that is `unviewed / (recent_views + 1)`
+fn cmp_coverage(
+    (unviewed, recent_views): (i64, i64),
+    (other_unviewed, other_recent_views): (i64, i64),
+) -> Ordering {
+    (i128::from(unviewed) * (i128::from(other_recent_views) + 1))
+        .cmp(&(i128::from(other_unviewed) * (i128::from(recent_views) + 1)))
+}
+
+/// Waits for the next command for the source.
+/// If there is no controller or it's dropped, it never returns.
+async fn recv(receiver: &mut Option<broadcast::Receiver<Command>>, source_name: &str) -> Command {
+    loop {
+        let Some(ref mut inner) = receiver else {
+            return future::pending().await;
+        };
+
+        match inner.recv().await {
+            Ok(command) if command.source_name() == source_name => return command,
+            Ok(_) => {}
+            Err(RecvError::Lagged(count)) => {
+                event!(Level::WARN, source = source_name, count, "Commands skipped");
+            }
+            Err(RecvError::Closed) => *receiver = None,
+        }
+    }
+}
+
+/// Waits for the next outcome of a fetch.
+/// If there are no outcomes or their sender is dropped, it never returns.
+async fn recv_outcome(
+    outcomes: &mut Option<mpsc::UnboundedReceiver<FetchOutcome>>,
+) -> FetchOutcome {
+    let Some(ref mut inner) = outcomes else {
+        return future::pending().await;
+    };
+
+    if let Some(outcome) = inner.recv().await {
+        return outcome;
+    }
+
+    *outcomes = None;
+
+    future::pending().await
+}
+
+/// Waits for a change of settings of the source and returns them.
+/// If there are no settings or they aren't updated anymore, it never returns.
+async fn recv_settings(
+    settings: &mut Option<watch::Receiver<SourcesSettings>>,
+    source_name: &str,
+) -> SourceSettings {
+    if let Some(inner) = settings {
+        while inner.changed().await.is_ok() {
+            if let Some(source_settings) = inner.borrow_and_update().get(source_name) {
+                return source_settings.clone();
+            }
+        }
+    }
+
+    future::pending().await
+}
+
+/// Waits for a change of the stock.
+/// If there is no stock or it isn't updated anymore, it never returns.
+async fn stock_changed(stock: &mut Option<watch::Receiver<Stock>>) {
+    if let Some(stock) = stock {
+        if stock.changed().await.is_ok() {
+            return;
+        }
+    }
+
+    future::pending::<()>().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FetchOutcome, Schedule};
+
+    use crate::{
+        domain::{
+            media::entities::{GenreStats, GenresStats},
+            media_parser::entities::{Genre, Genres},
+            source::entities::{Source, SourcePausedGenre},
+        },
+        infrastructure::media_parser::{control::Command, Controller, SourcesSettings, Stock},
+    };
+
+    use std::time::Duration;
+    use time::OffsetDateTime;
+    use tokio::sync::{mpsc, watch};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_schedule() {
+        let genres = Genres::new(vec![
+            Genre::new_sfw_image("a"),
+            Genre::new_sfw_image("b"),
+            Genre::new_sfw_image("c"),
+        ]);
+
+        let controller = Controller::default();
+        let mut schedule = Schedule::new("test", true, Duration::ZERO).with_controller(&controller);
+
+        assert!(controller.send(Command::Pause {
+            source_name: "test".to_owned(),
+            genre: Some(genres[1].clone()),
+        }));
+        // Commands for other sources are ignored
+        assert!(controller.send(Command::Pause {
+            source_name: "other".to_owned(),
+            genre: None,
+        }));
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[2]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+
+        controller.send(Command::Refresh {
+            source_name: "test".to_owned(),
+            genre: Some(genres[1].clone()),
+        });
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[2]);
+
+        controller.send(Command::Pause {
+            source_name: "test".to_owned(),
+            genre: None,
+        });
+        controller.send(Command::Resume {
+            source_name: "test".to_owned(),
+            genre: Some(genres[1].clone()),
+        });
+        controller.send(Command::Resume {
+            source_name: "test".to_owned(),
+            genre: None,
+        });
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_by_settings() {
+        let genres = Genres::new(vec![Genre::new_sfw_image("a"), Genre::new_sfw_image("b")]);
+        let sources_settings = |enabled, paused_genres: &[&str]| {
+            SourcesSettings::new(
+                vec![Source {
+                    id: Uuid::new_v4(),
+                    name: "test".to_owned(),
+                    url: "https://example.com".to_owned(),
+                    enabled,
+                    polling_interval_ms: 0,
+                    created: OffsetDateTime::now_utc(),
+                }],
+                paused_genres
+                    .iter()
+                    .map(|genre| SourcePausedGenre {
+                        source_name: "test".to_owned(),
+                        genre: (*genre).to_owned(),
+                        media_type: "img".to_owned(),
+                        age_restriction: "sfw".to_owned(),
+                    })
+                    .collect(),
+            )
+        };
+
+        let (settings_sender, settings) = watch::channel(sources_settings(true, &["a"]));
+
+        // The source is enabled by settings, which are saved after the schedule is created
+        let mut schedule =
+            Schedule::new("test", false, Duration::from_secs(3600)).with_settings(settings);
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+
+        settings_sender
+            .send(sources_settings(true, &["b"]))
+            .unwrap();
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+
+        settings_sender.send(sources_settings(false, &[])).unwrap();
+
+        let next_genre =
+            tokio::time::timeout(Duration::from_millis(50), schedule.next_genre(&genres));
+        assert!(next_genre.await.is_err());
+
+        settings_sender.send(sources_settings(true, &[])).unwrap();
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_by_stock() {
+        let genres = Genres::new(vec![
+            Genre::new_sfw_image("a"),
+            Genre::new_sfw_image("b"),
+            Genre::new_sfw_image("c"),
+        ]);
+        let genre_stats = |genre: &str, total| GenreStats {
+            total,
+            genre: genre.to_owned(),
+            media_type: "img".to_owned(),
+            age_restriction: "sfw".to_owned(),
+        };
+
+        let (_stock_sender, stock) = watch::channel(Stock::new(
+            GenresStats(vec![
+                genre_stats("a", 50),
+                genre_stats("b", 5),
+                genre_stats("c", 100),
+            ]),
+            vec![],
+        ));
+        let (outcomes_sender, outcomes) = mpsc::unbounded_channel();
+
+        let mut schedule = Schedule::new("test", true, Duration::ZERO)
+            .with_stock(stock, Some(100))
+            .with_outcomes(outcomes, Duration::from_secs(3600));
+
+        // Genre `c` is stocked up, so only `b` with the lowest stock and `a` are fetched
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+
+        outcomes_sender
+            .send(FetchOutcome {
+                genre: genres[1].clone(),
+                new_media_count: 0,
+            })
+            .unwrap();
+
+        // Genre `b` returned only duplicates, so it's backed off
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+        assert_eq!(schedule.next_genre(&genres).await, genres[0]);
+
+        outcomes_sender
+            .send(FetchOutcome {
+                genre: genres[1].clone(),
+                new_media_count: 1,
+            })
+            .unwrap();
+
+        assert_eq!(schedule.next_genre(&genres).await, genres[1]);
+    }
+}
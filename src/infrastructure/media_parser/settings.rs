@@ -0,0 +1,222 @@
+use crate::{
+    application::common::traits::{UnitOfWork as _, UnitOfWorkFactory},
+    domain::{
+        media_parser::{
+            entities::Genre,
+            value_objects::{AgeRestriction, MediaType},
+        },
+        source::entities::{Source, SourcePausedGenre},
+    },
+};
+
+use std::{collections::HashMap, time::Duration};
+use tokio::{sync::watch, time as tokio_time};
+use tracing::{event, instrument, Level};
+
+/// Time between updates of settings of sources, so changes by commands of admins
+/// are applied by workers in other processes
+pub const SETTINGS_POLLING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Settings of a source, which are changed by commands of admins and saved in the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSettings {
+    pub enabled: bool,
+    pub polling_interval: Duration,
+    pub paused_genres: Vec<Genre>,
+}
+
+/// Settings of all sources, which are known by the database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourcesSettings {
+    sources: HashMap<String, SourceSettings>,
+}
+
+impl SourcesSettings {
+    /// Combines sources with their paused genres.
+    /// Sources with the same name are updated together, so settings of the first one are used.
+    /// Paused genres with unknown media type or age restriction are skipped.
+    pub fn new(sources: Vec<Source>, paused_genres: Vec<SourcePausedGenre>) -> Self {
+        let mut settings = HashMap::new();
+
+        for source in sources {
+            let polling_interval = source.polling_interval();
+
+            settings
+                .entry(source.name)
+                .or_insert_with(|| SourceSettings {
+                    enabled: source.enabled,
+                    polling_interval,
+                    paused_genres: vec![],
+                });
+        }
+
+        for paused_genre in paused_genres {
+            let Some(source_settings) = settings.get_mut(&paused_genre.source_name) else {
+                continue;
+            };
+
+            let (Ok(media_type), Ok(age_restriction)) = (
+                MediaType::try_from(paused_genre.media_type.as_str()),
+                AgeRestriction::try_from(paused_genre.age_restriction.as_str()),
+            ) else {
+                event!(
+                    Level::WARN,
+                    source = paused_genre.source_name,
+                    genre = paused_genre.genre,
+                    media_type = paused_genre.media_type,
+                    age_restriction = paused_genre.age_restriction,
+                    "Unknown paused genre, it's skipped"
+                );
+
+                continue;
+            };
+
+            source_settings.paused_genres.push(Genre::new(
+                paused_genre.genre,
+                media_type,
+                age_restriction,
+            ));
+        }
+
+        Self { sources: settings }
+    }
+
+    /// Returns settings of the source or `None` if the source isn't in the database
+    pub fn get(&self, source_name: &str) -> Option<&SourceSettings> {
+        self.sources.get(source_name)
+    }
+}
+
+/// Gets settings of sources from the database
+async fn get_settings<UoWFactory>(uow_factory: &UoWFactory) -> Result<SourcesSettings, String>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let sources = uow
+        .source_reader()
+        .await
+        .map_err(|err| err.to_string())?
+        .get_all()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let paused_genres = uow
+        .source_reader()
+        .await
+        .map_err(|err| err.to_string())?
+        .get_paused_genres()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(SourcesSettings::new(sources, paused_genres))
+}
+
+/// Run polling of settings of sources and send them to schedules of workers.
+/// Schedules are notified only when settings are changed,
+/// so commands, which are applied by schedules before they're saved, aren't reverted.
+/// # Arguments
+/// * `uow_factory` - Unit of work factory.
+/// * `sender` - Sender of the settings to schedules.
+/// * `interval` - Time between updates of the settings.
+#[instrument(skip_all)]
+pub async fn run_settings_polling<UoWFactory>(
+    uow_factory: UoWFactory,
+    sender: watch::Sender<SourcesSettings>,
+    interval: Duration,
+) where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut interval = tokio_time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            () = sender.closed() => {
+                event!(Level::DEBUG, "Schedules are dropped, stop settings polling");
+
+                return;
+            }
+        }
+
+        match get_settings(&uow_factory).await {
+            Ok(settings) => {
+                sender.send_if_modified(|current| {
+                    if *current == settings {
+                        return false;
+                    }
+
+                    event!(Level::DEBUG, ?settings, "Updating settings of sources");
+
+                    *current = settings;
+
+                    true
+                });
+            }
+            Err(err) => {
+                event!(Level::ERROR, err, "Failed to get settings of sources");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceSettings, SourcesSettings};
+
+    use crate::domain::{
+        media_parser::entities::Genre,
+        source::entities::{Source, SourcePausedGenre},
+    };
+
+    use std::time::Duration;
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_sources_settings() {
+        let source = |name: &str, enabled| Source {
+            id: Uuid::new_v4(),
+            name: name.to_owned(),
+            url: "https://example.com".to_owned(),
+            enabled,
+            polling_interval_ms: 2500,
+            created: OffsetDateTime::now_utc(),
+        };
+        let paused_genre = |source_name: &str, media_type: &str| SourcePausedGenre {
+            source_name: source_name.to_owned(),
+            genre: "neko".to_owned(),
+            media_type: media_type.to_owned(),
+            age_restriction: "sfw".to_owned(),
+        };
+
+        let settings = SourcesSettings::new(
+            vec![source("a", false), source("b", true), source("a", true)],
+            vec![
+                paused_genre("a", "img"),
+                // Genres of unknown sources and with unknown media types are skipped
+                paused_genre("c", "img"),
+                paused_genre("a", "test"),
+            ],
+        );
+
+        assert_eq!(
+            settings.get("a"),
+            Some(&SourceSettings {
+                enabled: false,
+                polling_interval: Duration::from_millis(2500),
+                paused_genres: vec![Genre::new_sfw_image("neko")],
+            }),
+        );
+        assert_eq!(
+            settings.get("b"),
+            Some(&SourceSettings {
+                enabled: true,
+                polling_interval: Duration::from_millis(2500),
+                paused_genres: vec![],
+            }),
+        );
+        assert_eq!(settings.get("c"), None);
+    }
+}
@@ -0,0 +1,320 @@
+use super::{NekosBest, WaifuPics};
+use crate::{
+    application::media_parser::traits::Source,
+    domain::media_parser::entities::{Genre, Genres},
+};
+
+use serde::Deserialize;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{
+    fs,
+    sync::watch,
+    time::{self as tokio_time, MissedTickBehavior},
+};
+use tracing::{event, instrument, Level};
+
+/// Config of genres of a source.
+/// If genres aren't set, the default genres of the source are used.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceConfig {
+    pub name: String,
+    /// Genres in the same format as commands: `{name}_{media_type}_{age_restriction}`, for example, `neko_img_sfw`
+    pub genres: Option<Vec<String>>,
+}
+
+/// Config of sources, which is read from a TOML file, for example:
+/// ```toml
+/// [[sources]]
+/// name = "api.waifu.pics"
+/// genres = ["neko_img_sfw", "hug_gif_sfw", "waifu_img_nsfw"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourcesConfig {
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error("sources config path isn't set")]
+    PathNotSet,
+    #[error("error reading sources config {path}: {source}")]
+    Io { source: io::Error, path: PathBuf },
+    #[error("error parsing sources config: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("unknown source `{0}`")]
+    UnknownSource(String),
+    #[error("invalid genre `{genre}` of source `{source_name}`: {message}")]
+    InvalidGenre {
+        source_name: String,
+        genre: String,
+        message: String,
+    },
+}
+
+impl SourcesConfig {
+    /// Parses the config from TOML
+    /// # Errors
+    /// Returns error if the content isn't valid TOML or doesn't match the config structure
+    pub fn from_toml(content: &str) -> Result<Self, ErrorKind> {
+        toml::from_str(content).map_err(Into::into)
+    }
+
+    /// Returns genres of the source, if they're set in the config
+    /// # Errors
+    /// Returns error if a genre can't be parsed
+    fn genres(&self, source_name: &str) -> Result<Option<Genres>, ErrorKind> {
+        let Some(genres) = self
+            .sources
+            .iter()
+            .find(|source| source.name == source_name)
+            .and_then(|source| source.genres.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        genres
+            .iter()
+            .map(|genre| {
+                Genre::try_from(genre.as_str()).map_err(|err| ErrorKind::InvalidGenre {
+                    source_name: source_name.to_owned(),
+                    genre: genre.clone(),
+                    message: err.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(|genres| Some(Genres::new(genres)))
+    }
+}
+
+/// Set of media parser sources with genres from the config
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    pub nekos_best: NekosBest,
+    pub waifu_pics: WaifuPics,
+}
+
+impl Sources {
+    /// Creates sources with genres from the config.
+    /// Sources, which aren't in the config, use their default genres.
+    /// # Errors
+    /// Returns error if the config contains unknown sources or invalid genres
+    pub fn from_config(config: &SourcesConfig) -> Result<Self, ErrorKind> {
+        let mut sources = Self::default();
+
+        if let Some(source) = config.sources.iter().find(|source| {
+            source.name != sources.nekos_best.name() && source.name != sources.waifu_pics.name()
+        }) {
+            return Err(ErrorKind::UnknownSource(source.name.clone()));
+        }
+
+        if let Some(genres) = config.genres(sources.nekos_best.name())? {
+            sources.nekos_best = sources.nekos_best.with_genres(genres);
+        }
+        if let Some(genres) = config.genres(sources.waifu_pics.name())? {
+            sources.waifu_pics = sources.waifu_pics.with_genres(genres);
+        }
+
+        Ok(sources)
+    }
+
+    /// Returns all sources
+    pub fn all(&self) -> Vec<Arc<dyn Source>> {
+        vec![
+            Arc::new(self.nekos_best.clone()),
+            Arc::new(self.waifu_pics.clone()),
+        ]
+    }
+
+    /// Returns genres of all sources
+    pub fn genres(&self) -> Vec<&Genre> {
+        self.nekos_best
+            .genres()
+            .iter()
+            .chain(self.waifu_pics.genres().iter())
+            .collect()
+    }
+}
+
+/// Reads the sources config and publishes sources with genres from it to subscribers, for example, workers.
+/// If the config path isn't set, sources with default genres are used.
+pub struct SourcesReloader {
+    path: Option<PathBuf>,
+    sender: watch::Sender<Sources>,
+}
+
+impl SourcesReloader {
+    /// Creates the reloader and reads the config, if its path is set
+    /// # Errors
+    /// Returns error if the config can't be read or is invalid
+    pub async fn new(path: Option<PathBuf>) -> Result<Self, ErrorKind> {
+        let sources = match path {
+            Some(ref path) => read_sources(path).await?,
+            None => Sources::default(),
+        };
+
+        Ok(Self {
+            path,
+            sender: watch::Sender::new(sources),
+        })
+    }
+
+    /// Returns the current sources
+    pub fn sources(&self) -> Sources {
+        self.sender.borrow().clone()
+    }
+
+    /// Returns receiver, which is notified when sources are reloaded
+    pub fn subscribe(&self) -> watch::Receiver<Sources> {
+        self.sender.subscribe()
+    }
+
+    /// Reads the config again and publishes new sources.
+    /// If the config is invalid, the current sources are kept.
+    /// # Errors
+    /// Returns error if the config path isn't set or the config can't be read or is invalid
+    #[instrument(skip_all)]
+    pub async fn reload(&self) -> Result<Sources, ErrorKind> {
+        let Some(ref path) = self.path else {
+            return Err(ErrorKind::PathNotSet);
+        };
+
+        let sources = read_sources(path).await?;
+
+        self.sender.send_replace(sources.clone());
+
+        event!(Level::INFO, "Sources reloaded");
+
+        Ok(sources)
+    }
+}
+
+async fn read_sources(path: &Path) -> Result<Sources, ErrorKind> {
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|err| ErrorKind::Io {
+            source: err,
+            path: path.to_owned(),
+        })?;
+
+    Sources::from_config(&SourcesConfig::from_toml(&content)?)
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Reloads sources when the config file is modified.
+/// Modification time of the file is checked with the interval, so it works with mounted config maps and volumes.
+/// Does nothing if the config path isn't set.
+#[instrument(skip_all)]
+pub async fn run_watching(reloader: Arc<SourcesReloader>, interval: Duration) {
+    let Some(ref path) = reloader.path else {
+        return;
+    };
+
+    let mut last_modified = modified(path).await;
+
+    let mut interval = tokio_time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let modified = modified(path).await;
+
+        if modified == last_modified {
+            continue;
+        }
+
+        last_modified = modified;
+
+        event!(Level::DEBUG, "Sources config modified");
+
+        if let Err(err) = reloader.reload().await {
+            event!(Level::ERROR, %err, "Error reloading sources. Previous sources are kept");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorKind, Sources, SourcesConfig};
+
+    use crate::{
+        application::media_parser::traits::Source as _,
+        infrastructure::media_parser::{nekos_best, waifu_pics},
+    };
+
+    #[test]
+    fn test_sources_from_config() {
+        let sources = Sources::from_config(&SourcesConfig::default()).unwrap();
+
+        assert_eq!(sources.nekos_best.genres(), nekos_best::default_genres());
+        assert_eq!(sources.waifu_pics.genres(), waifu_pics::default_genres());
+
+        let config = SourcesConfig::from_toml(
+            r#"
+            [[sources]]
+            name = "api.waifu.pics"
+            genres = ["neko_img_sfw", "hug_gif_nsfw"]
+
+            [[sources]]
+            name = "nekos.best.v2"
+            "#,
+        )
+        .unwrap();
+        let sources = Sources::from_config(&config).unwrap();
+
+        assert_eq!(
+            sources
+                .waifu_pics
+                .genres()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["neko_img_sfw", "hug_gif_nsfw"]
+        );
+        assert_eq!(sources.nekos_best.genres(), nekos_best::default_genres());
+    }
+
+    #[test]
+    fn test_sources_from_invalid_config() {
+        let config = SourcesConfig::from_toml(
+            r#"
+            [[sources]]
+            name = "unknown"
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Sources::from_config(&config),
+            Err(ErrorKind::UnknownSource(name)) if name == "unknown"
+        ));
+
+        let config = SourcesConfig::from_toml(
+            r#"
+            [[sources]]
+            name = "api.waifu.pics"
+            genres = ["neko_test_sfw"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Sources::from_config(&config),
+            Err(ErrorKind::InvalidGenre { genre, .. }) if genre == "neko_test_sfw"
+        ));
+
+        assert!(SourcesConfig::from_toml("[[sources]]\nname = 1").is_err());
+        assert!(SourcesConfig::from_toml("[[sources]]\nname = \"a\"\nenabled = true").is_err());
+    }
+}
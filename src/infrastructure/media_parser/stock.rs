@@ -0,0 +1,217 @@
+use crate::{
+    application::{
+        common::traits::{UnitOfWork as _, UnitOfWorkFactory},
+        user_media_view::dto::GetUserMediaViewGenreViewStats,
+    },
+    domain::{
+        media::entities::GenresStats, media_parser::entities::Genre,
+        user_media_view::entities::GenreViewStats,
+    },
+};
+
+use std::{collections::HashMap, time::Duration};
+use time::OffsetDateTime;
+use tokio::{sync::watch, time as tokio_time};
+use tracing::{event, instrument, Level};
+
+/// Stock of media of a genre
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GenreStock {
+    /// Count of active media of the genre
+    pub total: i64,
+    /// Max count of media of the genre viewed by one active user
+    pub max_viewed: i64,
+    /// Count of views of media of the genre by active users since they're active
+    pub recent_views: i64,
+}
+
+impl GenreStock {
+    /// Returns count of media of the genre, which aren't viewed by any active user yet.
+    /// It's the pool, which is left for the user, who views the genre the most.
+    pub const fn unviewed(&self) -> i64 {
+        let unviewed = self.total - self.max_viewed;
+
+        if unviewed > 0 {
+            unviewed
+        } else {
+            0
+        }
+    }
+}
+
+/// Stock of media of all genres, which are known by the database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stock {
+    genres: HashMap<(String, String, String), GenreStock>,
+}
+
+impl Stock {
+    /// Combines count of media of genres with views of them by active users
+    pub fn new(genres_stats: GenresStats, genres_view_stats: Vec<GenreViewStats>) -> Self {
+        let mut genres = HashMap::new();
+
+        for genre_stats in genres_stats.0 {
+            genres
+                .entry((
+                    genre_stats.genre,
+                    genre_stats.media_type,
+                    genre_stats.age_restriction,
+                ))
+                .or_insert_with(GenreStock::default)
+                .total += genre_stats.total;
+        }
+
+        for genre_view_stats in genres_view_stats {
+            let genre_stock = genres
+                .entry((
+                    genre_view_stats.genre,
+                    genre_view_stats.media_type,
+                    genre_view_stats.age_restriction,
+                ))
+                .or_insert_with(GenreStock::default);
+
+            genre_stock.max_viewed = genre_view_stats.max_viewed;
+            genre_stock.recent_views = genre_view_stats.recent_views;
+        }
+
+        Self { genres }
+    }
+
+    /// Returns stock of the genre or `None` if there are no media of the genre in the database
+    pub fn get(&self, genre: &Genre) -> Option<&GenreStock> {
+        self.genres.get(&(
+            genre.name().to_owned(),
+            genre.media_type().as_str().to_owned(),
+            genre.age_restriction().as_str().to_owned(),
+        ))
+    }
+}
+
+/// Gets the stock from the database
+async fn get_stock<UoWFactory>(
+    uow_factory: &UoWFactory,
+    active_users_period: Duration,
+) -> Result<Stock, String>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let active_since = OffsetDateTime::now_utc() - active_users_period;
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    let genres_stats = uow
+        .media_reader()
+        .await
+        .map_err(|err| err.to_string())?
+        .get_genre_stats()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let genres_view_stats = uow
+        .user_media_view_reader()
+        .await
+        .map_err(|err| err.to_string())?
+        .get_genre_view_stats(GetUserMediaViewGenreViewStats::new(&active_since))
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(Stock::new(genres_stats, genres_view_stats))
+}
+
+/// Run polling of the stock of media and send it to schedules of workers
+/// # Arguments
+/// * `uow_factory` - Unit of work factory.
+/// * `sender` - Sender of the stock to schedules.
+/// * `active_users_period` - Users, who viewed media during this period, are active.
+/// * `interval` - Time between updates of the stock.
+#[instrument(skip_all)]
+pub async fn run_stock_polling<UoWFactory>(
+    uow_factory: UoWFactory,
+    sender: watch::Sender<Stock>,
+    active_users_period: Duration,
+    interval: Duration,
+) where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut interval = tokio_time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            () = sender.closed() => {
+                event!(Level::DEBUG, "Schedules are dropped, stop stock polling");
+
+                return;
+            }
+        }
+
+        match get_stock(&uow_factory, active_users_period).await {
+            Ok(stock) => {
+                event!(Level::TRACE, ?stock, "Updating stock");
+
+                if sender.send(stock).is_err() {
+                    event!(Level::DEBUG, "Schedules are dropped, stop stock polling");
+
+                    return;
+                }
+            }
+            Err(err) => {
+                event!(Level::ERROR, err, "Failed to get stock");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GenreStock, Stock};
+
+    use crate::domain::{
+        media::entities::{GenreStats, GenresStats},
+        media_parser::entities::Genre,
+        user_media_view::entities::GenreViewStats,
+    };
+
+    #[test]
+    fn test_stock() {
+        let stock = Stock::new(
+            GenresStats(vec![
+                GenreStats {
+                    total: 10,
+                    genre: "a".to_owned(),
+                    media_type: "img".to_owned(),
+                    age_restriction: "sfw".to_owned(),
+                },
+                GenreStats {
+                    total: 3,
+                    genre: "b".to_owned(),
+                    media_type: "img".to_owned(),
+                    age_restriction: "sfw".to_owned(),
+                },
+            ]),
+            vec![GenreViewStats {
+                genre: "b".to_owned(),
+                media_type: "img".to_owned(),
+                age_restriction: "sfw".to_owned(),
+                max_viewed: 5,
+                recent_views: 8,
+            }],
+        );
+
+        let genre_a = Genre::new_sfw_image("a");
+        let genre_b = Genre::new_sfw_image("b");
+
+        assert_eq!(
+            stock.get(&genre_a),
+            Some(&GenreStock {
+                total: 10,
+                max_viewed: 0,
+                recent_views: 0,
+            }),
+        );
+        assert_eq!(stock.get(&genre_a).unwrap().unviewed(), 10);
+        // Media can be viewed and deleted after that
+        assert_eq!(stock.get(&genre_b).unwrap().unviewed(), 0);
+        assert_eq!(stock.get(&Genre::new_sfw_gif("a")), None);
+    }
+}
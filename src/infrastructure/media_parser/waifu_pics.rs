@@ -16,9 +16,32 @@ use serde::Deserialize;
 use std::{borrow::Cow, vec};
 use tracing::{event, instrument, Level};
 
+/// Returns genres, which are provided by the source by default.
+/// They're used if genres of the source aren't set in the sources config.
+pub fn default_genres() -> &'static Genres {
+    lazy_static! {
+        static ref GENRES: Genres = Genres::new(
+            [
+                vec_new_sfw_gif![
+                    "bully", "cuddle", "cry", "hug", "kiss", "lick", "pat", "smug", "bonk", "yeet",
+                    "blush", "smile", "wave", "nom", "bite", "glomp", "slap", "kill", "kick",
+                    "happy", "wink", "poke", "dance", "cringe",
+                ],
+                vec_new_sfw_image!["waifu", "neko", "shinobu", "megumin", "awoo",],
+                vec_new_nsfw_gif!["blowjob"],
+                vec_new_nsfw_image!["waifu", "neko", "trap"],
+            ]
+            .concat()
+        );
+    }
+
+    &GENRES
+}
+
 #[derive(Debug, Clone)]
 pub struct WaifuPics<Client = reqwest::Client> {
     url: Cow<'static, str>,
+    genres: Genres,
     exclude_urls: Vec<Cow<'static, str>>,
     client: Client,
 }
@@ -34,10 +57,17 @@ impl<Client> WaifuPics<Client> {
         Self {
             client,
             exclude_urls: Vec::new(),
+            genres: default_genres().clone(),
             url: "https://api.waifu.pics".into(),
         }
     }
 
+    /// Set the genres to parse instead of the default ones
+    #[must_use]
+    pub fn with_genres(self, genres: Genres) -> Self {
+        Self { genres, ..self }
+    }
+
     /// Set the exclude url with a mutable reference
     pub fn exclude_url(&mut self, exclude_url: impl Into<Cow<'static, str>>) {
         self.exclude_urls.push(exclude_url.into());
@@ -66,23 +96,7 @@ impl Source for WaifuPics<reqwest::Client> {
     }
 
     fn genres(&self) -> &Genres {
-        lazy_static! {
-            static ref GENRES: Genres = Genres::new(
-                [
-                    vec_new_sfw_gif![
-                        "bully", "cuddle", "cry", "hug", "kiss", "lick", "pat", "smug", "bonk",
-                        "yeet", "blush", "smile", "wave", "nom", "bite", "glomp", "slap", "kill",
-                        "kick", "happy", "wink", "poke", "dance", "cringe",
-                    ],
-                    vec_new_sfw_image!["waifu", "neko", "shinobu", "megumin", "awoo",],
-                    vec_new_nsfw_gif!["blowjob"],
-                    vec_new_nsfw_image!["waifu", "neko", "trap"],
-                ]
-                .concat()
-            );
-        }
-
-        &GENRES
+        &self.genres
     }
 
     #[instrument(skip(self))]
@@ -93,7 +107,7 @@ impl Source for WaifuPics<reqwest::Client> {
         let age_restriction = match genre.age_restriction() {
             AgeRestriction::Sfw => "sfw",
             AgeRestriction::Nsfw => "nsfw",
-            AgeRestriction::Unknown => {
+            AgeRestriction::Questionable | AgeRestriction::Unknown => {
                 return Err(MediaGetException::new(
                     genre.clone(),
                     "only SFW/NSFW restrictions are valid",
@@ -7,12 +7,17 @@ use crate::{
         media::dto::CreateMedia,
         media_parser::traits::{Source, Worker},
         source::{
-            dto::{CreateSource, GetSourceByNameAndUrl},
+            dto::{CreateSource, GetSourceByNameAndUrl, RecordSourceFetchRun},
             exceptions::{SourceNameAndUrlAlreadyExists, SourceNameAndUrlNotExist},
         },
     },
-    domain::media_parser::entities::Media,
-    infrastructure::media_parser::{NekosBest, WaifuPics},
+    domain::media_parser::entities::{FetchResult, Genres, Media},
+    infrastructure::media_parser::{
+        settings::{run_settings_polling, SETTINGS_POLLING_INTERVAL},
+        stock::run_stock_polling,
+        Controller, FetchOutcome, NekosBest, Schedule, Scheduling, Sources, SourcesSettings, Stock,
+        WaifuPics,
+    },
 };
 
 use async_trait::async_trait;
@@ -21,20 +26,52 @@ use backoff::{
     exponential::{ExponentialBackoff, ExponentialBackoffBuilder},
     SystemClock,
 };
-use std::{borrow::Cow, time::Duration};
+use metrics::{counter, histogram};
+use std::{borrow::Cow, collections::HashSet, future, time::Duration};
 use time::OffsetDateTime;
 use tokio::{
-    sync::mpsc::{channel as tokio_mpsc_channel, Receiver},
+    sync::{
+        mpsc::{self, channel as tokio_mpsc_channel, Receiver},
+        watch,
+    },
     time as tokio_time,
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
+/// Max count of buffered media, after which they are saved
+const MEDIA_BATCH_SIZE: usize = 500;
+/// Max time media are buffered before they are saved
+const MEDIA_BATCH_INTERVAL: Duration = Duration::from_secs(5);
+/// Time between fetches of genres, if the schedule isn't set
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(3);
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug)]
 pub struct WorkerManager {
     channel_buffer: usize,
     backoff: ExponentialBackoff<SystemClock>,
+    schedule: Option<Schedule>,
+    shutdown: CancellationToken,
+}
+
+impl WorkerManager {
+    /// Set the schedule of fetches of genres of the source
+    #[must_use]
+    pub fn with_schedule(self, schedule: Schedule) -> Self {
+        Self {
+            schedule: Some(schedule),
+            ..self
+        }
+    }
+
+    /// Stop parsing, when the token is cancelled.
+    /// Media, which are already parsed, are still sent to the channel.
+    #[must_use]
+    pub fn with_shutdown(self, shutdown: CancellationToken) -> Self {
+        Self { shutdown, ..self }
+    }
 }
 
 impl Default for WorkerManager {
@@ -47,15 +84,23 @@ impl Default for WorkerManager {
                 .with_max_interval(Duration::from_secs(120))
                 .with_max_elapsed_time(Some(Duration::from_secs(3600)))
                 .build(),
+            schedule: None,
+            shutdown: CancellationToken::new(),
         }
     }
 }
 
 #[async_trait]
 impl Worker<NekosBest<reqwest::Client>> for WorkerManager {
-    async fn parse(mut self, source: NekosBest<reqwest::Client>) -> Receiver<Media> {
+    #[allow(clippy::too_many_lines)]
+    async fn parse(mut self, source: NekosBest<reqwest::Client>) -> Receiver<FetchResult> {
         let (sender, receiver) = tokio_mpsc_channel(self.channel_buffer);
 
+        let mut schedule = self
+            .schedule
+            .take()
+            .unwrap_or_else(|| Schedule::new(source.name(), true, DEFAULT_POLLING_INTERVAL));
+
         tokio::spawn(async move {
             let genres = source.genres();
 
@@ -64,68 +109,122 @@ impl Worker<NekosBest<reqwest::Client>> for WorkerManager {
             self.backoff.reset();
 
             loop {
-                for genre in genres.iter() {
-                    let now = OffsetDateTime::now_utc();
-
-                    let media_list = match source.get_media_list_by_genre(genre).await {
-                        Ok(media_list) => media_list,
-                        Err(err) => {
-                            event!(
-                                Level::ERROR,
-                                %err,
-                                source = source.name(),
-                                "Error getting media list",
-                            );
+                let Some(genre) = self
+                    .shutdown
+                    .run_until_cancelled(schedule.next_genre(genres))
+                    .await
+                else {
+                    event!(
+                        Level::DEBUG,
+                        source = source.name(),
+                        "Shutdown, stop parsing"
+                    );
 
-                            failed = true;
+                    return;
+                };
+                let genre = &genre;
 
-                            if let Some(duration) = self.backoff.next_backoff() {
-                                event!(
-                                    Level::WARN,
-                                    source = source.name(),
-                                    "Sleep and try again at {duration:2?}",
-                                );
+                let now = OffsetDateTime::now_utc();
 
-                                tokio_time::sleep(duration).await;
-                            }
+                let Some(media_list_result) = self
+                    .shutdown
+                    .run_until_cancelled(source.get_media_list_by_genre(genre))
+                    .await
+                else {
+                    event!(
+                        Level::DEBUG,
+                        source = source.name(),
+                        "Shutdown, stop parsing"
+                    );
 
-                            continue;
-                        }
-                    };
+                    return;
+                };
 
-                    if failed {
+                let media_list = match media_list_result {
+                    Ok(media_list) => media_list,
+                    Err(err) => {
                         event!(
-                            Level::INFO,
+                            Level::ERROR,
+                            %err,
                             source = source.name(),
-                            "Connection established successfully",
+                            "Error getting media list",
                         );
 
-                        failed = false;
-
-                        self.backoff.reset();
-                    }
+                        let fetch_result = FetchResult::Failure {
+                            genre: genre.clone(),
+                            error: err.to_string(),
+                            elapsed: (OffsetDateTime::now_utc() - now).unsigned_abs(),
+                        };
 
-                    let media_list_len = media_list.len();
+                        if let Err(err) = sender.send(fetch_result).await {
+                            event!(Level::DEBUG,
+                                %err,
+                                source = source.name(),
+                                "Channel closed, stop parsing",
+                            );
 
-                    let elapsed = (OffsetDateTime::now_utc() - now).as_seconds_f32();
+                            return;
+                        }
 
-                    event!(
-                        Level::TRACE,
-                        source = source.name(),
-                        "Media list with {media_list_len} media parsed in {elapsed} seconds",
-                    );
+                        failed = true;
 
-                    for media in media_list {
-                        if let Err(err) = sender.send(media).await {
-                            event!(Level::ERROR,
-                                %err,
+                        if let Some(duration) = self.backoff.next_backoff() {
+                            event!(
+                                Level::WARN,
                                 source = source.name(),
-                                "Error sending media to channel",
+                                "Sleep and try again at {duration:2?}",
                             );
+
+                            if self
+                                .shutdown
+                                .run_until_cancelled(tokio_time::sleep(duration))
+                                .await
+                                .is_none()
+                            {
+                                return;
+                            }
                         }
+
+                        continue;
                     }
+                };
+
+                if failed {
+                    event!(
+                        Level::INFO,
+                        source = source.name(),
+                        "Connection established successfully",
+                    );
+
+                    failed = false;
+
+                    self.backoff.reset();
+                }
+
+                let media_list_len = media_list.len();
+
+                let elapsed = (OffsetDateTime::now_utc() - now).unsigned_abs();
+
+                event!(
+                    Level::TRACE,
+                    source = source.name(),
+                    "Media list with {media_list_len} media parsed in {elapsed:2?}",
+                );
+
+                let fetch_result = FetchResult::Success {
+                    genre: genre.clone(),
+                    media_list,
+                    elapsed,
+                };
+
+                if let Err(err) = sender.send(fetch_result).await {
+                    event!(Level::DEBUG,
+                        %err,
+                        source = source.name(),
+                        "Channel closed, stop parsing",
+                    );
 
-                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    return;
                 }
             }
         });
@@ -136,9 +235,15 @@ impl Worker<NekosBest<reqwest::Client>> for WorkerManager {
 
 #[async_trait]
 impl Worker<WaifuPics<reqwest::Client>> for WorkerManager {
-    async fn parse(mut self, mut source: WaifuPics<reqwest::Client>) -> Receiver<Media> {
+    #[allow(clippy::too_many_lines)]
+    async fn parse(mut self, mut source: WaifuPics<reqwest::Client>) -> Receiver<FetchResult> {
         let (sender, receiver) = tokio_mpsc_channel(self.channel_buffer);
 
+        let mut schedule = self
+            .schedule
+            .take()
+            .unwrap_or_else(|| Schedule::new(source.name(), true, DEFAULT_POLLING_INTERVAL));
+
         tokio::spawn(async move {
             let genres = source.genres().clone();
 
@@ -147,71 +252,130 @@ impl Worker<WaifuPics<reqwest::Client>> for WorkerManager {
             self.backoff.reset();
 
             loop {
-                for genre in genres.iter() {
-                    let now = OffsetDateTime::now_utc();
+                let Some(genre) = self
+                    .shutdown
+                    .run_until_cancelled(schedule.next_genre(&genres))
+                    .await
+                else {
+                    event!(
+                        Level::DEBUG,
+                        source = source.name(),
+                        "Shutdown, stop parsing"
+                    );
+
+                    return;
+                };
+                let genre = &genre;
+
+                let now = OffsetDateTime::now_utc();
+
+                let Some(media_list_result) = self
+                    .shutdown
+                    .run_until_cancelled(source.get_media_list_by_genre(genre))
+                    .await
+                else {
+                    event!(
+                        Level::DEBUG,
+                        source = source.name(),
+                        "Shutdown, stop parsing"
+                    );
+
+                    return;
+                };
+
+                let media_list = match media_list_result {
+                    Ok(media_list) => media_list,
+                    Err(err) => {
+                        event!(Level::ERROR,
+                            %err,
+                            source = source.name(),
+                            "Error getting media list",
+                        );
+
+                        let fetch_result = FetchResult::Failure {
+                            genre: genre.clone(),
+                            error: err.to_string(),
+                            elapsed: (OffsetDateTime::now_utc() - now).unsigned_abs(),
+                        };
 
-                    let media_list = match source.get_media_list_by_genre(genre).await {
-                        Ok(media_list) => media_list,
-                        Err(err) => {
-                            event!(Level::ERROR,
+                        if let Err(err) = sender.send(fetch_result).await {
+                            event!(Level::DEBUG,
                                 %err,
                                 source = source.name(),
-                                "Error getting media list",
+                                "Channel closed, stop parsing",
                             );
 
-                            failed = true;
+                            return;
+                        }
 
-                            if let Some(backoff) = self.backoff.next_backoff() {
-                                event!(
-                                    Level::WARN,
-                                    source = source.name(),
-                                    "Sleep and try again at {backoff:2?}"
-                                );
+                        failed = true;
 
-                                tokio_time::sleep(backoff).await;
-                            }
+                        if let Some(backoff) = self.backoff.next_backoff() {
+                            event!(
+                                Level::WARN,
+                                source = source.name(),
+                                "Sleep and try again at {backoff:2?}"
+                            );
 
-                            continue;
+                            if self
+                                .shutdown
+                                .run_until_cancelled(tokio_time::sleep(backoff))
+                                .await
+                                .is_none()
+                            {
+                                return;
+                            }
                         }
-                    };
 
-                    if failed {
-                        event!(
-                            Level::INFO,
-                            source = source.name(),
-                            "Connection established successfully",
-                        );
+                        continue;
+                    }
+                };
 
-                        failed = false;
+                if failed {
+                    event!(
+                        Level::INFO,
+                        source = source.name(),
+                        "Connection established successfully",
+                    );
 
-                        self.backoff.reset();
-                    }
+                    failed = false;
 
-                    let media_list_len = media_list.len();
+                    self.backoff.reset();
+                }
 
-                    let elapsed = (OffsetDateTime::now_utc() - now).as_seconds_f32();
+                let media_list_len = media_list.len();
 
-                    event!(
-                        Level::TRACE,
+                let elapsed = (OffsetDateTime::now_utc() - now).unsigned_abs();
+
+                event!(
+                    Level::TRACE,
+                    source = source.name(),
+                    "Media list with {media_list_len} media parsed in {elapsed:2?}",
+                );
+
+                let media_urls = media_list
+                    .iter()
+                    .map(|media| media.url().to_owned())
+                    .collect::<Vec<_>>();
+
+                let fetch_result = FetchResult::Success {
+                    genre: genre.clone(),
+                    media_list,
+                    elapsed,
+                };
+
+                if let Err(err) = sender.send(fetch_result).await {
+                    event!(Level::DEBUG,
+                        %err,
                         source = source.name(),
-                        "Media list with {media_list_len} media parsed in {elapsed} seconds",
+                        "Channel closed, stop parsing",
                     );
 
-                    for media in media_list {
-                        let media_url = Cow::Owned(media.url().to_owned());
-
-                        if let Err(err) = sender.send(media).await {
-                            event!(Level::ERROR,
-                                %err,
-                                source = source.name(),
-                                "Error sending media to channel",
-                            );
-                        } else {
-                            source.exclude_url(media_url);
-                        }
-                    }
+                    return;
+                }
 
-                    tokio::time::sleep(Duration::from_secs(3)).await;
+                for media_url in media_urls {
+                    source.exclude_url(Cow::Owned(media_url));
                 }
             }
         });
@@ -239,23 +403,32 @@ pub enum ErrorKind {
 /// Run polling for a source and worker manager.
 /// This function creates a source in the database if it doesn't exist
 /// and then starts polling for media from the source and save them in the database.
+/// Fetches are scheduled with settings of the source in the database, commands of the controller,
+/// the stock of media and outcomes of previous fetches.
 /// # Arguments
 /// * `worker` - Worker manager for the source.
 /// * `source` - Source to parse.
 /// * `uow_factory` - Unit of work factory.
+/// * `controller` - Controller, which sends commands to the worker.
+/// * `settings` - Receiver of settings of sources in the database.
+/// * `stock` - Receiver of the stock of media.
+/// * `scheduling` - Settings of scheduling of fetches by the stock.
+#[allow(clippy::too_many_lines)]
 #[instrument(skip_all, fields(source = source.name()))]
 pub async fn run_polling<S, UoWFactory>(
     worker: WorkerManager,
     source: S,
     uow_factory: UoWFactory,
+    controller: &Controller,
+    settings: watch::Receiver<SourcesSettings>,
+    stock: watch::Receiver<Stock>,
+    scheduling: &Scheduling,
 ) -> Result<(), ErrorKind>
 where
     S: Source + 'static,
     WorkerManager: Worker<S>,
     UoWFactory: UnitOfWorkFactory,
 {
-    let mut source_id = Uuid::new_v4();
-
     event!(Level::DEBUG, "Creating source");
 
     let mut uow = uow_factory.new_unit_of_work();
@@ -263,7 +436,11 @@ where
     let create_source_result = uow
         .source_repo()
         .await?
-        .create(CreateSource::new(&source_id, source.name(), source.url()))
+        .create(CreateSource::new(
+            &Uuid::new_v4(),
+            source.name(),
+            source.url(),
+        ))
         .await;
 
     match create_source_result {
@@ -275,15 +452,7 @@ where
         Err(RepoKind::Exception(_)) => {
             uow.rollback().await?;
 
-            let db_source = uow
-                .source_reader()
-                .await?
-                .get_by_name_and_url(GetSourceByNameAndUrl::new(source.name(), source.url()))
-                .await?;
-
             event!(Level::DEBUG, "Source already exists");
-
-            source_id = db_source.id;
         }
         Err(RepoKind::Unexpected(err)) => {
             uow.rollback().await?;
@@ -294,68 +463,503 @@ where
         }
     };
 
+    let db_source = uow
+        .source_reader()
+        .await?
+        .get_by_name_and_url(GetSourceByNameAndUrl::new(source.name(), source.url()))
+        .await?;
+    let source_id = db_source.id;
+
+    if !db_source.enabled {
+        event!(
+            Level::INFO,
+            "Source is disabled. It isn't polled until it's resumed"
+        );
+    }
+
     event!(Level::DEBUG, "Starting worker manager");
 
-    let mut receiver = Worker::<S>::parse(worker, source).await;
+    let source_name = source.name().to_owned();
+
+    let (outcomes_sender, outcomes_receiver) = mpsc::unbounded_channel();
+
+    let schedule = Schedule::new(
+        source.name(),
+        db_source.enabled,
+        db_source.polling_interval(),
+    )
+    .with_controller(controller)
+    .with_settings(settings)
+    .with_stock(stock, scheduling.stock_target)
+    .with_outcomes(outcomes_receiver, scheduling.duplicates_max_backoff);
+
+    let mut receiver = Worker::<S>::parse(worker.with_schedule(schedule), source).await;
+
+    let mut buffer = vec![];
+    let mut buffered_media_count = 0;
+    let mut flush_deadline = None;
+
+    loop {
+        let fetch_result = match flush_deadline {
+            Some(deadline) => {
+                if let Ok(fetch_result) = tokio_time::timeout_at(deadline, receiver.recv()).await {
+                    fetch_result
+                } else {
+                    flush_fetch_results(
+                        &mut uow,
+                        &mut buffer,
+                        &source_id,
+                        &source_name,
+                        &outcomes_sender,
+                    )
+                    .await;
+
+                    buffered_media_count = 0;
+                    flush_deadline = None;
+
+                    continue;
+                }
+            }
+            None => receiver.recv().await,
+        };
 
-    while let Some(media) = receiver.recv().await {
-        let media_id = Uuid::new_v4();
+        let Some(fetch_result) = fetch_result else {
+            break;
+        };
 
-        let create_media_result = uow
-            .media_repo()
-            .await?
-            .create(CreateMedia::new(
-                &media_id,
+        if buffer.is_empty() {
+            flush_deadline = Some(tokio_time::Instant::now() + MEDIA_BATCH_INTERVAL);
+        }
+
+        if let FetchResult::Success { ref media_list, .. } = fetch_result {
+            buffered_media_count += media_list.len();
+        }
+
+        buffer.push(fetch_result);
+
+        if buffered_media_count >= MEDIA_BATCH_SIZE {
+            flush_fetch_results(
+                &mut uow,
+                &mut buffer,
+                &source_id,
+                &source_name,
+                &outcomes_sender,
+            )
+            .await;
+
+            buffered_media_count = 0;
+            flush_deadline = None;
+        }
+    }
+
+    flush_fetch_results(
+        &mut uow,
+        &mut buffer,
+        &source_id,
+        &source_name,
+        &outcomes_sender,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Returns count of new and duplicate media of the fetch.
+/// Created ids are removed from the set, so the same media isn't counted as new for two fetches.
+fn count_new_media(media_ids: &[Uuid], created_media_ids: &mut HashSet<Uuid>) -> (i64, i64) {
+    let new_media_count = media_ids
+        .iter()
+        .filter(|media_id| created_media_ids.remove(media_id))
+        .count();
+
+    #[allow(clippy::cast_possible_wrap)]
+    (
+        new_media_count as i64,
+        (media_ids.len() - new_media_count) as i64,
+    )
+}
+
+/// Returns error, count of new and duplicate media of the fetch.
+/// If media of the fetches aren't saved, successful fetches are failed with the save error,
+/// so they aren't counted as fetches of only duplicates.
+fn fetch_run_counts<'a>(
+    fetch_result: &'a FetchResult,
+    media_ids: &[Uuid],
+    created_media_ids: &mut HashSet<Uuid>,
+    save_error: Option<&'a str>,
+) -> (Option<&'a str>, i64, i64) {
+    match fetch_result {
+        FetchResult::Success { .. } => {
+            if let Some(save_error) = save_error {
+                return (Some(save_error), 0, 0);
+            }
+
+            let (new_media_count, duplicate_media_count) =
+                count_new_media(media_ids, created_media_ids);
+
+            (None, new_media_count, duplicate_media_count)
+        }
+        FetchResult::Failure { error, .. } => (Some(error), 0, 0),
+    }
+}
+
+/// Save media of the fetch results and record the fetch runs, see [`save_fetch_results`].
+/// Errors are logged and the buffer is cleared anyway, so the polling goes on after failures of the database.
+async fn flush_fetch_results<UoW>(
+    uow: &mut UoW,
+    buffer: &mut Vec<FetchResult>,
+    source_id: &Uuid,
+    source_name: &str,
+    outcomes: &mpsc::UnboundedSender<FetchOutcome>,
+) where
+    UoW: UnitOfWork,
+{
+    if let Err(err) = save_fetch_results(uow, buffer, source_id, source_name, outcomes).await {
+        event!(Level::ERROR, %err, "Failed to save fetch results");
+
+        buffer.clear();
+    }
+}
+
+/// Save media of the fetch results in the database with one request and record the fetch runs.
+/// Outcomes of successful fetches are sent to the schedule, if media are saved.
+/// If media can't be saved, the fetch runs are recorded as failed.
+/// The buffer is cleared after saving.
+/// # Errors
+/// Returns error if the unit of work fails
+#[allow(clippy::too_many_lines)]
+async fn save_fetch_results<UoW>(
+    uow: &mut UoW,
+    buffer: &mut Vec<FetchResult>,
+    source_id: &Uuid,
+    source_name: &str,
+    outcomes: &mpsc::UnboundedSender<FetchOutcome>,
+) -> Result<(), ErrorKind>
+where
+    UoW: UnitOfWork,
+{
+    if buffer.is_empty() {
+        return Ok(());
+    }
+
+    let media_ids = buffer
+        .iter()
+        .map(|fetch_result| match fetch_result {
+            FetchResult::Success { media_list, .. } => {
+                media_list.iter().map(|_| Uuid::new_v4()).collect()
+            }
+            FetchResult::Failure { .. } => vec![],
+        })
+        .collect::<Vec<Vec<_>>>();
+
+    let media_list = buffer
+        .iter()
+        .zip(&media_ids)
+        .filter_map(|(fetch_result, media_ids)| match fetch_result {
+            FetchResult::Success { media_list, .. } => Some(media_list.iter().zip(media_ids)),
+            FetchResult::Failure { .. } => None,
+        })
+        .flatten()
+        .map(|(media, media_id): (&Media, &Uuid)| {
+            CreateMedia::new(
+                media_id,
                 media.url(),
                 Some(media.genre().name()),
                 media.genre().media_type().as_str(),
-                Some(media.genre().is_sfw()),
-                &source_id,
+                media.genre().age_restriction().as_str(),
+                source_id,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    event!(
+        Level::TRACE,
+        fetch_count = buffer.len(),
+        media_count = media_list.len(),
+        "Saving media",
+    );
+
+    let create_media_result = uow.media_repo().await?.create_many(&media_list).await;
+
+    let (mut created_media_ids, save_error) = match create_media_result {
+        Ok(created_media_ids) => {
+            uow.commit().await?;
+
+            (created_media_ids.into_iter().collect::<HashSet<_>>(), None)
+        }
+        Err(err) => {
+            uow.rollback().await?;
+
+            event!(Level::ERROR, %err, "Failed to save media");
+
+            (HashSet::new(), Some(format!("Failed to save media: {err}")))
+        }
+    };
+
+    for (fetch_result, media_ids) in buffer.drain(..).zip(&media_ids) {
+        let (error, new_media_count, duplicate_media_count) = fetch_run_counts(
+            &fetch_result,
+            media_ids,
+            &mut created_media_ids,
+            save_error.as_deref(),
+        );
+
+        let genre = fetch_result.genre();
+
+        if error.is_none() {
+            // The schedule is dropped, if the worker is stopped
+            let _ = outcomes.send(FetchOutcome {
+                genre: genre.clone(),
+                new_media_count,
+            });
+        }
+
+        counter!(
+            "media_parser_fetches_total",
+            "source" => source_name.to_owned(),
+            "outcome" => if error.is_some() { "failure" } else { "success" },
+        )
+        .increment(1);
+        counter!("media_parser_media_total", "source" => source_name.to_owned(), "kind" => "new")
+            .increment(new_media_count.unsigned_abs());
+        counter!("media_parser_media_total", "source" => source_name.to_owned(), "kind" => "duplicate")
+            .increment(duplicate_media_count.unsigned_abs());
+        histogram!("media_parser_fetch_duration_seconds", "source" => source_name.to_owned())
+            .record(fetch_result.elapsed());
+
+        event!(
+            Level::TRACE,
+            %genre,
+            new_media_count,
+            duplicate_media_count,
+            "Recording fetch run",
+        );
+
+        #[allow(clippy::cast_possible_truncation)]
+        let record_fetch_run_result = uow
+            .source_repo()
+            .await?
+            .record_fetch_run(RecordSourceFetchRun::new(
+                source_id,
+                genre.name(),
+                genre.media_type().as_str(),
+                genre.age_restriction().as_str(),
+                error,
+                new_media_count,
+                duplicate_media_count,
+                fetch_result.elapsed().as_millis() as i64,
             ))
             .await;
 
-        match create_media_result {
+        match record_fetch_run_result {
             Ok(()) => uow.commit().await?,
-            Err(RepoKind::Exception(_)) => uow.rollback().await?,
-            Err(RepoKind::Unexpected(_)) => uow.rollback().await?,
-        };
+            Err(err) => {
+                uow.rollback().await?;
+
+                event!(Level::ERROR, %err, %genre, "Failed to record fetch run");
+            }
+        }
     }
 
     Ok(())
 }
 
-/// Run polling for all known sources.
+/// Wait until genres of the source are changed and return the source with new genres.
+/// If the sources aren't reloaded anymore, it never returns.
+async fn wait_genres_changed<S>(
+    sources: &mut watch::Receiver<Sources>,
+    get_source: fn(&Sources) -> S,
+    genres: &Genres,
+) -> S
+where
+    S: Source,
+{
+    loop {
+        if sources.changed().await.is_err() {
+            return future::pending().await;
+        }
+
+        let source = get_source(&sources.borrow_and_update());
+
+        if source.genres() != genres {
+            return source;
+        }
+    }
+}
+
+/// Run polling for a source and restart it when genres of the source are changed.
 /// # Arguments
+/// * `sources` - Receiver of sources, which is notified when sources are reloaded.
+/// * `get_source` - Function to get the source from the sources.
 /// * `uow_factory` - Unit of work factory.
+/// * `controller` - Controller, which sends commands to the worker.
+/// * `settings` - Receiver of settings of sources in the database.
+/// * `stock` - Receiver of the stock of media.
+/// * `scheduling` - Settings of scheduling of fetches by the stock.
+/// * `shutdown` - Token, which stops parsing. Parsed media are saved before the polling is stopped.
+#[allow(clippy::too_many_arguments)]
+async fn run_reloadable_polling<S, UoWFactory>(
+    mut sources: watch::Receiver<Sources>,
+    get_source: fn(&Sources) -> S,
+    uow_factory: UoWFactory,
+    controller: Controller,
+    settings: watch::Receiver<SourcesSettings>,
+    stock: watch::Receiver<Stock>,
+    scheduling: Scheduling,
+    shutdown: CancellationToken,
+) where
+    S: Source + 'static,
+    WorkerManager: Worker<S>,
+    UoWFactory: UnitOfWorkFactory + Clone,
+{
+    let mut source = get_source(&sources.borrow_and_update());
+
+    loop {
+        let source_name = source.name().to_owned();
+        let genres = source.genres().clone();
+
+        tokio::select! {
+            result = run_polling(
+                WorkerManager::default().with_shutdown(shutdown.clone()),
+                source,
+                uow_factory.clone(),
+                &controller,
+                settings.clone(),
+                stock.clone(),
+                &scheduling,
+            ) => {
+                match result {
+                    Ok(()) => {
+                        event!(Level::INFO, source = source_name, "Worker manager stopped");
+                    }
+                    Err(err) => {
+                        event!(Level::ERROR, %err, source = source_name, "Worker manager stopped");
+                    }
+                };
+
+                return;
+            }
+            // Genres aren't changed during the shutdown, so parsed media are saved by the current worker manager
+            Some(new_source) = shutdown.run_until_cancelled(wait_genres_changed(&mut sources, get_source, &genres)) => {
+                event!(Level::INFO, source = source_name, "Genres changed, restarting worker manager");
+
+                source = new_source;
+            }
+        }
+    }
+}
+
+/// Run polling for all known sources and polling of settings of sources and the stock of media for their schedules.
+/// Polling of a source is restarted when its genres are changed by reloading of the sources.
+/// # Arguments
+/// * `sources` - Receiver of sources, which is notified when sources are reloaded.
+/// * `uow_factory` - Unit of work factory.
+/// * `controller` - Controller, which sends commands to workers of the sources.
+/// * `scheduling` - Settings of scheduling of fetches by the stock.
+/// * `shutdown` - Token, which stops parsing. Parsed media are saved before the function returns.
 #[instrument(skip_all)]
 pub async fn run_pollings<UoWFactory>(
-    nekos_best: NekosBest,
-    waifu_pics: WaifuPics,
+    sources: watch::Receiver<Sources>,
     uow_factory: UoWFactory,
+    controller: Controller,
+    scheduling: Scheduling,
+    shutdown: CancellationToken,
 ) where
     UoWFactory: UnitOfWorkFactory + Clone + Send + 'static,
     UoWFactory::UnitOfWork: Send,
 {
+    let (settings_sender, settings_receiver) = watch::channel(SourcesSettings::default());
+    let (stock_sender, stock_receiver) = watch::channel(Stock::default());
+
     tokio::join!(
-        async {
-            match run_polling(WorkerManager::default(), nekos_best, uow_factory.clone()).await {
-                Ok(()) => {
-                    event!(Level::INFO, "Worker manager stopped for `nekos.best`");
-                }
-                Err(err) => {
-                    event!(Level::ERROR, %err, "Worker manager stopped for `nekos.best`");
-                }
-            };
-        },
-        async {
-            match run_polling(WorkerManager::default(), waifu_pics, uow_factory.clone()).await {
-                Ok(()) => {
-                    event!(Level::INFO, "Worker manager stopped for `waifu.pics`");
-                }
-                Err(err) => {
-                    event!(Level::ERROR, %err, "Worker manager stopped for `waifu.pics`");
-                }
-            };
-        },
+        run_settings_polling(
+            uow_factory.clone(),
+            settings_sender,
+            SETTINGS_POLLING_INTERVAL,
+        ),
+        run_stock_polling(
+            uow_factory.clone(),
+            stock_sender,
+            scheduling.active_users_period,
+            scheduling.stock_polling_interval,
+        ),
+        run_reloadable_polling(
+            sources.clone(),
+            |sources| sources.nekos_best.clone(),
+            uow_factory.clone(),
+            controller.clone(),
+            settings_receiver.clone(),
+            stock_receiver.clone(),
+            scheduling,
+            shutdown.clone(),
+        ),
+        run_reloadable_polling(
+            sources,
+            |sources| sources.waifu_pics.clone(),
+            uow_factory,
+            controller,
+            settings_receiver,
+            stock_receiver,
+            scheduling,
+            shutdown,
+        ),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{count_new_media, fetch_run_counts};
+
+    use crate::domain::media_parser::entities::{FetchResult, Genre};
+
+    use std::{collections::HashSet, time::Duration};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_count_new_media() {
+        let media_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let mut created_media_ids = HashSet::from([media_ids[0], media_ids[2]]);
+
+        assert_eq!(count_new_media(&media_ids, &mut created_media_ids), (2, 1));
+        assert!(created_media_ids.is_empty());
+        assert_eq!(count_new_media(&media_ids, &mut created_media_ids), (0, 3));
+        assert_eq!(count_new_media(&[], &mut created_media_ids), (0, 0));
+    }
+
+    #[test]
+    fn test_fetch_run_counts() {
+        let success = FetchResult::Success {
+            genre: Genre::new_sfw_image("neko"),
+            media_list: vec![],
+            elapsed: Duration::ZERO,
+        };
+        let failure = FetchResult::Failure {
+            genre: Genre::new_sfw_image("neko"),
+            error: "Timeout".to_owned(),
+            elapsed: Duration::ZERO,
+        };
+        let media_ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let mut created_media_ids = HashSet::from([media_ids[0]]);
+        assert_eq!(
+            fetch_run_counts(&success, &media_ids, &mut created_media_ids, None),
+            (None, 1, 1)
+        );
+
+        // Media aren't saved, so the fetch isn't counted as a fetch of duplicates
+        let mut created_media_ids = HashSet::new();
+        assert_eq!(
+            fetch_run_counts(
+                &success,
+                &media_ids,
+                &mut created_media_ids,
+                Some("Failed to save media")
+            ),
+            (Some("Failed to save media"), 0, 0)
+        );
+        assert_eq!(
+            fetch_run_counts(&failure, &[], &mut created_media_ids, None),
+            (Some("Timeout"), 0, 0)
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    application::{
+        common::{
+            exceptions::{BeginError, CommitError, RepoError, RollbackError},
+            traits::{UnitOfWork as _, UnitOfWorkFactory},
+        },
+        media::dto::{GetMediaToValidate, SetMediaFileInfo},
+    },
+    domain::{
+        media::value_objects::{send_method::MAX_FILE_SIZE, MediaStatus},
+        media_parser::value_objects::MediaType,
+    },
+};
+
+use image::{io::Reader as ImageReader, ImageFormat};
+use metrics::counter;
+use std::{io::Cursor, num::NonZeroU32, time::Duration};
+use tokio::time::{self as tokio_time, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+/// Count of media, which are read from the database per request
+const BATCH_SIZE: u64 = 100;
+/// Timeout of a download request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Count of bytes, which are read from the start of a file to sniff its type and dimensions
+const HEAD_SIZE: usize = 64 * 1024;
+/// Time to wait before the next try if there are no media to validate
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Info about media content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FileInfo {
+    mime: Option<&'static str>,
+    size: Option<i64>,
+    dimensions: Option<(u32, u32)>,
+}
+
+impl FileInfo {
+    /// # Arguments
+    /// * `head` - Start of the file
+    /// * `size` - Size of the file from `Content-Length` header
+    fn from_head(head: &[u8], size: Option<i64>) -> Self {
+        let (mime, image_format) = match sniff(head) {
+            Some((mime, image_format)) => (Some(mime), image_format),
+            None => (None, None),
+        };
+
+        let dimensions = image_format.and_then(|image_format| {
+            ImageReader::with_format(Cursor::new(head), image_format)
+                .into_dimensions()
+                .ok()
+        });
+
+        Self {
+            mime,
+            size,
+            dimensions,
+        }
+    }
+
+    /// Returns the media type, which matches the content, or the reason why Telegram can't send it
+    /// # Arguments
+    /// * `media_type` - Current type of the media. MP4 files of videos stay videos, other MP4 files are animations.
+    fn validate(&self, media_type: &str) -> Result<MediaType, &'static str> {
+        if self.size.is_some_and(|size| size > MAX_FILE_SIZE) {
+            return Err("file is too big");
+        }
+
+        match self.mime {
+            Some("video/mp4") if media_type == MediaType::Video.as_str() => Ok(MediaType::Video),
+            Some("image/gif" | "video/mp4") => Ok(MediaType::Gif),
+            Some("video/webm") => Err("WebM isn't sent by url"),
+            Some(_) => Ok(MediaType::Image),
+            None => Err("content isn't supported"),
+        }
+    }
+}
+
+/// Returns MIME type of the content by its magic bytes and its image format, if it's an image.
+/// Only formats, which Telegram supports as photos, animations and videos, are recognized, and also `WebM` to reject it.
+fn sniff(head: &[u8]) -> Option<(&'static str, Option<ImageFormat>)> {
+    // `WebM` starts with EBML header
+    if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some(("video/webm", None));
+    }
+
+    // MP4 starts with box size and `ftyp` box, which contains the major brand
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        let brand = &head[8..12];
+
+        return [b"isom", b"iso2", b"mp41", b"mp42", b"avc1", b"M4V "]
+            .iter()
+            .any(|mp4_brand| brand == *mp4_brand)
+            .then_some(("video/mp4", None));
+    }
+
+    let image_format = image::guess_format(head).ok()?;
+    let mime = match image_format {
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Png => "image/png",
+        ImageFormat::WebP => "image/webp",
+        _ => return None,
+    };
+
+    Some((mime, Some(image_format)))
+}
+
+/// Reads the start of media files and validates them against Telegram limits
+#[derive(Debug, Clone)]
+pub struct MediaValidator<Client = reqwest::Client> {
+    client: Client,
+    requests_per_second: NonZeroU32,
+}
+
+impl MediaValidator {
+    /// # Arguments
+    /// * `requests_per_second` - Max count of download requests per second
+    pub fn new(requests_per_second: NonZeroU32) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            requests_per_second,
+        }
+    }
+
+    /// Reads the start of the media file and returns info about it.
+    /// If the media can't be downloaded, returns `None`.
+    #[instrument(skip(self))]
+    async fn inspect(&self, url: &str) -> Option<FileInfo> {
+        let mut response = match self
+            .client
+            .get(url)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+        {
+            Ok(response) => response,
+            Err(err) => {
+                event!(Level::DEBUG, %err, "Error downloading media");
+
+                return None;
+            }
+        };
+
+        let size = response
+            .content_length()
+            .and_then(|content_length| i64::try_from(content_length).ok());
+
+        let mut head = Vec::with_capacity(HEAD_SIZE);
+        // The rest of the file isn't downloaded, because the response is dropped
+        while head.len() < HEAD_SIZE {
+            match response.chunk().await {
+                Ok(Some(chunk)) => head.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(err) => {
+                    event!(Level::DEBUG, %err, "Error downloading media");
+
+                    return None;
+                }
+            }
+        }
+
+        Some(FileInfo::from_head(&head, size))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error(transparent)]
+    Begin(#[from] BeginError),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+    #[error(transparent)]
+    Rollback(#[from] RollbackError),
+    #[error(transparent)]
+    Unexpected(#[from] RepoError),
+}
+
+/// Validate media, which weren't validated yet, and save info about their content.
+/// Media with a type, which doesn't match the content, are retagged, and media, which Telegram can't send, are marked as invalid.
+/// Media, which can't be downloaded, are marked as validated without info and are sent as documents.
+/// Validation stops between media, when the shutdown token is cancelled.
+/// # Errors
+/// Returns error if media can't be read from the database
+#[instrument(skip_all)]
+pub async fn run_validation<UoWFactory>(
+    validator: MediaValidator,
+    uow_factory: UoWFactory,
+    shutdown: CancellationToken,
+) -> Result<(), ErrorKind>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut rate_limit =
+        tokio_time::interval(Duration::from_secs(1) / validator.requests_per_second.get());
+    rate_limit.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    loop {
+        let media_list = uow
+            .media_reader()
+            .await?
+            .get_to_validate(GetMediaToValidate::new(BATCH_SIZE))
+            .await?;
+
+        // Don't keep the transaction open while media are downloaded
+        uow.commit().await?;
+
+        if media_list.is_empty() {
+            event!(Level::DEBUG, "No media to validate");
+
+            if shutdown
+                .run_until_cancelled(tokio_time::sleep(IDLE_INTERVAL))
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media validator stopped by shutdown");
+
+                return Ok(());
+            }
+
+            continue;
+        }
+
+        event!(Level::DEBUG, count = media_list.len(), "Validating media");
+
+        for media in media_list {
+            if shutdown
+                .run_until_cancelled(rate_limit.tick())
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media validator stopped by shutdown");
+
+                return Ok(());
+            }
+
+            // The transaction is committed before, so nothing is lost if the shutdown interrupts the request
+            let Some(file_info) = shutdown
+                .run_until_cancelled(validator.inspect(&media.url))
+                .await
+            else {
+                event!(Level::DEBUG, "Media validator stopped by shutdown");
+
+                return Ok(());
+            };
+
+            let validation = file_info
+                .as_ref()
+                .map(|file_info| file_info.validate(&media.media_type));
+
+            let (result, media_type, status) = match validation {
+                None => ("failed", None, None),
+                Some(Ok(media_type)) if media_type.as_str() == media.media_type => {
+                    ("valid", None, None)
+                }
+                Some(Ok(media_type)) => {
+                    event!(
+                        Level::INFO,
+                        media_id = %media.id,
+                        url = media.url,
+                        from = media.media_type,
+                        to = %media_type,
+                        "Media type doesn't match content",
+                    );
+
+                    ("retagged", Some(media_type), None)
+                }
+                Some(Err(reason)) => {
+                    event!(Level::INFO, media_id = %media.id, url = media.url, reason, "Media is invalid");
+
+                    ("invalid", None, Some(MediaStatus::Invalid))
+                }
+            };
+
+            counter!("media_validator_results_total", "result" => result).increment(1);
+
+            let file_info = file_info.unwrap_or_default();
+            let (width, height) = file_info
+                .dimensions
+                .and_then(|(width, height)| Some((width.try_into().ok()?, height.try_into().ok()?)))
+                .unzip();
+
+            let set_file_info_result = uow
+                .media_repo()
+                .await?
+                .set_file_info(SetMediaFileInfo::new(
+                    &media.id,
+                    media_type.map(MediaType::as_str),
+                    status.map(MediaStatus::as_str),
+                    file_info.mime,
+                    file_info.size,
+                    width,
+                    height,
+                ))
+                .await;
+
+            match set_file_info_result {
+                Ok(()) => uow.commit().await?,
+                Err(err) => {
+                    uow.rollback().await?;
+
+                    event!(Level::ERROR, %err, media_id = %media.id, "Failed to set media file info");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileInfo;
+
+    use crate::domain::{
+        media::value_objects::send_method::MAX_FILE_SIZE, media_parser::value_objects::MediaType,
+    };
+
+    use image::{DynamicImage, ImageOutputFormat};
+    use std::io::Cursor;
+
+    fn encode(format: ImageOutputFormat) -> Vec<u8> {
+        let mut content = Vec::new();
+        DynamicImage::new_rgb8(40, 30)
+            .write_to(&mut Cursor::new(&mut content), format)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn test_file_info_from_head() {
+        let png = FileInfo::from_head(&encode(ImageOutputFormat::Png), Some(100));
+        assert_eq!(png.mime, Some("image/png"));
+        assert_eq!(png.size, Some(100));
+        assert_eq!(png.dimensions, Some((40, 30)));
+
+        let gif = FileInfo::from_head(&encode(ImageOutputFormat::Gif), None);
+        assert_eq!(gif.mime, Some("image/gif"));
+        assert_eq!(gif.dimensions, Some((40, 30)));
+
+        let jpeg = FileInfo::from_head(&encode(ImageOutputFormat::Jpeg(80)), None);
+        assert_eq!(jpeg.mime, Some("image/jpeg"));
+        assert_eq!(jpeg.dimensions, Some((40, 30)));
+
+        let mp4 = FileInfo::from_head(b"\0\0\0\x20ftypisom\0\0\x02\0isomiso2avc1mp41", None);
+        assert_eq!(mp4.mime, Some("video/mp4"));
+        assert_eq!(mp4.dimensions, None);
+
+        let webm = FileInfo::from_head(b"\x1A\x45\xDF\xA3\x9F\x42\x86\x81\x01webm", None);
+        assert_eq!(webm.mime, Some("video/webm"));
+
+        let heic = FileInfo::from_head(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic", None);
+        assert_eq!(heic.mime, None);
+
+        let html = FileInfo::from_head(b"<!DOCTYPE html><html></html>", None);
+        assert_eq!(html.mime, None);
+    }
+
+    #[test]
+    fn test_file_info_validate() {
+        let file_info = |mime, size| FileInfo {
+            mime,
+            size,
+            dimensions: None,
+        };
+
+        assert_eq!(
+            file_info(Some("image/gif"), Some(1024)).validate("gif"),
+            Ok(MediaType::Gif)
+        );
+        assert_eq!(
+            file_info(Some("video/mp4"), None).validate("gif"),
+            Ok(MediaType::Gif)
+        );
+        assert_eq!(
+            file_info(Some("image/png"), None).validate("gif"),
+            Ok(MediaType::Image)
+        );
+        assert_eq!(
+            file_info(Some("image/webp"), Some(MAX_FILE_SIZE)).validate("img"),
+            Ok(MediaType::Image)
+        );
+        assert!(file_info(Some("image/jpeg"), Some(MAX_FILE_SIZE + 1))
+            .validate("img")
+            .is_err());
+        assert!(file_info(None, Some(1024)).validate("img").is_err());
+        assert_eq!(
+            file_info(Some("video/mp4"), None).validate("video"),
+            Ok(MediaType::Video)
+        );
+        assert_eq!(
+            file_info(Some("image/gif"), None).validate("video"),
+            Ok(MediaType::Gif)
+        );
+        assert!(file_info(Some("video/webm"), None)
+            .validate("video")
+            .is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    application::{
+        common::{
+            exceptions::{BeginError, CommitError, RepoError, RollbackError},
+            traits::{UnitOfWork as _, UnitOfWorkFactory},
+        },
+        media::dto::{GetMediaToCheck, MarkMediaChecked},
+    },
+    domain::media::value_objects::MediaStatus,
+};
+
+use metrics::counter;
+use reqwest::StatusCode;
+use std::{num::NonZeroU32, time::Duration};
+use time::OffsetDateTime;
+use tokio::time::{self as tokio_time, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+/// Count of media, which are read from the database per request
+const BATCH_SIZE: u64 = 100;
+/// Timeout of a check request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait before the next try if there are no media to check
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Checks media urls with HEAD requests
+#[derive(Debug, Clone)]
+pub struct MediaVerifier<Client = reqwest::Client> {
+    client: Client,
+    requests_per_second: NonZeroU32,
+    recheck_interval: Duration,
+}
+
+impl MediaVerifier {
+    /// # Arguments
+    /// * `requests_per_second` - Max count of check requests per second
+    /// * `recheck_interval` - Time after which checked media are checked again
+    pub fn new(requests_per_second: NonZeroU32, recheck_interval: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            requests_per_second,
+            recheck_interval,
+        }
+    }
+
+    /// Returns status of the media by its url.
+    /// If the check is inconclusive, for example, the host doesn't respond, returns `None`.
+    #[instrument(skip(self))]
+    async fn check(&self, url: &str) -> Option<MediaStatus> {
+        match self.client.head(url).timeout(REQUEST_TIMEOUT).send().await {
+            Ok(response) => status_from_response(response.status()),
+            Err(err) => {
+                event!(Level::DEBUG, %err, "Error checking media");
+
+                None
+            }
+        }
+    }
+}
+
+/// Returns status of media by status code of the response to HEAD request.
+/// Only codes, which mean that the file is deleted, mark media as broken,
+/// because some hosts don't allow HEAD requests or limit them.
+fn status_from_response(status_code: StatusCode) -> Option<MediaStatus> {
+    if status_code.is_success() {
+        Some(MediaStatus::Active)
+    } else if matches!(status_code, StatusCode::NOT_FOUND | StatusCode::GONE) {
+        Some(MediaStatus::Broken)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error(transparent)]
+    Begin(#[from] BeginError),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+    #[error(transparent)]
+    Rollback(#[from] RollbackError),
+    #[error(transparent)]
+    Unexpected(#[from] RepoError),
+}
+
+/// Check media, which weren't checked for the longest time, and update their statuses.
+/// Media are checked again after the recheck interval, so media, which are available again, become active.
+/// Invalid media aren't checked, because they aren't sent to users even if they are available.
+/// Verification stops between media, when the shutdown token is cancelled.
+/// # Errors
+/// Returns error if media can't be read from the database
+#[instrument(skip_all)]
+pub async fn run_verification<UoWFactory>(
+    verifier: MediaVerifier,
+    uow_factory: UoWFactory,
+    shutdown: CancellationToken,
+) -> Result<(), ErrorKind>
+where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut rate_limit =
+        tokio_time::interval(Duration::from_secs(1) / verifier.requests_per_second.get());
+    rate_limit.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut uow = uow_factory.new_unit_of_work();
+
+    loop {
+        let checked_before = OffsetDateTime::now_utc() - verifier.recheck_interval;
+
+        let media_list = uow
+            .media_reader()
+            .await?
+            .get_to_check(GetMediaToCheck::new(checked_before, BATCH_SIZE))
+            .await?;
+
+        // Don't keep the transaction open while media are checked
+        uow.commit().await?;
+
+        if media_list.is_empty() {
+            event!(Level::DEBUG, "No media to check");
+
+            if shutdown
+                .run_until_cancelled(tokio_time::sleep(IDLE_INTERVAL))
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media verifier stopped by shutdown");
+
+                return Ok(());
+            }
+
+            continue;
+        }
+
+        event!(Level::DEBUG, count = media_list.len(), "Checking media");
+
+        for media in media_list {
+            if shutdown
+                .run_until_cancelled(rate_limit.tick())
+                .await
+                .is_none()
+            {
+                event!(Level::DEBUG, "Media verifier stopped by shutdown");
+
+                return Ok(());
+            }
+
+            let Some(status) = shutdown
+                .run_until_cancelled(verifier.check(&media.url))
+                .await
+            else {
+                event!(Level::DEBUG, "Media verifier stopped by shutdown");
+
+                return Ok(());
+            };
+
+            counter!(
+                "media_verifier_checks_total",
+                "status" => status.map_or("unknown", MediaStatus::as_str),
+            )
+            .increment(1);
+
+            if status == Some(MediaStatus::Broken) {
+                event!(Level::INFO, media_id = %media.id, url = media.url, "Media is broken");
+            }
+
+            let mark_checked_result = uow
+                .media_repo()
+                .await?
+                .mark_checked(MarkMediaChecked::new(
+                    &media.id,
+                    status.map(MediaStatus::as_str),
+                ))
+                .await;
+
+            match mark_checked_result {
+                Ok(()) => uow.commit().await?,
+                Err(err) => {
+                    uow.rollback().await?;
+
+                    event!(Level::ERROR, %err, media_id = %media.id, "Failed to mark media as checked");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::status_from_response;
+
+    use crate::domain::media::value_objects::MediaStatus;
+
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_status_from_response() {
+        assert_eq!(
+            status_from_response(StatusCode::OK),
+            Some(MediaStatus::Active)
+        );
+        assert_eq!(
+            status_from_response(StatusCode::NOT_FOUND),
+            Some(MediaStatus::Broken)
+        );
+        assert_eq!(
+            status_from_response(StatusCode::GONE),
+            Some(MediaStatus::Broken)
+        );
+        assert_eq!(status_from_response(StatusCode::METHOD_NOT_ALLOWED), None);
+        assert_eq!(status_from_response(StatusCode::TOO_MANY_REQUESTS), None);
+        assert_eq!(status_from_response(StatusCode::BAD_GATEWAY), None);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod layer;
+pub mod media_stats;
+pub mod server;
+
+pub use layer::Layer;
+pub use media_stats::run_media_stats_polling;
+pub use server::{describe, run_server};
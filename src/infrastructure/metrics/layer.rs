@@ -0,0 +1,195 @@
+use metrics::{counter, histogram};
+use std::{fmt, time::Instant};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Metadata, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+/// Target of spans of database repositories
+const REPOSITORIES_TARGET: &str = concat!(
+    env!("CARGO_CRATE_NAME"),
+    "::infrastructure::database::repositories::"
+);
+/// Target prefix of spans and events of the Telegram Bot API client
+const TELEGRAM_CLIENT_TARGET: &str = "telers::client";
+
+enum Timing {
+    /// Span of a repository method
+    Repository {
+        repository: &'static str,
+        method: &'static str,
+        start: Instant,
+    },
+    /// Span of a request to Telegram Bot API
+    TelegramRequest {
+        method_name: Option<String>,
+        failed: bool,
+        start: Instant,
+    },
+}
+
+#[derive(Default)]
+struct MethodNameVisitor(Option<String>);
+
+impl Visit for MethodNameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "method_name" {
+            self.0 = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+#[derive(Default)]
+struct OkVisitor(Option<bool>);
+
+impl Visit for OkVisitor {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "ok" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+/// Tracing layer, which converts spans of database repositories and Telegram Bot API client to metrics.
+/// Use it with [`Layer::is_tracked`] as a per-layer filter,
+/// so the metrics don't depend on the logging level.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Layer;
+
+impl Layer {
+    /// Checks if spans and events with the metadata are converted to metrics
+    pub fn is_tracked(metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with(REPOSITORIES_TARGET)
+            || metadata.target().starts_with(TELEGRAM_CLIENT_TARGET)
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for Layer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let metadata = attrs.metadata();
+
+        let timing = if let Some(repository) = metadata.target().strip_prefix(REPOSITORIES_TARGET) {
+            Timing::Repository {
+                repository,
+                method: metadata.name(),
+                start: Instant::now(),
+            }
+        } else if metadata.target().starts_with(TELEGRAM_CLIENT_TARGET) && metadata.name() == "send"
+        {
+            let mut visitor = MethodNameVisitor::default();
+            attrs.record(&mut visitor);
+
+            Timing::TelegramRequest {
+                method_name: visitor.0,
+                failed: false,
+                start: Instant::now(),
+            }
+        } else {
+            return;
+        };
+
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(timing);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        if span.metadata().target().starts_with(TELEGRAM_CLIENT_TARGET)
+            && span.metadata().name() == "check"
+        {
+            let mut visitor = OkVisitor::default();
+            values.record(&mut visitor);
+
+            if visitor.0 == Some(false) {
+                counter!("telegram_api_errors_total").increment(1);
+            }
+
+            return;
+        }
+
+        let mut extensions = span.extensions_mut();
+
+        if let Some(Timing::TelegramRequest { method_name, .. }) = extensions.get_mut::<Timing>() {
+            let mut visitor = MethodNameVisitor::default();
+            values.record(&mut visitor);
+
+            if visitor.0.is_some() {
+                *method_name = visitor.0;
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Errors and warnings inside of a request span mean that the request wasn't sent or the response wasn't received
+        if *event.metadata().level() > Level::WARN {
+            return;
+        }
+
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+
+        let mut extensions = span.extensions_mut();
+
+        if let Some(Timing::TelegramRequest { failed, .. }) = extensions.get_mut::<Timing>() {
+            *failed = true;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<Timing>() else {
+            return;
+        };
+
+        match timing {
+            Timing::Repository {
+                repository,
+                method,
+                start,
+            } => {
+                histogram!(
+                    "db_query_duration_seconds",
+                    "repository" => repository,
+                    "method" => method,
+                )
+                .record(start.elapsed());
+            }
+            Timing::TelegramRequest {
+                method_name,
+                failed,
+                start,
+            } => {
+                let method_name = method_name.unwrap_or_else(|| "unknown".to_owned());
+                let outcome = if failed { "error" } else { "ok" };
+
+                counter!(
+                    "telegram_api_requests_total",
+                    "method" => method_name.clone(),
+                    "outcome" => outcome,
+                )
+                .increment(1);
+                histogram!(
+                    "telegram_api_request_duration_seconds",
+                    "method" => method_name,
+                )
+                .record(start.elapsed());
+            }
+        }
+    }
+}
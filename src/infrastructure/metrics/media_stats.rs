@@ -0,0 +1,71 @@
+use crate::application::common::traits::{UnitOfWork as _, UnitOfWorkFactory};
+
+use metrics::gauge;
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+/// Polls media stats from the database and updates media gauges
+/// # Arguments
+/// * `uow_factory` - Unit of work factory
+/// * `interval` - Interval between polls
+/// * `shutdown` - Token, which stops the polling when it's cancelled
+#[instrument(skip_all)]
+pub async fn run_media_stats_polling<UoWFactory>(
+    uow_factory: UoWFactory,
+    interval: Duration,
+    shutdown: CancellationToken,
+) where
+    UoWFactory: UnitOfWorkFactory,
+{
+    let mut interval = time::interval(interval);
+
+    loop {
+        if shutdown
+            .run_until_cancelled(interval.tick())
+            .await
+            .is_none()
+        {
+            event!(Level::DEBUG, "Media stats polling stopped by shutdown");
+
+            return;
+        }
+
+        let mut uow = uow_factory.new_unit_of_work();
+
+        let media_stats = match uow.media_reader().await {
+            Ok(mut media_reader) => media_reader.get_media_stats().await,
+            Err(err) => {
+                event!(Level::ERROR, %err, "Failed to get media reader");
+
+                continue;
+            }
+        };
+
+        let media_stats = match media_stats {
+            Ok(media_stats) => media_stats,
+            Err(err) => {
+                event!(Level::ERROR, %err, "Failed to get media stats");
+
+                continue;
+            }
+        };
+
+        event!(Level::TRACE, ?media_stats, "Updating media gauges");
+
+        #[allow(clippy::cast_precision_loss)]
+        {
+            gauge!("media_count", "media_type" => "gif").set(media_stats.gif as f64);
+            gauge!("media_count", "media_type" => "image").set(media_stats.image as f64);
+            gauge!("media_count", "media_type" => "video").set(media_stats.video as f64);
+            gauge!("media_count", "media_type" => "unknown").set(media_stats.unknown as f64);
+            gauge!("media_count_by_age_restriction", "age_restriction" => "sfw")
+                .set(media_stats.sfw as f64);
+            gauge!("media_count_by_age_restriction", "age_restriction" => "questionable")
+                .set(media_stats.questionable as f64);
+            gauge!("media_count_by_age_restriction", "age_restriction" => "nsfw")
+                .set(media_stats.nsfw as f64);
+        }
+    }
+}
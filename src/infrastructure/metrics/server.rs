@@ -0,0 +1,95 @@
+use axum::{routing::get, Router};
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use metrics_exporter_prometheus::{BuildError, PrometheusBuilder, PrometheusHandle};
+use std::{io, net::SocketAddr};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Describe all metrics, which are exposed by the bot
+pub fn describe() {
+    describe_counter!(
+        "updates_handled_total",
+        "Total number of updates handled by handler and outcome"
+    );
+    describe_histogram!(
+        "update_handling_duration_seconds",
+        Unit::Seconds,
+        "Duration of updates handling by handler"
+    );
+    describe_counter!(
+        "telegram_api_requests_total",
+        "Total number of requests to Telegram Bot API by method and outcome"
+    );
+    describe_histogram!(
+        "telegram_api_request_duration_seconds",
+        Unit::Seconds,
+        "Duration of requests to Telegram Bot API by method"
+    );
+    describe_counter!(
+        "telegram_api_errors_total",
+        "Total number of errors returned by Telegram Bot API"
+    );
+    describe_counter!(
+        "media_sent_total",
+        "Total number of media sent to users by genre and media type"
+    );
+    describe_histogram!(
+        "db_query_duration_seconds",
+        Unit::Seconds,
+        "Duration of database queries by repository and method"
+    );
+    describe_counter!(
+        "media_parser_fetches_total",
+        "Total number of media parser fetches by source and outcome"
+    );
+    describe_histogram!(
+        "media_parser_fetch_duration_seconds",
+        Unit::Seconds,
+        "Duration of media parser fetches by source"
+    );
+    describe_counter!(
+        "media_parser_media_total",
+        "Total number of parsed media by source and kind (new or duplicate)"
+    );
+    describe_gauge!(
+        "media_count",
+        "Count of media in the database by media type"
+    );
+    describe_gauge!(
+        "media_count_by_age_restriction",
+        "Count of media in the database by age restriction"
+    );
+}
+
+/// Install Prometheus recorder and run HTTP listener, which exposes metrics on `/metrics`, until the shutdown token is cancelled
+/// # Errors
+/// Returns error if the recorder can't be installed or the listener can't be bound
+#[instrument(skip_all, fields(%address))]
+pub async fn run_server(address: SocketAddr, shutdown: CancellationToken) -> Result<(), ErrorKind> {
+    let handle = PrometheusBuilder::new().install_recorder()?;
+
+    describe();
+
+    let listener = TcpListener::bind(address).await?;
+
+    event!(Level::INFO, "Metrics server started");
+
+    let router = Router::new().route(
+        "/metrics",
+        get(|| async move { PrometheusHandle::render(&handle) }),
+    );
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await
+        .map_err(Into::into)
+}
@@ -0,0 +1,138 @@
+use std::{
+    future::{self, Future},
+    time::Duration,
+};
+use tokio::{signal as tokio_signal, task::JoinHandle, time as tokio_time};
+use tokio_util::{
+    sync::CancellationToken,
+    task::{task_tracker::TaskTrackerToken, TaskTracker},
+};
+use tracing::{event, Level};
+
+/// Graceful shutdown of background tasks and in-flight deliveries.
+/// Tasks are told to stop by the cancellation token, and the shutdown waits until tracked tasks and deliveries finish.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl Shutdown {
+    /// Returns token, which is cancelled when the shutdown is started
+    pub const fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Returns `true` if the shutdown is started
+    pub fn is_started(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Spawns the task, which the shutdown waits for
+    pub fn spawn<F>(&self, task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker.spawn(task)
+    }
+
+    /// Returns guard of an in-flight delivery. The shutdown waits until the guard is dropped.
+    pub fn delivery(&self) -> TaskTrackerToken {
+        self.tracker.token()
+    }
+
+    /// Cancels the token and waits until tracked tasks and deliveries finish.
+    /// Returns `false` if they didn't finish before the timeout.
+    pub async fn run(&self, timeout: Duration) -> bool {
+        event!(
+            Level::INFO,
+            tasks = self.tracker.len(),
+            "Waiting for tasks to finish"
+        );
+
+        self.token.cancel();
+        self.tracker.close();
+
+        if tokio_time::timeout(timeout, self.tracker.wait())
+            .await
+            .is_ok()
+        {
+            event!(Level::INFO, "All tasks finished");
+
+            true
+        } else {
+            event!(
+                Level::WARN,
+                tasks = self.tracker.len(),
+                "Tasks didn't finish before the shutdown timeout"
+            );
+
+            false
+        }
+    }
+}
+
+/// Waits for SIGINT (Ctrl+C) or SIGTERM, which Docker and Kubernetes send to stop the process.
+/// If a signal can't be listened for, the error is logged and only the other signal is waited for.
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio_signal::ctrl_c().await {
+            event!(Level::ERROR, %err, "Error listening for Ctrl+C signal");
+
+            future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio_signal::unix::signal(tokio_signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                event!(Level::ERROR, %err, "Error listening for terminate signal");
+
+                future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {
+            event!(Level::INFO, "Ctrl+C signal received");
+        }
+        () = terminate => {
+            event!(Level::INFO, "Terminate signal received");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shutdown;
+
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let shutdown = Shutdown::default();
+
+        let token = shutdown.token().clone();
+        let task = shutdown.spawn(async move {
+            token.cancelled().await;
+        });
+        let delivery = shutdown.delivery();
+
+        assert!(!shutdown.run(Duration::from_millis(10)).await);
+        assert!(shutdown.is_started());
+        assert!(task.is_finished());
+
+        drop(delivery);
+
+        assert!(shutdown.run(Duration::from_millis(10)).await);
+    }
+}
@@ -1,4 +1,6 @@
 mod application;
+mod cli;
+mod commands;
 mod config;
 mod domain;
 mod extractors;
@@ -6,35 +8,74 @@ mod filters;
 mod handlers;
 mod infrastructure;
 mod middlewares;
+mod webhook;
 
-use config::read_config_from_env;
+use clap::Parser as _;
+use cli::{Cli, Command as CliCommand};
+use config::{
+    read_config, Config, MediaParserWorker as MediaParserWorkerConfig, Runtime as RuntimeConfig,
+    RuntimeFlavor,
+};
 use infrastructure::{
-    database::SqlxUnitOfWorkFactory,
-    media_parser::{worker, NekosBest, WaifuPics},
+    database::{migrations, SqlxUnitOfWorkFactory},
+    health::{self, Heartbeats, Layer as HealthLayer, Readiness},
+    media_deduplicator::{self, MediaDeduplicator},
+    media_parser::{
+        sources, worker, Controller as MediaParserController, Scheduling, SourcesReloader,
+    },
+    media_validator::{self, MediaValidator},
+    media_verifier::{self, MediaVerifier},
+    metrics::{self, Layer as MetricsLayer},
+    shutdown::{self, Shutdown},
 };
 use middlewares::{
-    Database as DatabaseMiddleware, MediaParserSources as MediaParserSourcesMiddleware,
+    Database as DatabaseMiddleware, HandlerMetrics as HandlerMetricsMiddleware,
+    MediaParserSources as MediaParserSourcesMiddleware, Shutdown as ShutdownMiddleware,
     ACL as ACLMiddleware,
 };
-use sqlx::{PgPool, Pool, Postgres};
+use sqlx::{postgres::PgPoolOptions, PgPool, Pool, Postgres};
+use std::{io, sync::Arc, time::Duration};
 use telers::{
+    enums::ChatType,
     errors::HandlerError,
     event::ToServiceProvider,
-    filters::{Command, Text},
-    methods::SetMyCommands,
-    types::{BotCommand, BotCommandScopeAllPrivateChats},
+    filters::{ChatType as ChatTypeFilter, Command, Text, User as UserFilter},
+    methods::{DeleteWebhook, SetMyCommands, SetWebhook},
+    types::{BotCommand, BotCommandScopeAllPrivateChats, InputFile, Update},
     Bot, Dispatcher, Router,
 };
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
 use tracing::{event, Level};
-use tracing_subscriber::{fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter};
+use tracing_subscriber::{
+    filter::filter_fn, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter,
+    Layer as _,
+};
+
+/// Interval between updates of media gauges
+const MEDIA_STATS_POLLING_INTERVAL: Duration = Duration::from_secs(60);
 
 async fn set_my_commands(bot: Bot) -> Result<(), HandlerError> {
     let help_command = BotCommand::new("help", "Show help message");
     let source_command = BotCommand::new("source", "Show source of the bot");
     let gifs_command = BotCommand::new("gifs", "Get random gifs");
     let images_command = BotCommand::new("images", "Get random images");
+    let videos_command = BotCommand::new("videos", "Get random videos");
+    let genres_command = BotCommand::new("genres", "Browse genres");
+    let history_command = BotCommand::new("history", "Show viewed media");
+    let my_data_command = BotCommand::new("mydata", "Export your data");
+    let delete_me_command = BotCommand::new("deleteme", "Delete your data");
 
-    let private_chats = [help_command, source_command, gifs_command, images_command];
+    let private_chats = [
+        help_command,
+        source_command,
+        gifs_command,
+        images_command,
+        videos_command,
+        genres_command,
+        history_command,
+        my_data_command,
+        delete_me_command,
+    ];
 
     bot.send(SetMyCommands::new(private_chats.clone()).scope(BotCommandScopeAllPrivateChats {}))
         .await?;
@@ -42,28 +83,117 @@ async fn set_my_commands(bot: Bot) -> Result<(), HandlerError> {
     Ok(())
 }
 
-#[tokio::main(flavor = "current_thread")]
-#[allow(clippy::too_many_lines)]
-async fn main() {
-    let config = match read_config_from_env() {
+async fn set_webhook(bot: Bot, method: SetWebhook) -> Result<(), HandlerError> {
+    bot.send(method).await?;
+
+    event!(Level::INFO, "Webhook set");
+
+    Ok(())
+}
+
+async fn delete_webhook(bot: Bot) -> Result<(), HandlerError> {
+    bot.send(DeleteWebhook::new()).await?;
+
+    event!(Level::INFO, "Webhook deleted");
+
+    Ok(())
+}
+
+fn media_parser_scheduling(config: &MediaParserWorkerConfig) -> Scheduling {
+    Scheduling {
+        stock_target: config.stock_target,
+        active_users_period: config.active_users_period,
+        stock_polling_interval: config.stock_polling_interval,
+        duplicates_max_backoff: config.duplicates_max_backoff,
+    }
+}
+
+/// Builds the runtime with the flavor and count of worker threads from the config
+fn build_runtime(config: &RuntimeConfig) -> io::Result<Runtime> {
+    let mut builder = match config.flavor {
+        RuntimeFlavor::CurrentThread => RuntimeBuilder::new_current_thread(),
+        RuntimeFlavor::MultiThread => {
+            let mut builder = RuntimeBuilder::new_multi_thread();
+
+            if let Some(worker_threads) = config.worker_threads {
+                builder.worker_threads(worker_threads.get());
+            }
+
+            builder
+        }
+    };
+
+    builder.enable_all().build()
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let heartbeats = Arc::new(Heartbeats::new());
+
+    // Only the bot needs the token, so other commands can be run with the database config only
+    let require_bot_token = matches!(cli.command, None | Some(CliCommand::Serve));
+
+    let config = match read_config(cli.config.as_deref(), require_bot_token) {
         Ok(config) => {
+            // Logging level is applied only to logs, so metrics from spans don't depend on it
             tracing_subscriber::registry()
-                .with(fmt::layer())
-                .with(EnvFilter::from_env("LOGGING_LEVEL"))
+                .with(fmt::layer().with_filter(EnvFilter::from_env("LOGGING_LEVEL")))
+                .with(
+                    config
+                        .metrics
+                        .enabled
+                        .then(|| MetricsLayer.with_filter(filter_fn(MetricsLayer::is_tracked))),
+                )
+                .with(config.health.enabled.then(|| {
+                    HealthLayer::new(heartbeats.clone())
+                        .with_filter(filter_fn(HealthLayer::is_tracked))
+                }))
                 .init();
 
-            event!(Level::DEBUG, "Config loaded from env");
+            event!(Level::DEBUG, "Config loaded");
 
             config
         }
         Err(err) => {
-            eprintln!("Error reading config from env: {err}");
+            eprintln!("Error reading config: {err}");
+
+            std::process::exit(1);
+        }
+    };
+
+    let runtime = match build_runtime(&config.runtime) {
+        Ok(runtime) => {
+            event!(
+                Level::DEBUG,
+                flavor = ?config.runtime.flavor,
+                worker_threads = ?config.runtime.worker_threads,
+                "Runtime built",
+            );
+
+            runtime
+        }
+        Err(err) => {
+            eprintln!("Error building runtime: {err}");
 
             std::process::exit(1);
         }
     };
 
-    let pool = match PgPool::connect(&config.database.get_postgres_url()).await {
+    runtime.block_on(run(cli, config, heartbeats));
+}
+
+/// Runs the command of the CLI
+async fn run(cli: Cli, config: Config, heartbeats: Arc<Heartbeats>) {
+    let pool_options = PgPoolOptions::new()
+        .max_connections(config.database.max_connections)
+        .min_connections(config.database.min_connections)
+        .acquire_timeout(config.database.acquire_timeout);
+
+    let pool = match pool_options
+        .connect(&config.database.get_postgres_url())
+        .await
+    {
         Ok(pool) => {
             event!(Level::DEBUG, "Database pool created");
 
@@ -76,6 +206,108 @@ async fn main() {
         }
     };
 
+    let sources_reloader = match SourcesReloader::new(config.sources.config_path.clone()).await {
+        Ok(sources_reloader) => {
+            event!(Level::DEBUG, "Media parser sources loaded");
+
+            Arc::new(sources_reloader)
+        }
+        Err(err) => {
+            eprintln!("Error loading media parser sources: {err}");
+
+            std::process::exit(1);
+        }
+    };
+
+    let command = cli.command.unwrap_or_default();
+
+    if matches!(command, CliCommand::Serve | CliCommand::Worker) && config.database.run_migrations {
+        match migrations::run(&pool).await {
+            Ok(()) => {
+                event!(Level::INFO, "Migrations applied");
+            }
+            Err(err) => {
+                eprintln!("Error applying migrations: {err}");
+
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let result = match command {
+        CliCommand::Serve => {
+            serve(config, pool, heartbeats, sources_reloader).await;
+
+            Ok(())
+        }
+        CliCommand::Worker => {
+            let media_verifier = config.media_verifier.start_verifier.then(|| {
+                MediaVerifier::new(
+                    config.rate_limits.media_verifier_requests_per_second,
+                    config.media_verifier.recheck_interval,
+                )
+            });
+
+            let media_deduplicator = config.media_parser_worker.deduplicate_media.then(|| {
+                MediaDeduplicator::new(
+                    config.rate_limits.media_deduplication_requests_per_second,
+                    config.media_parser_worker.max_hash_distance,
+                )
+            });
+
+            let media_validator = config.media_parser_worker.validate_media.then(|| {
+                MediaValidator::new(config.rate_limits.media_validation_requests_per_second)
+            });
+
+            commands::worker(
+                pool,
+                sources_reloader,
+                config.sources.reload_interval,
+                media_parser_scheduling(&config.media_parser_worker),
+                config.shutdown.timeout,
+                media_validator,
+                media_deduplicator,
+                media_verifier,
+            )
+            .await;
+
+            Ok(())
+        }
+        CliCommand::Migrate { action } => {
+            commands::migrate(&pool, action).await.map_err(Into::into)
+        }
+        CliCommand::Export { output } => {
+            commands::export(SqlxUnitOfWorkFactory::new(pool), output.as_deref()).await
+        }
+        CliCommand::Import { input } => {
+            commands::import(SqlxUnitOfWorkFactory::new(pool), input.as_deref()).await
+        }
+        CliCommand::Users { action } => {
+            commands::users(SqlxUnitOfWorkFactory::new(pool), action).await
+        }
+        CliCommand::Media { action } => {
+            let sources = sources_reloader.sources();
+            let provided_genres = sources.genres();
+
+            commands::media(SqlxUnitOfWorkFactory::new(pool), &provided_genres, action).await
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error running command: {err}");
+
+        std::process::exit(1);
+    }
+}
+
+/// Runs the bot until it's stopped
+#[allow(clippy::too_many_lines)]
+async fn serve(
+    config: Config,
+    pool: PgPool,
+    heartbeats: Arc<Heartbeats>,
+    sources_reloader: Arc<SourcesReloader>,
+) {
     let mut main_router = Router::new("main");
 
     main_router
@@ -89,15 +321,71 @@ async fn main() {
         .outer_middlewares
         .register(ACLMiddleware::<SqlxUnitOfWorkFactory<Postgres>>::new());
 
-    let nekos_best = NekosBest::default();
-    let waifu_pics = WaifuPics::default();
+    // Commands of admins are sent to the worker, if it's started in the same process
+    let media_parser_controller = MediaParserController::default();
 
-    main_router.message.inner_middlewares.register(
-        MediaParserSourcesMiddleware::default()
-            .source(nekos_best.clone())
-            .source(waifu_pics.clone()),
+    let media_parser_sources_middleware = MediaParserSourcesMiddleware::new(
+        sources_reloader.clone(),
+        media_parser_controller.clone(),
     );
 
+    main_router
+        .message
+        .inner_middlewares
+        .register(media_parser_sources_middleware.clone());
+    main_router
+        .callback_query
+        .inner_middlewares
+        .register(media_parser_sources_middleware);
+
+    // The worker and in-flight deliveries are finished before the connection pool is closed
+    let shutdown = Shutdown::default();
+
+    let shutdown_middleware = ShutdownMiddleware::new(shutdown.clone());
+
+    main_router
+        .message
+        .inner_middlewares
+        .register(shutdown_middleware.clone());
+    main_router
+        .callback_query
+        .inner_middlewares
+        .register(shutdown_middleware);
+
+    if config.metrics.enabled {
+        let handler_metrics_middleware = HandlerMetricsMiddleware::default().commands([
+            "start",
+            "help",
+            "source",
+            "about",
+            "gifs",
+            "images",
+            "videos",
+            "genres",
+            "stats",
+            "statistics",
+            "history",
+            "mydata",
+            "deleteme",
+            "settings",
+            "sources",
+            "reload",
+            "pause",
+            "resume",
+            "refresh",
+            "interval",
+        ]);
+
+        main_router
+            .message
+            .inner_middlewares
+            .register(handler_metrics_middleware.clone());
+        main_router
+            .callback_query
+            .inner_middlewares
+            .register(handler_metrics_middleware);
+    }
+
     let mut user_router = Router::new("users");
 
     user_router
@@ -116,10 +404,54 @@ async fn main() {
         .message
         .register(handlers::media::images)
         .filter(Command::one("images"));
+    user_router
+        .message
+        .register(handlers::media::videos)
+        .filter(Command::one("videos"));
+    user_router
+        .message
+        .register(handlers::media::genres)
+        .filter(Command::one("genres"));
+    user_router
+        .callback_query
+        .register(handlers::media::genres_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single("genres"));
     user_router
         .message
         .register(handlers::stats::stats::<SqlxUnitOfWorkFactory<Postgres>>)
         .filter(Command::many(["stats", "statistics"]));
+    user_router
+        .message
+        .register(handlers::history::history::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("history"));
+    user_router
+        .callback_query
+        .register(handlers::history::history_page_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single(
+            handlers::history::HISTORY_PAGE_PREFIX,
+        ));
+    user_router
+        .callback_query
+        .register(handlers::history::history_send_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single("history send "));
+    user_router
+        .callback_query
+        .register(handlers::history::history_forget_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single("history forget "));
+    user_router
+        .message
+        .register(handlers::user::my_data::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("mydata"))
+        .filter(ChatTypeFilter::one(ChatType::Private));
+    user_router
+        .message
+        .register(handlers::user::delete_me)
+        .filter(Command::one("deleteme"))
+        .filter(ChatTypeFilter::one(ChatType::Private));
+    user_router
+        .callback_query
+        .register(handlers::user::delete_me_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single("user delete_me_"));
     user_router
         .message
         .register(handlers::user::settings)
@@ -137,61 +469,306 @@ async fn main() {
         .register(
             handlers::user::update_age_restriction_callback::<SqlxUnitOfWorkFactory<Postgres>>,
         )
-        .filter(Text::many([
-            "user enable_show_nsfw",
-            "user disable_show_nsfw",
-        ]));
+        .filter(Text::starts_with_single("user max_age_restriction "));
+    user_router
+        .callback_query
+        .register(handlers::media::genre_callback::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Text::starts_with_single("media next "));
     user_router
         .message
         .register(handlers::media::genre::<SqlxUnitOfWorkFactory<Postgres>>)
         .filter(Text::starts_with_single("/"));
 
+    let mut admin_router = Router::new("admins");
+
+    admin_router
+        .message
+        .register(handlers::admin::source_stats::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("sources"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+    admin_router
+        .message
+        .register(handlers::admin::reload_sources)
+        .filter(Command::one("reload"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+    admin_router
+        .message
+        .register(handlers::admin::pause_source::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("pause"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+    admin_router
+        .message
+        .register(handlers::admin::resume_source::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("resume"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+    admin_router
+        .message
+        .register(handlers::admin::refresh_source)
+        .filter(Command::one("refresh"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+    admin_router
+        .message
+        .register(handlers::admin::source_polling_interval::<SqlxUnitOfWorkFactory<Postgres>>)
+        .filter(Command::one("interval"))
+        .filter(UserFilter::ids(config.bot.admin_ids.clone()));
+
+    // Admin router is included before user router, because user router handles all unknown commands as genres
+    main_router.include(admin_router);
     main_router.include(user_router);
 
+    // Sources are reloaded even without the worker, because handlers use their genres
+    main_router.startup.register(
+        |sources_reloader, reload_interval| async move {
+            tokio::spawn(sources::run_watching(sources_reloader, reload_interval));
+
+            Ok(())
+        },
+        (sources_reloader.clone(), config.sources.reload_interval),
+    );
+
     if config.media_parser_worker.start_worker {
         main_router.startup.register(
-            |nekos_best, waifu_pics, pool| async {
-                tokio::spawn(worker::run_pollings(
-                    nekos_best,
-                    waifu_pics,
+            |sources_reloader: Arc<SourcesReloader>,
+             pool,
+             media_parser_controller,
+             scheduling,
+             shutdown: Shutdown| async move {
+                shutdown.spawn(worker::run_pollings(
+                    sources_reloader.subscribe(),
                     SqlxUnitOfWorkFactory::new(pool),
+                    media_parser_controller,
+                    scheduling,
+                    shutdown.token().clone(),
                 ));
 
                 Ok(())
             },
-            (nekos_best, waifu_pics, pool.clone()),
+            (
+                sources_reloader,
+                pool.clone(),
+                media_parser_controller,
+                media_parser_scheduling(&config.media_parser_worker),
+                shutdown.clone(),
+            ),
         );
+
+        if config.media_parser_worker.validate_media {
+            let media_validator =
+                MediaValidator::new(config.rate_limits.media_validation_requests_per_second);
+
+            main_router.startup.register(
+                |media_validator, pool, shutdown: Shutdown| async move {
+                    let token = shutdown.token().clone();
+
+                    shutdown.spawn(async move {
+                        if let Err(err) = media_validator::run_validation(
+                            media_validator,
+                            SqlxUnitOfWorkFactory::new(pool),
+                            token,
+                        )
+                        .await
+                        {
+                            event!(Level::ERROR, %err, "Media validator stopped with error");
+                        }
+                    });
+
+                    Ok(())
+                },
+                (media_validator, pool.clone(), shutdown.clone()),
+            );
+        }
+
+        if config.media_parser_worker.deduplicate_media {
+            let media_deduplicator = MediaDeduplicator::new(
+                config.rate_limits.media_deduplication_requests_per_second,
+                config.media_parser_worker.max_hash_distance,
+            );
+
+            main_router.startup.register(
+                |media_deduplicator, pool, shutdown: Shutdown| async move {
+                    let token = shutdown.token().clone();
+
+                    shutdown.spawn(async move {
+                        if let Err(err) = media_deduplicator::run_deduplication(
+                            media_deduplicator,
+                            SqlxUnitOfWorkFactory::new(pool),
+                            token,
+                        )
+                        .await
+                        {
+                            event!(Level::ERROR, %err, "Media deduplicator stopped with error");
+                        }
+                    });
+
+                    Ok(())
+                },
+                (media_deduplicator, pool.clone(), shutdown.clone()),
+            );
+        }
     } else {
         event!(Level::WARN, "Media parser worker disabled. To enable it set `START_MEDIA_PARSER_WORKER` to `true` in env");
     }
 
-    // Shutdown the connection pool
+    if config.media_verifier.start_verifier {
+        let media_verifier = MediaVerifier::new(
+            config.rate_limits.media_verifier_requests_per_second,
+            config.media_verifier.recheck_interval,
+        );
+
+        main_router.startup.register(
+            |media_verifier, pool, shutdown: Shutdown| async move {
+                let token = shutdown.token().clone();
+
+                shutdown.spawn(async move {
+                    if let Err(err) = media_verifier::run_verification(
+                        media_verifier,
+                        SqlxUnitOfWorkFactory::new(pool),
+                        token,
+                    )
+                    .await
+                    {
+                        event!(Level::ERROR, %err, "Media verifier stopped with error");
+                    }
+                });
+
+                Ok(())
+            },
+            (media_verifier, pool.clone(), shutdown.clone()),
+        );
+    }
+
+    if config.metrics.enabled {
+        main_router.startup.register(
+            |address, pool, shutdown: Shutdown| async move {
+                let token = shutdown.token().clone();
+
+                shutdown.spawn(async move {
+                    if let Err(err) = metrics::run_server(address, token).await {
+                        event!(Level::ERROR, %err, "Metrics server stopped with error");
+                    }
+                });
+                shutdown.spawn(metrics::run_media_stats_polling(
+                    SqlxUnitOfWorkFactory::new(pool),
+                    MEDIA_STATS_POLLING_INTERVAL,
+                    shutdown.token().clone(),
+                ));
+
+                Ok(())
+            },
+            (config.metrics.address, pool.clone(), shutdown.clone()),
+        );
+    }
+
+    if config.health.enabled {
+        let readiness = Readiness {
+            heartbeats,
+            uow_factory: SqlxUnitOfWorkFactory::new(pool.clone()),
+            max_poll_age: config.health.max_poll_age,
+            max_worker_inactivity: config.health.max_worker_inactivity,
+            // There are no polls in webhook mode
+            check_polling: !config.webhook.enabled,
+            check_worker: config.media_parser_worker.start_worker,
+        };
+
+        main_router.startup.register(
+            |address, readiness, shutdown: Shutdown| async move {
+                let token = shutdown.token().clone();
+
+                shutdown.spawn(async move {
+                    if let Err(err) = health::run_server(address, readiness, token).await {
+                        event!(Level::ERROR, %err, "Health server stopped with error");
+                    }
+                });
+
+                Ok(())
+            },
+            (config.health.address, readiness, shutdown.clone()),
+        );
+    }
+
+    // Wait for the worker, background tasks and in-flight deliveries and shutdown the connection pool
     main_router.shutdown.register(
-        |pool| async move {
-            Pool::close(&pool).await;
+        |pool, shutdown: Shutdown, timeout| async move {
+            // Tasks, which didn't finish, can still use connections, so the pool is closed only after all of them
+            if shutdown.run(timeout).await {
+                Pool::close(&pool).await;
+            }
             Ok(())
         },
-        (pool,),
+        (pool, shutdown, config.shutdown.timeout),
     );
 
     let bot = Bot::new(config.bot.token);
+    let allowed_updates = main_router.resolve_used_update_types();
 
     main_router
         .startup
         .register(set_my_commands, (bot.clone(),));
 
+    if config.webhook.enabled {
+        let set_webhook_method = SetWebhook::new(config.webhook.url)
+            .secret_token_option(config.webhook.secret_token.clone())
+            .certificate_option(config.webhook.certificate_path.map(InputFile::fs))
+            .allowed_updates(allowed_updates.iter().map(ToString::to_string));
+
+        main_router
+            .startup
+            .register(set_webhook, (bot.clone(), set_webhook_method));
+        main_router
+            .shutdown
+            .register(delete_webhook, (bot.clone(),));
+    }
+
     let dispatcher = Dispatcher::builder()
-        .bot(bot)
-        .allowed_updates(main_router.resolve_used_update_types())
+        .bot(bot.clone())
+        .allowed_updates(allowed_updates)
         .router(main_router)
         .build();
 
-    match dispatcher
-        .to_service_provider_default()
-        .unwrap()
-        .run_polling()
-        .await
-    {
+    let dispatcher_service = dispatcher.to_service_provider_default().unwrap();
+
+    if !config.webhook.enabled {
+        match dispatcher_service.run_polling().await {
+            Ok(()) => {
+                event!(Level::WARN, "Bot stopped");
+            }
+            Err(err) => {
+                event!(Level::ERROR, %err, "Bot stopped with error");
+            }
+        }
+
+        return;
+    }
+
+    let dispatcher_service = Arc::new(dispatcher_service);
+
+    if let Err(err) = dispatcher_service.emit_startup().await {
+        event!(Level::ERROR, %err, "Error emitting startup");
+
+        std::process::exit(1);
+    }
+
+    let webhook_router = webhook::router(
+        &config.webhook.path,
+        config.webhook.secret_token.map(Into::into),
+        {
+            let dispatcher_service = dispatcher_service.clone();
+
+            move |update: Update| {
+                let dispatcher_service = dispatcher_service.clone();
+                let bot = bot.clone();
+
+                async move {
+                    dispatcher_service
+                        .feed_update(bot, update)
+                        .await
+                        .map(|_| ())
+                }
+            }
+        },
+    );
+
+    match webhook::run_server(config.webhook.address, webhook_router, shutdown::signal()).await {
         Ok(()) => {
             event!(Level::WARN, "Bot stopped");
         }
@@ -199,4 +776,8 @@ async fn main() {
             event!(Level::ERROR, %err, "Bot stopped with error");
         }
     }
+
+    if let Err(err) = dispatcher_service.emit_shutdown().await {
+        event!(Level::ERROR, %err, "Error emitting shutdown");
+    }
 }
@@ -1,7 +1,11 @@
 pub mod acl;
 pub mod database;
+pub mod handler_metrics;
 pub mod media_parser_sources;
+pub mod shutdown;
 
 pub use acl::ACL;
 pub use database::Database;
+pub use handler_metrics::HandlerMetrics;
 pub use media_parser_sources::MediaParserSources;
+pub use shutdown::Shutdown;
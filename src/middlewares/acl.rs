@@ -6,7 +6,7 @@ use crate::{
         },
         user::dto::{CreateUser, GetUserByTgId},
     },
-    domain::user::entities::User as UserEntity,
+    domain::{media_parser::value_objects::AgeRestriction, user::entities::User as UserEntity},
 };
 
 use anyhow::anyhow;
@@ -112,7 +112,7 @@ where
 
         let db_user_id = Uuid::new_v4();
 
-        let create_user = CreateUser::new(&db_user_id, user_id, None, None);
+        let create_user = CreateUser::new(&db_user_id, user_id, None, AgeRestriction::Sfw.as_str());
 
         let create_user_result = uow
             .user_repo()
@@ -141,7 +141,7 @@ where
             id: *create_user.id(),
             tg_id: create_user.tg_id(),
             language_code: create_user.language_code().map(ToOwned::to_owned),
-            show_nsfw: create_user.show_nsfw(),
+            max_age_restriction: create_user.max_age_restriction().to_owned(),
             created: OffsetDateTime::now_utc(), // approximate time
         };
 
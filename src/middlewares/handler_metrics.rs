@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use metrics::{counter, histogram};
+use std::time::Instant;
+use telers::{
+    errors::EventErrorKind,
+    event::{
+        telegram::{HandlerRequest, HandlerResponse},
+        EventReturn,
+    },
+    middlewares::{InnerMiddleware, Next},
+};
+
+/// Prefix of data of history page callback queries, check `handlers::history::HISTORY_PAGE_PREFIX`
+const HISTORY_PAGE_PREFIX: &str = "h:";
+/// First words of data of known callback queries
+const CALLBACK_DATA_WORDS: [&str; 4] = ["media", "genres", "user", "history"];
+
+/// Middleware, which counts handled updates and measures duration of their handling.
+/// Handlers are named by commands for messages and by the first word of data for callback queries.
+/// Commands, which aren't known, are named as `genre`, because all unknown commands are handled as genres.
+/// Other texts, which aren't known, are named as `other`, so count of label values is limited.
+#[derive(Default, Clone)]
+pub struct HandlerMetrics {
+    commands: Vec<&'static str>,
+}
+
+impl HandlerMetrics {
+    pub fn commands(mut self, commands: impl IntoIterator<Item = &'static str>) -> Self {
+        self.commands.extend(commands);
+        self
+    }
+
+    fn handler_name(&self, text: Option<&str>) -> String {
+        let Some(text) = text else {
+            return "unknown".to_owned();
+        };
+
+        if let Some(command) = text.strip_prefix('/') {
+            let command = command
+                .split([' ', '@'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+
+            if self.commands.contains(&command.as_str()) {
+                command
+            } else {
+                "genre".to_owned()
+            }
+        } else if text.starts_with(HISTORY_PAGE_PREFIX) {
+            "history_page".to_owned()
+        } else {
+            let word = text.split(' ').next().unwrap_or_default();
+
+            if CALLBACK_DATA_WORDS.contains(&word) {
+                word.to_owned()
+            } else {
+                "other".to_owned()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl InnerMiddleware for HandlerMetrics {
+    async fn call(
+        &self,
+        request: HandlerRequest,
+        next: Next,
+    ) -> Result<HandlerResponse, EventErrorKind> {
+        let handler = self.handler_name(request.update.text());
+        let start = Instant::now();
+
+        let result = next(request).await;
+
+        let outcome = match result {
+            Ok(HandlerResponse {
+                handler_result: Ok(EventReturn::Finish),
+                ..
+            }) => "finish",
+            Ok(HandlerResponse {
+                handler_result: Ok(EventReturn::Skip),
+                ..
+            }) => "skip",
+            Ok(HandlerResponse {
+                handler_result: Ok(EventReturn::Cancel),
+                ..
+            }) => "cancel",
+            Ok(HandlerResponse {
+                handler_result: Err(_),
+                ..
+            })
+            | Err(_) => "error",
+        };
+
+        counter!("updates_handled_total", "handler" => handler.clone(), "outcome" => outcome)
+            .increment(1);
+        histogram!("update_handling_duration_seconds", "handler" => handler)
+            .record(start.elapsed());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HandlerMetrics;
+
+    #[test]
+    fn test_handler_name() {
+        let middleware = HandlerMetrics::default().commands(["start", "gifs"]);
+
+        assert_eq!(middleware.handler_name(Some("/start")), "start");
+        assert_eq!(middleware.handler_name(Some("/gifs@bot 5")), "gifs");
+        assert_eq!(middleware.handler_name(Some("/GIFS")), "gifs");
+        assert_eq!(middleware.handler_name(Some("/waifu 5")), "genre");
+        assert_eq!(middleware.handler_name(Some("media next 1 waifu")), "media");
+        assert_eq!(middleware.handler_name(Some("genres")), "genres");
+        assert_eq!(middleware.handler_name(Some("user settings")), "user");
+        assert_eq!(
+            middleware.handler_name(Some("history send 0190a5b6")),
+            "history"
+        );
+        assert_eq!(
+            middleware.handler_name(Some("h:AZClts7XdCaF0o1SMXsB5Q waifu img sfw")),
+            "history_page"
+        );
+        assert_eq!(middleware.handler_name(Some("hello there")), "other");
+        assert_eq!(middleware.handler_name(Some("")), "other");
+        assert_eq!(middleware.handler_name(None), "unknown");
+    }
+}
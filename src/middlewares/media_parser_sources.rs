@@ -1,4 +1,4 @@
-use crate::application::media_parser::traits::Source;
+use crate::infrastructure::media_parser::{Controller, SourcesReloader};
 
 use async_trait::async_trait;
 use std::sync::Arc;
@@ -8,18 +8,20 @@ use telers::{
     middlewares::{InnerMiddleware, Next},
 };
 
-#[derive(Default)]
+/// Provides the current media parser sources, their reloader and controller of their workers to handlers.
+/// Sources are taken for each request, so handlers see genres of the last reload.
+#[derive(Clone)]
 pub struct MediaParserSources {
-    sources: Vec<Arc<dyn Source>>,
+    reloader: Arc<SourcesReloader>,
+    controller: Controller,
 }
 
 impl MediaParserSources {
-    pub fn source<S>(mut self, source: impl Into<Arc<S>>) -> Self
-    where
-        S: Source + 'static,
-    {
-        self.sources.push(source.into());
-        self
+    pub fn new(reloader: Arc<SourcesReloader>, controller: Controller) -> Self {
+        Self {
+            reloader,
+            controller,
+        }
     }
 }
 
@@ -30,9 +32,17 @@ impl InnerMiddleware for MediaParserSources {
         request: HandlerRequest,
         next: Next,
     ) -> Result<HandlerResponse, EventErrorKind> {
+        request.context.insert(
+            "media_parser_sources",
+            Box::new(self.reloader.sources().all()),
+        );
+        request.context.insert(
+            "media_parser_sources_reloader",
+            Box::new(self.reloader.clone()),
+        );
         request
             .context
-            .insert("media_parser_sources", Box::new(self.sources.clone()));
+            .insert("media_parser_controller", Box::new(self.controller.clone()));
 
         next(request).await
     }
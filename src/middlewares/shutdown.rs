@@ -0,0 +1,38 @@
+use crate::infrastructure::shutdown::Shutdown as ShutdownInfra;
+
+use async_trait::async_trait;
+use telers::{
+    errors::EventErrorKind,
+    event::telegram::{HandlerRequest, HandlerResponse},
+    middlewares::{InnerMiddleware, Next},
+};
+
+/// Provides the shutdown to handlers and tracks their calls,
+/// so the shutdown waits for in-flight deliveries before the connection pool is closed.
+#[derive(Clone)]
+pub struct Shutdown {
+    shutdown: ShutdownInfra,
+}
+
+impl Shutdown {
+    pub const fn new(shutdown: ShutdownInfra) -> Self {
+        Self { shutdown }
+    }
+}
+
+#[async_trait]
+impl InnerMiddleware for Shutdown {
+    async fn call(
+        &self,
+        request: HandlerRequest,
+        next: Next,
+    ) -> Result<HandlerResponse, EventErrorKind> {
+        let _delivery = self.shutdown.delivery();
+
+        request
+            .context
+            .insert("shutdown", Box::new(self.shutdown.clone()));
+
+        next(request).await
+    }
+}
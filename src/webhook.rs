@@ -0,0 +1,203 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use serde::de::DeserializeOwned;
+use std::{fmt::Display, future::Future, io, net::SocketAddr, sync::Arc};
+use tokio::net::TcpListener;
+use tracing::{event, instrument, Level};
+
+/// Header with the secret token, which Telegram sends in every webhook request
+pub const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+struct Webhook<FeedUpdate> {
+    secret_token: Option<Box<str>>,
+    feed_update: FeedUpdate,
+}
+
+/// Checks if the request has the same secret token as the webhook.
+/// If the webhook doesn't have a secret token, all requests are valid.
+fn is_secret_token_valid(headers: &HeaderMap, secret_token: Option<&str>) -> bool {
+    let Some(secret_token) = secret_token else {
+        return true;
+    };
+
+    headers
+        .get(SECRET_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value == secret_token)
+}
+
+/// Handles the update from the body of the request.
+/// The body is parsed only after the secret token is checked.
+/// Updates, which can't be parsed, are skipped with 200, because Telegram resends the update on errors
+/// and doesn't send next updates until it's accepted.
+async fn handle_update<Update, FeedUpdate, Fut, E>(
+    State(webhook): State<Arc<Webhook<FeedUpdate>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode
+where
+    Update: DeserializeOwned,
+    FeedUpdate: Fn(Update) -> Fut,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    if !is_secret_token_valid(&headers, webhook.secret_token.as_deref()) {
+        event!(Level::WARN, "Webhook request with invalid secret token");
+
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let update = match serde_json::from_slice(&body) {
+        Ok(update) => update,
+        Err(err) => {
+            event!(Level::ERROR, %err, "Error parsing update, it's skipped");
+
+            return StatusCode::OK;
+        }
+    };
+
+    let fut = (webhook.feed_update)(update);
+
+    // Telegram waits for the response before sending the next update,
+    // so the update is handled in background to not block other updates
+    tokio::spawn(async move {
+        if let Err(err) = fut.await {
+            event!(Level::ERROR, %err, "Error handling update");
+        }
+    });
+
+    StatusCode::OK
+}
+
+/// Creates a router, which accepts updates from Telegram on the path
+/// # Arguments
+/// * `path` - Path of the webhook, for example, `/webhook`
+/// * `secret_token` - Secret token, which is set in `setWebhook`. Requests with other tokens are rejected.
+/// * `feed_update` - Function, which handles the update
+pub fn router<Update, FeedUpdate, Fut, E>(
+    path: &str,
+    secret_token: Option<Box<str>>,
+    feed_update: FeedUpdate,
+) -> Router
+where
+    Update: DeserializeOwned + Send + 'static,
+    FeedUpdate: Fn(Update) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display + 'static,
+{
+    Router::new()
+        .route(path, post(handle_update::<Update, FeedUpdate, Fut, E>))
+        .with_state(Arc::new(Webhook {
+            secret_token,
+            feed_update,
+        }))
+}
+
+/// Run HTTP listener, which accepts updates from Telegram, until the shutdown signal
+/// # Errors
+/// Returns error if the listener can't be bound
+#[instrument(skip_all, fields(%address))]
+pub async fn run_server(
+    address: SocketAddr,
+    router: Router,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> Result<(), io::Error> {
+    let listener = TcpListener::bind(address).await?;
+
+    event!(Level::INFO, "Webhook server started");
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{router, SECRET_TOKEN_HEADER};
+
+    use telers::types::Update;
+    use tokio::{net::TcpListener, sync::mpsc};
+
+    const UPDATE: &str = r#"{
+        "update_id": 1,
+        "message": {
+            "message_id": 2,
+            "date": 0,
+            "chat": {"id": 3, "type": "private", "first_name": "Test"},
+            "from": {"id": 3, "is_bot": false, "first_name": "Test"},
+            "text": "/start"
+        }
+    }"#;
+
+    #[tokio::test]
+    async fn test_webhook() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let router = router("/webhook", Some("secret".into()), move |update: Update| {
+            let sender = sender.clone();
+
+            async move { sender.send(update).map_err(|err| err.to_string()) }
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(UPDATE)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 401);
+
+        // Invalid body isn't parsed without the secret token
+        let response = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body("not an update")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 401);
+
+        // Update, which can't be parsed, is skipped, so Telegram doesn't resend it
+        let response = client
+            .post(&url)
+            .header(SECRET_TOKEN_HEADER, "secret")
+            .body("not an update")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+
+        let response = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header(SECRET_TOKEN_HEADER, "secret")
+            .body(UPDATE)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+
+        let update = serde_json::to_value(receiver.recv().await.unwrap()).unwrap();
+
+        assert_eq!(update["update_id"], 1);
+        assert_eq!(update["message"]["text"], "/start");
+        assert!(receiver.try_recv().is_err());
+    }
+}